@@ -3,7 +3,7 @@
 use std::{
     fmt,
     fs::File,
-    io::{self, BufReader, IsTerminal, Read, Write},
+    io::{self, BufRead, BufReader, IsTerminal, Read, Write},
     path::{Path, PathBuf},
     process,
     str::FromStr,
@@ -11,8 +11,9 @@ use std::{
 
 use anyhow::Context;
 use clap::{Parser, Subcommand, ValueEnum};
+use flate2::read::GzDecoder;
 use term_transcript::{
-    test::{MatchKind, TestConfig, TestOutputConfig, TestStats},
+    test::{MatchKind, ParseError, Parsed, TestConfig, TestOutputConfig, TestStats},
     traits::SpawnShell,
     Transcript,
 };
@@ -47,6 +48,12 @@ enum Command {
         shell: ShellArgs,
         /// Inputs to supply to the shell.
         inputs: Vec<String>,
+        /// Checks that programs invoked by the inputs can be located in `PATH`
+        /// before spawning the shell. Inputs starting with shell keywords or builtins
+        /// (e.g., `for` or `cd`) are skipped; shell functions and aliases are reported as missing.
+        /// The check is skipped for shells other than `sh` / `bash`.
+        #[arg(long)]
+        check: bool,
         #[command(flatten)]
         template: TemplateArgs,
     },
@@ -55,7 +62,8 @@ enum Command {
     Test {
         #[command(flatten)]
         shell: ShellArgs,
-        /// Paths to the SVG file(s) to test.
+        /// Paths to the SVG file(s) to test. Files with the `.gz` extension are treated
+        /// as gzip-compressed.
         #[arg(name = "svg")]
         svg_paths: Vec<PathBuf>,
         /// Prints terminal output for passed user inputs.
@@ -64,20 +72,61 @@ enum Command {
         /// Matches coloring of the terminal output, rather than matching only text.
         #[arg(long, short = 'p')]
         precise: bool,
+        /// Number of unchanged lines to display around changed lines in output diffs.
+        /// If not specified, the entire output is displayed.
+        #[arg(long, value_name = "LINES")]
+        context: Option<usize>,
+        /// Working directory to execute inputs in. If not specified, inputs are executed
+        /// in the current directory.
+        #[arg(long, value_name = "DIR")]
+        cwd: Option<PathBuf>,
         /// Controls coloring of the output.
         #[arg(long, short = 'c', default_value = "auto", value_enum, env)]
         color: ColorPreference,
     },
 
+    /// Checks that previously captured SVG snapshots are well-formed and self-consistent
+    /// without executing any commands.
+    Validate {
+        /// Paths to the SVG file(s) to validate. Files with the `.gz` extension are treated
+        /// as gzip-compressed.
+        #[arg(name = "svg")]
+        svg_paths: Vec<PathBuf>,
+        /// Controls coloring of the output.
+        #[arg(long, short = 'c', default_value = "auto", value_enum, env)]
+        color: ColorPreference,
+    },
+
+    /// Merges previously captured SVG snapshots into a single SVG. Template options are not
+    /// recovered from the snapshots; instead, they are taken from the command-line args
+    /// (or default options are used).
+    Merge {
+        /// Paths to the SVG files to merge, in the order of their interactions in the merged SVG.
+        #[arg(name = "svg", required = true)]
+        svg_paths: Vec<PathBuf>,
+        #[command(flatten)]
+        template: TemplateArgs,
+    },
+
     /// Prints a previously saved SVG file to stdout with the captured coloring (unless
     /// the coloring of the output is switched off).
     Print {
         /// Path to the SVG file to output. If set to `-`, the SVG will be read from stdin.
+        /// Files with the `.gz` extension are treated as gzip-compressed.
         #[arg(name = "svg")]
         svg_path: PathBuf,
         /// Controls coloring of the output.
         #[arg(long, short = 'c', default_value = "auto", value_enum, env)]
         color: ColorPreference,
+        /// Omits headers separating inputs and outputs, as well as empty lines
+        /// between interactions.
+        #[arg(long, conflicts_with = "header_format")]
+        no_headers: bool,
+        /// Format of headers separating inputs and outputs. `{kind}` is replaced
+        /// with `Input` or `Output`, and `{index}` with the 1-based interaction index.
+        /// By default, headers look like `----------  Input #1 ----------`.
+        #[arg(long, value_name = "FORMAT")]
+        header_format: Option<String>,
     },
 }
 
@@ -104,9 +153,16 @@ impl Command {
             Self::Exec {
                 shell,
                 inputs,
+                check,
                 template,
             } => {
-                let inputs = inputs.into_iter().map(|input| template.create_input(input));
+                let inputs: Vec<_> = inputs
+                    .into_iter()
+                    .map(|input| template.create_input(input))
+                    .collect();
+                if check {
+                    shell.verify_commands(&inputs)?;
+                }
                 let transcript = shell.create_transcript(inputs)?;
                 template.render(&transcript)?;
             }
@@ -116,6 +172,8 @@ impl Command {
                 svg_paths,
                 precise,
                 verbose,
+                context,
+                cwd,
                 color,
             } => {
                 let match_kind = if precise {
@@ -133,6 +191,12 @@ impl Command {
                     })
                     .with_match_kind(match_kind)
                     .with_color_choice(color.into());
+                if let Some(context) = context {
+                    test_config = test_config.with_diff_context(context);
+                }
+                if let Some(cwd) = cwd {
+                    test_config = test_config.with_working_dir(cwd);
+                }
 
                 let mut totals = FullTestStats::default();
                 let out = StandardStream::stdout(color.into());
@@ -145,7 +209,7 @@ impl Command {
                             totals.errors += stats.errors(match_kind);
                         }
                         Err(err) => {
-                            Self::report_failure(&out, svg_path, err)?;
+                            Self::report_failure(&out, "testing", svg_path, err)?;
                             totals.failures += 1;
                         }
                     }
@@ -158,7 +222,45 @@ impl Command {
                 }
             }
 
-            Self::Print { svg_path, color } => Self::print_file(&svg_path, color)?,
+            Self::Validate { svg_paths, color } => {
+                let out = StandardStream::stdout(color.into());
+                let mut has_failures = false;
+                for svg_path in &svg_paths {
+                    if let Err(err) = Self::validate_file(svg_path) {
+                        Self::report_failure(&out, "validating", svg_path, err)?;
+                        has_failures = true;
+                    }
+                }
+                if has_failures {
+                    process::exit(1);
+                }
+            }
+
+            Self::Merge {
+                svg_paths,
+                template,
+            } => {
+                let transcripts = svg_paths
+                    .iter()
+                    .map(PathBuf::as_path)
+                    .map(Self::read_file)
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                template.render(&Transcript::concat(transcripts))?;
+            }
+
+            Self::Print {
+                svg_path,
+                color,
+                no_headers,
+                header_format,
+            } => {
+                let headers = if no_headers {
+                    None
+                } else {
+                    Some(header_format.as_deref())
+                };
+                Self::print_file(&svg_path, color, headers)?;
+            }
         }
         Ok(())
     }
@@ -167,14 +269,41 @@ impl Command {
         svg_path: &Path,
         test_config: &mut TestConfig<Cmd>,
     ) -> anyhow::Result<TestStats> {
-        let svg = BufReader::new(File::open(svg_path)?);
-        let transcript = Transcript::from_svg(svg)?;
+        let transcript = Self::parse_file(svg_path)?;
         test_config
             .test_transcript_for_stats(&transcript)
             .map(|(stats, _)| stats)
             .map_err(From::from)
     }
 
+    fn read_file(svg_path: &Path) -> anyhow::Result<Transcript> {
+        Ok(Self::parse_file(svg_path)?.to_captured())
+    }
+
+    fn parse_file(svg_path: &Path) -> anyhow::Result<Transcript<Parsed>> {
+        Self::open_file(svg_path, Transcript::from_svg)
+    }
+
+    fn validate_file(svg_path: &Path) -> anyhow::Result<()> {
+        Self::open_file(svg_path, Transcript::validate_svg)
+    }
+
+    /// Opens the file at `svg_path` and processes it with `parse`. Files with the `.gz` extension
+    /// are decompressed on the fly.
+    fn open_file<T>(
+        svg_path: &Path,
+        parse: impl FnOnce(Box<dyn BufRead>) -> Result<T, ParseError>,
+    ) -> anyhow::Result<T> {
+        let svg = File::open(svg_path)
+            .with_context(|| format!("Failed opening file `{}`", svg_path.display()))?;
+        let svg: Box<dyn BufRead> = if svg_path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(BufReader::new(GzDecoder::new(svg)))
+        } else {
+            Box::new(BufReader::new(svg))
+        };
+        parse(svg).with_context(|| format!("Failed parsing file `{}`", svg_path.display()))
+    }
+
     fn report_test_start(out: &StandardStream, svg_path: &Path) -> io::Result<()> {
         let mut out = out.lock();
         write!(out, "Testing file ")?;
@@ -184,10 +313,15 @@ impl Command {
         writeln!(out, "...")
     }
 
-    fn report_failure(out: &StandardStream, svg_path: &Path, err: anyhow::Error) -> io::Result<()> {
+    fn report_failure(
+        out: &StandardStream,
+        action: &str,
+        svg_path: &Path,
+        err: anyhow::Error,
+    ) -> io::Result<()> {
         let mut out = out.lock();
         out.set_color(ColorSpec::new().set_fg(Some(Color::Red)))?;
-        write!(out, "Error testing file ")?;
+        write!(out, "Error {action} file ")?;
         out.set_color(
             ColorSpec::new()
                 .set_reset(false)
@@ -209,13 +343,16 @@ impl Command {
         writeln!(out)
     }
 
-    fn print_file(svg_path: &Path, color: ColorPreference) -> anyhow::Result<()> {
+    fn print_file(
+        svg_path: &Path,
+        color: ColorPreference,
+        headers: Option<Option<&str>>,
+    ) -> anyhow::Result<()> {
         let transcript = if svg_path.as_os_str() == "-" {
             let svg = BufReader::new(io::stdin());
             Transcript::from_svg(svg)?
         } else {
-            let svg = BufReader::new(File::open(svg_path)?);
-            Transcript::from_svg(svg)?
+            Self::parse_file(svg_path)?
         };
 
         let color = ColorChoice::from(color);
@@ -223,12 +360,12 @@ impl Command {
         let mut out = out.lock();
 
         for (i, interaction) in transcript.interactions().iter().enumerate() {
-            if i > 0 {
-                writeln!(out)?;
+            if let Some(format) = headers {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                Self::print_header(&mut out, "", format, "Input", i + 1)?;
             }
-            out.set_color(ColorSpec::new().set_bold(true))?;
-            writeln!(out, "----------  Input #{} ----------", i + 1)?;
-            out.reset()?;
 
             let input = interaction.input();
             writeln!(out, "{} {}", input.prompt().unwrap_or("$"), input.as_ref())?;
@@ -246,9 +383,9 @@ impl Command {
                 }
             }
 
-            out.set_color(ColorSpec::new().set_bold(true))?;
-            writeln!(out, "\n---------- Output #{} ----------", i + 1)?;
-            out.reset()?;
+            if let Some(format) = headers {
+                Self::print_header(&mut out, "\n", format, "Output", i + 1)?;
+            }
 
             if color == ColorChoice::Never {
                 writeln!(out, "{}", interaction.output().plaintext())?;
@@ -262,6 +399,27 @@ impl Command {
         }
         Ok(())
     }
+
+    /// Prints a header using the custom `format`, or the default format (which aligns
+    /// input and output headers) if `format` is `None`.
+    fn print_header(
+        out: &mut impl WriteColor,
+        prefix: &str,
+        format: Option<&str>,
+        kind: &str,
+        index: usize,
+    ) -> io::Result<()> {
+        let header = if let Some(format) = format {
+            format
+                .replace("{kind}", kind)
+                .replace("{index}", &index.to_string())
+        } else {
+            format!("---------- {kind:>6} #{index} ----------")
+        };
+        out.set_color(ColorSpec::new().set_bold(true))?;
+        writeln!(out, "{prefix}{header}")?;
+        out.reset()
+    }
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -6,7 +6,10 @@ use clap::Args;
 use humantime::Duration;
 #[cfg(feature = "portable-pty")]
 use term_transcript::PtyCommand;
-use term_transcript::{traits::Echoing, Captured, ExitStatus, ShellOptions, Transcript, UserInput};
+use term_transcript::{
+    traits::{ConfigureCommand, Echoing},
+    ShellOptions, Transcript, UserInput,
+};
 
 #[cfg(feature = "portable-pty")]
 mod pty {
@@ -72,20 +75,15 @@ impl ExitCodeCheck {
         }
     }
 
-    fn check_exit_code(self, response: &Captured) -> Option<ExitStatus> {
-        let response = response.to_plaintext().ok()?;
+    fn apply<Cmd: ConfigureCommand>(self, options: ShellOptions<Cmd>) -> ShellOptions<Cmd> {
         match self {
-            Self::Sh => response.trim().parse().ok().map(ExitStatus),
-            Self::PowerShell => match response.trim() {
-                "True" => Some(ExitStatus(0)),
-                "False" => Some(ExitStatus(1)),
-                _ => None,
-            },
+            Self::Sh => options.with_sh_status_check(),
+            Self::PowerShell => options.with_pwsh_status_check(),
         }
     }
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Clone, Args)]
 pub(crate) struct ShellArgs {
     /// Execute shell in a pseudo-terminal (PTY), rather than connecting to it via pipes.
     /// PTY size can be specified by providing row and column count in a string like 19x80.
@@ -133,7 +131,7 @@ impl ShellArgs {
             options = options.with_current_dir(dir);
         }
         if let Some(check) = exit_code_check {
-            options = options.with_status_check("echo $?", move |code| check.check_exit_code(code));
+            options = check.apply(options);
         }
         options
             .with_io_timeout(self.io_timeout.into())
@@ -164,12 +162,32 @@ impl ShellArgs {
             options = options.with_current_dir(dir);
         }
         if let Some(check) = exit_code_check {
-            options.with_status_check("echo $?", move |code| check.check_exit_code(code))
+            check.apply(options)
         } else {
             options
         }
     }
 
+    /// Checks that programs invoked by `inputs` can be located in `PATH` of the configured shell.
+    /// The check is skipped with a warning for shells not recognized as POSIX-like, since
+    /// their inputs may not start with a program name.
+    pub fn verify_commands(&self, inputs: &[UserInput]) -> anyhow::Result<()> {
+        let exit_code_check = match &self.shell {
+            Some(shell) => ExitCodeCheck::detect(shell),
+            None => ExitCodeCheck::for_default_shell(),
+        };
+        if !matches!(exit_code_check, Some(ExitCodeCheck::Sh)) {
+            eprintln!("warning: skipping command check since the shell is not POSIX-like");
+            return Ok(());
+        }
+
+        let options = self.clone().into_std_options();
+        if let Err(missing) = options.verify_commands(inputs.iter().cloned()) {
+            anyhow::bail!("commands not found in PATH: {}", missing.join(", "));
+        }
+        Ok(())
+    }
+
     #[cfg(feature = "portable-pty")]
     pub fn create_transcript(
         self,
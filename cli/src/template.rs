@@ -21,6 +21,7 @@ enum NamedPalette {
     Xterm,
     Ubuntu,
     Gjm8,
+    HighContrast,
 }
 
 impl From<NamedPalette> for svg::NamedPalette {
@@ -31,6 +32,7 @@ impl From<NamedPalette> for svg::NamedPalette {
             NamedPalette::Xterm => Self::Xterm,
             NamedPalette::Ubuntu => Self::Ubuntu,
             NamedPalette::Gjm8 => Self::Gjm8,
+            NamedPalette::HighContrast => Self::HighContrast,
         }
     }
 }
@@ -54,20 +56,15 @@ impl From<LineNumbers> for svg::LineNumbers {
 
 #[derive(Debug, Args)]
 pub(crate) struct TemplateArgs {
-    /// Path to the configuration TOML file.
+    /// Path to the configuration TOML file. Options explicitly specified via command-line args
+    /// (e.g., `--width` or `--palette`) take precedence over the options from the file.
     ///
     /// See https://slowli.github.io/term-transcript/term_transcript/svg/ for the configuration format.
-    #[arg(
-        long,
-        conflicts_with_all = [
-            "palette", "line_numbers", "window_frame", "additional_styles", "font_family", "width",
-            "scroll", "hard_wrap", "no_wrap",
-        ]
-    )]
+    #[arg(long)]
     config_path: Option<PathBuf>,
-    /// Color palette to use.
-    #[arg(long, short = 'p', default_value = "gjm8", value_enum)]
-    palette: NamedPalette,
+    /// Color palette to use. If not specified, the `gjm8` palette is used.
+    #[arg(long, short = 'p', value_enum)]
+    palette: Option<NamedPalette>,
     /// Line numbering strategy.
     #[arg(long, short = 'n', value_enum)]
     line_numbers: Option<LineNumbers>,
@@ -84,21 +81,17 @@ pub(crate) struct TemplateArgs {
     #[arg(long = "font")]
     font_family: Option<String>,
     /// Configures width of the rendered console in SVG units. Hint: use together with `--hard-wrap $chars`,
-    /// where width is around $chars * 9.
-    #[arg(long, default_value = "720")]
-    width: usize,
+    /// where width is around $chars * 9. If not specified, the width is 720.
+    #[arg(long)]
+    width: Option<usize>,
     /// Enables scrolling animation, but only if the snapshot height exceeds a threshold height (in SVG units).
     /// If not specified, the default height is sufficient to fit 19 lines with the default template.
     #[arg(long, value_name = "HEIGHT")]
     scroll: Option<Option<usize>>,
-    /// Specifies text wrapping threshold in number of chars.
-    #[arg(
-        long = "hard-wrap",
-        value_name = "CHARS",
-        conflicts_with = "no_wrap",
-        default_value = "80"
-    )]
-    hard_wrap: usize,
+    /// Specifies text wrapping threshold in number of chars. If not specified, text is wrapped
+    /// at 80 chars.
+    #[arg(long = "hard-wrap", value_name = "CHARS", conflicts_with = "no_wrap")]
+    hard_wrap: Option<usize>,
     /// Disables text wrapping (by default, text is hard-wrapped at 80 chars). Line overflows
     /// will be hidden.
     #[arg(long = "no-wrap")]
@@ -110,48 +103,73 @@ pub(crate) struct TemplateArgs {
     /// Hides all user inputs; only outputs will be rendered.
     #[arg(long = "no-inputs")]
     no_inputs: bool,
+    /// Minifies the rendered SVG by removing insignificant whitespace between tags.
+    #[arg(long)]
+    minify: bool,
     /// Path to a custom Handlebars template to use. `-` means not to use a template at all,
     /// and instead output JSON data that would be fed to a template.
     ///
     /// See https://slowli.github.io/term-transcript/term_transcript/svg/ for docs on templating.
-    #[arg(long = "tpl")]
+    #[arg(long = "tpl", visible_alias = "template")]
     template_path: Option<PathBuf>,
     /// File to save the rendered SVG into. If omitted, the output will be printed to stdout.
     #[arg(long = "out", short = 'o')]
     out: Option<PathBuf>,
+    /// Renders each interaction into a separate file. File names are derived from `--out`
+    /// by appending a 1-based interaction index to the file stem (e.g., `out-1.svg`, `out-2.svg`).
+    /// Cannot be used with `--tpl -`.
+    #[arg(long, requires = "out")]
+    split: bool,
 }
 
 impl From<TemplateArgs> for TemplateOptions {
     fn from(value: TemplateArgs) -> Self {
-        let mut this = Self {
-            width: value.width,
-            palette: svg::NamedPalette::from(value.palette).into(),
-            line_numbers: value.line_numbers.map(svg::LineNumbers::from),
-            window_frame: value.window_frame,
-            scroll: value.scroll.map(|max_height| {
+        let mut this = Self::default();
+        value.apply_overrides(&mut this);
+        this
+    }
+}
+
+impl TemplateArgs {
+    /// Overrides `options` with the values explicitly specified in the command-line args.
+    fn apply_overrides(self, options: &mut TemplateOptions) {
+        if let Some(width) = self.width {
+            options.width = width;
+        }
+        if let Some(palette) = self.palette {
+            options.palette = svg::NamedPalette::from(palette).into();
+        }
+        if let Some(line_numbers) = self.line_numbers {
+            options.line_numbers = Some(line_numbers.into());
+        }
+        if self.window_frame {
+            options.window_frame = true;
+        }
+        if let Some(max_height) = self.scroll {
+            options.scroll = Some(
                 max_height.map_or_else(ScrollOptions::default, |max_height| ScrollOptions {
                     max_height,
                     ..ScrollOptions::default()
-                })
-            }),
-            wrap: if value.no_wrap {
-                None
-            } else {
-                Some(WrapOptions::HardBreakAt(value.hard_wrap))
-            },
-            additional_styles: value.additional_styles.unwrap_or_default(),
-            ..Self::default()
-        };
-
-        if let Some(mut font_family) = value.font_family {
+                }),
+            );
+        }
+        if self.no_wrap {
+            options.wrap = None;
+        } else if let Some(hard_wrap) = self.hard_wrap {
+            options.wrap = Some(WrapOptions::HardBreakAt(hard_wrap));
+        }
+        if let Some(additional_styles) = self.additional_styles {
+            options.additional_styles = additional_styles;
+        }
+        if let Some(mut font_family) = self.font_family {
             font_family.push_str(", monospace");
-            this.font_family = font_family;
+            options.font_family = font_family;
+        }
+        if self.minify {
+            options.minify = true;
         }
-        this
     }
-}
 
-impl TemplateArgs {
     pub fn create_input(&self, command: String) -> UserInput {
         let input = UserInput::command(command);
         if self.no_inputs {
@@ -163,6 +181,7 @@ impl TemplateArgs {
 
     pub fn render(mut self, transcript: &Transcript) -> anyhow::Result<()> {
         let pure_svg = self.pure_svg;
+        let split = self.split;
         let out_path = mem::take(&mut self.out);
         let template_path = mem::take(&mut self.template_path);
         let config_path = mem::take(&mut self.config_path);
@@ -170,15 +189,20 @@ impl TemplateArgs {
         let options = if let Some(path) = &config_path {
             let config = fs::read_to_string(path)
                 .with_context(|| format!("cannot read TOML config from `{}`", path.display()))?;
-            toml::from_str(&config).with_context(|| {
+            let mut options: TemplateOptions = toml::from_str(&config).with_context(|| {
                 format!("failed deserializing TOML config from `{}`", path.display())
-            })?
+            })?;
+            self.apply_overrides(&mut options);
+            options
         } else {
             TemplateOptions::from(self)
         };
 
         let template = if let Some(template_path) = template_path {
             if template_path.as_os_str() == "-" {
+                if split {
+                    anyhow::bail!("`--split` cannot be used with outputting data (`--tpl -`)");
+                }
                 return Self::render_data(out_path.as_deref(), transcript, &options);
             }
             let template = Self::load_template(&template_path)?;
@@ -189,6 +213,11 @@ impl TemplateArgs {
             Template::new(options)
         };
 
+        if split {
+            let out_path = out_path.context("`--split` requires specifying `--out`")?;
+            return Self::render_split(&template, &out_path, transcript);
+        }
+
         if let Some(out_path) = out_path {
             let out = File::create(&out_path)
                 .with_context(|| format!("cannot create output file `{}`", out_path.display()))?;
@@ -203,6 +232,30 @@ impl TemplateArgs {
         Ok(())
     }
 
+    fn render_split(
+        template: &Template,
+        out_path: &Path,
+        transcript: &Transcript,
+    ) -> anyhow::Result<()> {
+        let stem = out_path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = out_path.extension().map(|ext| ext.to_string_lossy());
+
+        for (i, rendered) in template.render_each(transcript).enumerate() {
+            let mut file_name = format!("{stem}-{}", i + 1);
+            if let Some(extension) = &extension {
+                file_name.push('.');
+                file_name.push_str(extension);
+            }
+            let path = out_path.with_file_name(file_name);
+
+            let rendered =
+                rendered.with_context(|| format!("cannot render interaction #{}", i + 1))?;
+            fs::write(&path, rendered)
+                .with_context(|| format!("cannot write output file `{}`", path.display()))?;
+        }
+        Ok(())
+    }
+
     fn render_data(
         out_path: Option<&Path>,
         transcript: &Transcript,
@@ -0,0 +1,157 @@
+//! Export of transcripts to the asciicast format.
+
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    time::Duration,
+};
+
+use crate::{Interaction, Transcript};
+
+/// Interval between displaying an input and its output if the duration of the interaction
+/// was not recorded.
+const DEFAULT_OUTPUT_DELAY: Duration = Duration::from_millis(500);
+/// Pause between displaying an output and the next input.
+const INPUT_DELAY: Duration = Duration::from_secs(1);
+
+impl Transcript {
+    /// Terminal width in chars specified in the asciicast header.
+    const ASCIICAST_WIDTH: usize = 80;
+    /// Terminal height in lines specified in the asciicast header.
+    const ASCIICAST_HEIGHT: usize = 24;
+
+    /// Writes this transcript in the [asciicast v2] format used by [asciinema].
+    ///
+    /// Since transcripts are static, the event timing is synthesized: each output is displayed
+    /// after the recorded [duration](Interaction::duration()) of the interaction
+    /// (or 0.5 seconds if the duration is not available), and the next input is displayed
+    /// a second after the previous output. [Hidden](crate::UserInput::hide()) inputs
+    /// and empty outputs are not displayed. Terminal size in the header is fixed to 80×24.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error should it occur when writing to `writer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use term_transcript::{Transcript, UserInput};
+    /// let mut transcript = Transcript::new();
+    /// transcript.add_interaction(UserInput::command("echo hello"), "hello");
+    ///
+    /// let mut buffer = vec![];
+    /// transcript.to_asciicast(&mut buffer)?;
+    /// let asciicast = String::from_utf8(buffer)?;
+    /// let lines: Vec<_> = asciicast.lines().collect();
+    /// assert_eq!(lines[0], r#"{"version": 2, "width": 80, "height": 24}"#);
+    /// assert_eq!(lines[1], r#"[0.000000, "o", "$ echo hello\r\n"]"#);
+    /// assert_eq!(lines[2], r#"[0.500000, "o", "hello\r\n"]"#);
+    /// # anyhow::Ok(())
+    /// ```
+    ///
+    /// [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+    /// [asciinema]: https://asciinema.org/
+    pub fn to_asciicast<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            r#"{{"version": 2, "width": {}, "height": {}}}"#,
+            Self::ASCIICAST_WIDTH,
+            Self::ASCIICAST_HEIGHT
+        )?;
+
+        let mut timestamp = Duration::ZERO;
+        for (i, interaction) in self.interactions.iter().enumerate() {
+            if i > 0 {
+                timestamp += INPUT_DELAY;
+            }
+            let input = &interaction.input;
+            if !input.hidden {
+                let mut input_text = String::new();
+                if let Some(prompt) = input.prompt() {
+                    input_text.push_str(prompt);
+                    input_text.push(' ');
+                }
+                input_text.push_str(&input.text);
+                write_event(&mut writer, timestamp, &input_text)?;
+            }
+
+            timestamp += interaction.duration.unwrap_or(DEFAULT_OUTPUT_DELAY);
+            let output = Self::asciicast_output(interaction);
+            if !output.is_empty() {
+                write_event(&mut writer, timestamp, &output)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn asciicast_output(interaction: &Interaction) -> String {
+        let mut output = interaction.output.as_ref().to_owned();
+        if let Some(stderr) = &interaction.stderr {
+            if !output.is_empty() && !output.ends_with('\n') {
+                output.push('\n');
+            }
+            output.push_str(stderr.as_ref());
+        }
+        output
+    }
+}
+
+/// Writes a single output event. Newlines in `text` are converted to CRLF line endings
+/// expected by terminals, and the text is terminated with a newline.
+fn write_event(writer: &mut impl Write, timestamp: Duration, text: &str) -> io::Result<()> {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    for ch in text.chars().chain(['\n']) {
+        match ch {
+            '\n' => escaped.push_str(r"\r\n"),
+            '"' => escaped.push_str(r#"\""#),
+            '\\' => escaped.push_str(r"\\"),
+            '\t' => escaped.push_str(r"\t"),
+            ch if ch.is_control() => {
+                write!(escaped, "\\u{:04x}", u32::from(ch)).unwrap();
+                // ^-- `unwrap` is safe; writing to a string never fails.
+            }
+            ch => escaped.push(ch),
+        }
+    }
+    writeln!(
+        writer,
+        r#"[{:.6}, "o", "{escaped}"]"#,
+        timestamp.as_secs_f64()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UserInput;
+
+    #[test]
+    fn exporting_transcript_to_asciicast() {
+        let mut transcript = Transcript::new();
+        transcript.add_existing_interaction(
+            Interaction::new(
+                UserInput::command("test"),
+                "Hello, \u{1b}[32m\"world\"\u{1b}[0m!\nbye\n",
+            )
+            .with_duration(Duration::from_millis(1_250)),
+        );
+        transcript.add_interaction(UserInput::command("hidden").hide(), "");
+        transcript.add_interaction(UserInput::interrupt(), "\\");
+
+        let mut buffer = vec![];
+        transcript.to_asciicast(&mut buffer).unwrap();
+        let asciicast = String::from_utf8(buffer).unwrap();
+        let lines: Vec<_> = asciicast.lines().collect();
+
+        assert_eq!(
+            lines[1..],
+            [
+                r#"[0.000000, "o", "$ test\r\n"]"#,
+                r#"[1.250000, "o", "Hello, \u001b[32m\"world\"\u001b[0m!\r\nbye\r\n"]"#,
+                r#"[3.750000, "o", "^C\r\n"]"#,
+                r#"[4.250000, "o", "\\\r\n"]"#,
+            ]
+        );
+    }
+}
@@ -82,6 +82,14 @@
 //! Exposes [the eponymous module](crate::test) that allows parsing [`Transcript`]s
 //! from SVG files and testing them.
 //!
+//! ## `gzip`
+//!
+//! *(Off by default)*
+//!
+//! Allows parsing gzip-compressed SVG snapshots via [`Transcript::from_svg_gz()`] and rendering
+//! gzip-compressed snapshots via [`svg::Template::render_gz()`] (the latter also requires
+//! the `svg` feature). Uses the [`flate2`] crate under the hood.
+//!
 //! ## `pretty_assertions`
 //!
 //! *(On by default)*
@@ -102,6 +110,7 @@
 //! [`isatty`]: https://man7.org/linux/man-pages/man3/isatty.3.html
 //! [Handlebars]: https://handlebarsjs.com/
 //! [`pretty_assertions`]: https://docs.rs/pretty_assertions/
+//! [`flate2`]: https://docs.rs/flate2/
 //! [`portable-pty`]: https://docs.rs/portable-pty/
 //! [`tracing`]: https://docs.rs/tracing/
 //!
@@ -150,8 +159,18 @@
 #![warn(clippy::all, clippy::pedantic)]
 #![allow(clippy::must_use_candidate, clippy::module_name_repetitions)]
 
-use std::{borrow::Cow, error::Error as StdError, fmt, io, num::ParseIntError};
+use std::{
+    borrow::Cow,
+    error::Error as StdError,
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    num::ParseIntError,
+    ops::Range,
+    time::Duration,
+};
 
+mod asciicast;
 #[cfg(feature = "portable-pty")]
 mod pty;
 mod shell;
@@ -169,7 +188,7 @@ mod write;
 #[cfg(feature = "portable-pty")]
 pub use self::pty::{PtyCommand, PtyShell};
 pub use self::{
-    shell::{ShellOptions, StdShell},
+    shell::{ShellContext, ShellOptions, StdShell},
     term::{Captured, TermOutput},
 };
 
@@ -233,7 +252,7 @@ impl StdError for TermError {
 }
 
 /// Transcript of a user interacting with the terminal.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Transcript<Out: TermOutput = Captured> {
     interactions: Vec<Interaction<Out>>,
 }
@@ -261,6 +280,21 @@ impl<Out: TermOutput> Transcript<Out> {
     pub fn interactions_mut(&mut self) -> &mut [Interaction<Out>] {
         &mut self.interactions
     }
+
+    /// Shortens this transcript, keeping the first `len` interactions and dropping the rest.
+    /// If `len` is greater or equal to the current number of interactions, this has no effect.
+    pub fn truncate(&mut self, len: usize) {
+        self.interactions.truncate(len);
+    }
+
+    /// Concatenates interactions from the provided `transcripts` into a single transcript.
+    pub fn concat(transcripts: impl IntoIterator<Item = Self>) -> Self {
+        let interactions = transcripts
+            .into_iter()
+            .flat_map(|transcript| transcript.interactions)
+            .collect();
+        Self { interactions }
+    }
 }
 
 impl Transcript {
@@ -285,6 +319,37 @@ impl Transcript {
     ) -> &mut Self {
         self.add_existing_interaction(Interaction::new(input, output))
     }
+
+    /// Renders this transcript into an SVG string using the [default template]
+    /// with the specified `options`. This is a shortcut for creating a [`Template`]
+    /// and calling [`Template::render_to_string()`].
+    ///
+    /// [default template]: svg::Template::new()
+    /// [`Template`]: svg::Template
+    /// [`Template::render_to_string()`]: svg::Template::render_to_string()
+    ///
+    /// # Errors
+    ///
+    /// Returns a Handlebars rendering error, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use term_transcript::{svg::TemplateOptions, Transcript, UserInput};
+    /// let mut transcript = Transcript::new();
+    /// transcript.add_interaction(UserInput::command("echo hello"), "hello");
+    /// let svg = transcript.render_to_svg_string(TemplateOptions::default())?;
+    /// assert!(svg.contains("<svg"));
+    /// # anyhow::Ok(())
+    /// ```
+    #[cfg(feature = "svg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
+    pub fn render_to_svg_string(
+        &self,
+        options: svg::TemplateOptions,
+    ) -> Result<String, handlebars::RenderError> {
+        svg::Template::new(options).render_to_string(self)
+    }
 }
 
 /// Portable, platform-independent version of [`ExitStatus`] from the standard library.
@@ -343,7 +408,14 @@ impl ExitStatus {
 pub struct Interaction<Out: TermOutput = Captured> {
     input: UserInput,
     output: Out,
+    stderr: Option<Out>,
     exit_status: Option<ExitStatus>,
+    duration: Option<Duration>,
+    highlighted_lines: Vec<usize>,
+    is_truncated: bool,
+    folded_lines: Vec<Range<usize>>,
+    #[cfg(feature = "svg")]
+    palette: Option<svg::Palette>,
 }
 
 impl Interaction {
@@ -352,16 +424,78 @@ impl Interaction {
         Self {
             input: input.into(),
             output: Captured::from(output.into()),
+            stderr: None,
             exit_status: None,
+            duration: None,
+            highlighted_lines: vec![],
+            is_truncated: false,
+            folded_lines: vec![],
+            #[cfg(feature = "svg")]
+            palette: None,
         }
     }
 
+    /// Assigns output to the standard error stream for this interaction. When rendering,
+    /// this output is displayed after the [main output](Self::output()).
+    #[must_use]
+    pub fn with_stderr(mut self, stderr: Captured) -> Self {
+        self.stderr = Some(stderr);
+        self
+    }
+
     /// Assigns an exit status to this interaction.
     #[must_use]
     pub fn with_exit_status(mut self, exit_status: ExitStatus) -> Self {
         self.exit_status = Some(exit_status);
         self
     }
+
+    /// Assigns the wall-clock duration of executing the input of this interaction.
+    #[must_use]
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Marks output lines with the specified zero-based indices to be highlighted
+    /// in the rendered transcript (e.g., with a background band in the default template).
+    /// Lines are counted as displayed, i.e., after applying [wrapping](crate::svg::WrapOptions).
+    /// Out-of-range indices are ignored during rendering.
+    #[must_use]
+    pub fn with_highlighted_lines(mut self, lines: impl IntoIterator<Item = usize>) -> Self {
+        self.highlighted_lines.extend(lines);
+        self
+    }
+
+    /// Folds the output lines with the specified zero-based indices, so that they are rendered
+    /// as a single "… N lines hidden …" marker line. This can be used to reduce the height
+    /// of long outputs while preserving context around the folded section. Lines are counted
+    /// in the captured output, i.e., before applying [wrapping](crate::svg::WrapOptions);
+    /// out-of-range indices are ignored. Overlapping or adjacent ranges are merged.
+    ///
+    /// In the default template, the full output is retained in the `data-unfolded`
+    /// attribute of the output element, so that parsing the rendered transcript
+    /// restores the unfolded output.
+    #[must_use]
+    pub fn fold_lines(mut self, range: Range<usize>) -> Self {
+        self.folded_lines.push(range);
+        self
+    }
+
+    /// Overrides the palette used to render this interaction, e.g. to compare color schemes
+    /// within a single transcript. By default, the [global palette](svg::TemplateOptions::palette)
+    /// is used.
+    ///
+    /// In the default template, the palette applies to the text colors of the interaction
+    /// and to the output background. The pure SVG template only applies the palette
+    /// to the text colors.
+    #[cfg(feature = "svg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
+    #[must_use]
+    pub fn with_palette(mut self, palette: impl Into<svg::Palette>) -> Self {
+        self.palette = Some(palette.into());
+        self
+    }
 }
 
 impl<Out: TermOutput> Interaction<Out> {
@@ -375,6 +509,12 @@ impl<Out: TermOutput> Interaction<Out> {
         &self.output
     }
 
+    /// Output to the standard error stream, if it was recorded separately
+    /// from the [main output](Self::output()).
+    pub fn stderr(&self) -> Option<&Out> {
+        self.stderr.as_ref()
+    }
+
     /// Sets the output for this interaction.
     pub fn set_output(&mut self, output: Out) {
         self.output = output;
@@ -384,24 +524,90 @@ impl<Out: TermOutput> Interaction<Out> {
     pub fn exit_status(&self) -> Option<ExitStatus> {
         self.exit_status
     }
+
+    /// Returns the wall-clock duration of executing the input of this interaction, if available.
+    ///
+    /// The duration is recorded when [capturing a transcript](Transcript::from_inputs())
+    /// and is measured from sending the input to the shell until receiving the last output line.
+    /// Thus, it is approximate, and is not recorded for inputs that produce no output.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Returns zero-based indices of the highlighted output lines.
+    pub fn highlighted_lines(&self) -> &[usize] {
+        &self.highlighted_lines
+    }
+
+    /// Returns the [folded](Interaction::fold_lines()) ranges of output lines.
+    pub fn folded_lines(&self) -> &[Range<usize>] {
+        &self.folded_lines
+    }
+
+    /// Checks whether the output of this interaction was truncated because it has exceeded
+    /// the [configured limit](crate::ShellOptions::with_max_output_bytes()).
+    pub fn is_truncated(&self) -> bool {
+        self.is_truncated
+    }
+
+    /// Returns the [palette override](Interaction::with_palette()) for this interaction, if any.
+    #[cfg(feature = "svg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
+    pub fn palette(&self) -> Option<&svg::Palette> {
+        self.palette.as_ref()
+    }
+}
+
+/// Compares the input, output, stderr and exit status of interactions. Capture metadata
+/// (e.g., the [duration](Interaction::duration())) and rendering options
+/// (e.g., [highlighted lines](Interaction::highlighted_lines())) are not compared, so that
+/// captures of the same inputs producing the same outputs are considered equal.
+impl<Out: TermOutput + PartialEq> PartialEq for Interaction<Out> {
+    fn eq(&self, other: &Self) -> bool {
+        self.input == other.input
+            && self.output == other.output
+            && self.stderr == other.stderr
+            && self.exit_status == other.exit_status
+    }
+}
+
+impl<Out: TermOutput + Eq> Eq for Interaction<Out> {}
+
+/// Hashes the same fields as compared by the [`PartialEq`] implementation.
+impl<Out: TermOutput + Hash> Hash for Interaction<Out> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.input.hash(state);
+        self.output.hash(state);
+        self.stderr.hash(state);
+        self.exit_status.hash(state);
+    }
 }
 
 /// User input during interaction with a terminal.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "svg", derive(serde::Serialize))]
 pub struct UserInput {
     text: String,
     prompt: Option<Cow<'static, str>>,
     hidden: bool,
+    #[cfg_attr(feature = "svg", serde(skip))]
+    interrupt: bool,
+    #[cfg_attr(feature = "svg", serde(skip))]
+    stdin_data: Option<String>,
+    #[cfg_attr(feature = "svg", serde(skip_serializing_if = "Option::is_none"))]
+    continuation_prompt: Option<Cow<'static, str>>,
 }
 
 impl UserInput {
+    pub(crate) const INTERRUPT_TEXT: &'static str = "^C";
+
     #[cfg(feature = "test")]
     pub(crate) fn intern_prompt(prompt: String) -> Cow<'static, str> {
         match prompt.as_str() {
             "$" => Cow::Borrowed("$"),
             ">>>" => Cow::Borrowed(">>>"),
             "..." => Cow::Borrowed("..."),
+            ">" => Cow::Borrowed(">"),
             _ => Cow::Owned(prompt),
         }
     }
@@ -412,6 +618,9 @@ impl UserInput {
             text: text.into(),
             prompt: Some(Cow::Borrowed("$")),
             hidden: false,
+            interrupt: false,
+            stdin_data: None,
+            continuation_prompt: None,
         }
     }
 
@@ -421,6 +630,9 @@ impl UserInput {
             text: text.into(),
             prompt: Some(Cow::Borrowed(">>>")),
             hidden: false,
+            interrupt: false,
+            stdin_data: None,
+            continuation_prompt: None,
         }
     }
 
@@ -430,20 +642,141 @@ impl UserInput {
             text: text.into(),
             prompt: Some(Cow::Borrowed("...")),
             hidden: false,
+            interrupt: false,
+            stdin_data: None,
+            continuation_prompt: None,
         }
     }
 
+    /// Creates a multi-line command input by joining the provided `lines` with `\n`.
+    ///
+    /// When the input is replayed in a shell, each line is written separately (i.e., as if
+    /// the user has pressed Enter after each line), so it's up to the shell to handle continuation
+    /// (e.g., via trailing `\` or unclosed brackets / quotes). In the rendered transcript,
+    /// the first line is prefixed with the `$` prompt, and the following lines
+    /// with the `>` [continuation prompt](Self::continuation_prompt()), similar to `sh`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use term_transcript::UserInput;
+    /// let input = UserInput::multiline(["echo \\", "  hello"]);
+    /// assert_eq!(input.as_ref(), "echo \\\n  hello");
+    /// assert_eq!(input.prompt(), Some("$"));
+    /// assert_eq!(input.continuation_prompt(), Some(">"));
+    /// ```
+    pub fn multiline(lines: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let mut text = String::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            text.push_str(line.as_ref());
+        }
+        Self {
+            continuation_prompt: Some(Cow::Borrowed(">")),
+            ..Self::command(text)
+        }
+    }
+
+    /// Creates an interrupt input, i.e., one emulating the user pressing Ctrl+C. This is useful
+    /// to snapshot the behavior of a long-running program (e.g., a server or a REPL launched
+    /// by the previous input) on interrupt.
+    ///
+    /// The input is rendered as `^C` without a prompt. When the input is replayed in a shell,
+    /// it is translated by [`ShellProcess::interrupt()`](crate::traits::ShellProcess::interrupt()),
+    /// which differs depending on how the shell is connected:
+    ///
+    /// - For shells connected to a pseudo-terminal (e.g., [`PtyCommand`]), the ETX byte (`\x03`)
+    ///   is written to the shell input, so that the terminal sends `SIGINT` to the foreground
+    ///   process group (on Unix), or a `CTRL_C_EVENT` (on Windows).
+    /// - For shells communicating via OS pipes (e.g., [`StdShell`]), `SIGINT` is sent to
+    ///   the direct children of the shell process using `pkill -INT -P`. This is only supported
+    ///   on Unix; on Windows, replaying an interrupt input returns an error.
+    ///
+    /// Unlike with other inputs, the prompt of an interrupt is not customized by
+    /// [`ShellOptions`] (e.g., via [`ShellOptions::with_cwd_prompt()`]), since querying
+    /// the shell would interfere with the interrupted program.
+    ///
+    /// [`PtyCommand`]: crate::PtyCommand
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use term_transcript::UserInput;
+    /// let input = UserInput::interrupt();
+    /// assert!(input.is_interrupt());
+    /// assert_eq!(input.as_ref(), "^C");
+    /// assert_eq!(input.prompt(), None);
+    /// ```
+    pub fn interrupt() -> Self {
+        Self {
+            text: Self::INTERRUPT_TEXT.to_owned(),
+            prompt: None,
+            hidden: false,
+            interrupt: true,
+            stdin_data: None,
+            continuation_prompt: None,
+        }
+    }
+
+    /// Checks whether this is an [interrupt input](Self::interrupt()).
+    pub fn is_interrupt(&self) -> bool {
+        self.interrupt
+    }
+
     /// Returns the prompt part of this input.
     pub fn prompt(&self) -> Option<&str> {
         self.prompt.as_deref()
     }
 
+    /// Returns the prompt displayed before the second and following lines of this input,
+    /// if any. See [`Self::multiline()`].
+    pub fn continuation_prompt(&self) -> Option<&str> {
+        self.continuation_prompt.as_deref()
+    }
+
     /// Marks this input as hidden (one that should not be displayed in the rendered transcript).
     #[must_use]
     pub fn hide(mut self) -> Self {
         self.hidden = true;
         self
     }
+
+    /// Attaches data supplied to the standard input of the command (e.g., for `grep pattern`
+    /// or `wc -l`). The data is not displayed in the rendered transcript.
+    ///
+    /// When the input is replayed in a shell, `data` is written to the shell input line by line
+    /// after the command, and is followed by end of input sent via
+    /// [`ShellProcess::send_eof()`](crate::traits::ShellProcess::send_eof()). Since the shell
+    /// session is persistent, the end of input cannot be signaled by closing the shell input.
+    /// Thus, this requires a shell connected to a terminal (e.g., [`PtyCommand`]) on Unix;
+    /// for shells communicating via OS pipes (e.g., [`StdShell`]), capturing such an input
+    /// returns an error. If the terminal echoes input, echoed data will be a part
+    /// of the captured output, same as if the data was typed in by the user.
+    ///
+    /// Data is ignored for [interrupt inputs](Self::interrupt()).
+    ///
+    /// [`PtyCommand`]: crate::PtyCommand
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use term_transcript::UserInput;
+    /// let input = UserInput::command("wc -l").with_stdin_data("first\nsecond");
+    /// assert_eq!(input.as_ref(), "wc -l");
+    /// assert_eq!(input.stdin_data(), Some("first\nsecond"));
+    /// ```
+    #[must_use]
+    pub fn with_stdin_data(mut self, data: impl Into<String>) -> Self {
+        self.stdin_data = Some(data.into());
+        self
+    }
+
+    /// Returns the [standard input data](Self::with_stdin_data()) for this input, if any.
+    pub fn stdin_data(&self) -> Option<&str> {
+        self.stdin_data.as_deref()
+    }
 }
 
 /// Returns the command part of the input without the prompt.
@@ -47,8 +47,10 @@ fn into_io_error(err: Box<dyn StdError + Send + Sync>) -> io::Error {
 pub struct PtyCommand {
     args: Vec<OsString>,
     env: HashMap<OsString, OsString>,
+    env_clear: bool,
     current_dir: Option<PathBuf>,
     pty_size: PtySize,
+    raw_mode: bool,
 }
 
 #[cfg(unix)]
@@ -75,6 +77,7 @@ impl PtyCommand {
         Self {
             args: vec![command.into()],
             env: HashMap::new(),
+            env_clear: false,
             current_dir: None,
             pty_size: PtySize {
                 rows: 19,
@@ -82,6 +85,7 @@ impl PtyCommand {
                 pixel_width: 0,
                 pixel_height: 0,
             },
+            raw_mode: false,
         }
     }
 
@@ -92,6 +96,24 @@ impl PtyCommand {
         self
     }
 
+    /// Sets whether the PTY should be switched to the raw mode (i.e., without the line discipline
+    /// imposed by the default cooked mode) before spawning the command.
+    ///
+    /// The raw mode mainly affects how input is echoed (it is not echoed at all) and how newlines
+    /// are handled (e.g., `\n` is not translated into `\r\n` in the output). Most escape sequences
+    /// emitted by the captured programs are still dropped when capturing output.
+    ///
+    /// The raw mode is set up using the `stty` utility, so this option is only supported
+    /// on Unix; it is ignored on other platforms.
+    pub fn with_raw_mode(&mut self, raw_mode: bool) -> &mut Self {
+        self.raw_mode = raw_mode;
+        self
+    }
+
+    fn is_raw(&self) -> bool {
+        cfg!(unix) && self.raw_mode
+    }
+
     /// Adds a command argument.
     pub fn arg(&mut self, arg: impl Into<OsString>) -> &mut Self {
         self.args.push(arg.into());
@@ -99,7 +121,18 @@ impl PtyCommand {
     }
 
     fn to_command_builder(&self) -> CommandBuilder {
-        let mut builder = CommandBuilder::from_argv(self.args.clone());
+        let args = if self.is_raw() {
+            // `$0` is set to a dummy value, so that `$@` expands to the original command.
+            let wrapper = ["sh", "-c", "stty raw -echo && exec \"$@\"", "sh"];
+            let wrapper = wrapper.into_iter().map(OsString::from);
+            wrapper.chain(self.args.iter().cloned()).collect()
+        } else {
+            self.args.clone()
+        };
+        let mut builder = CommandBuilder::from_argv(args);
+        if self.env_clear {
+            builder.env_clear();
+        }
         for (name, value) in &self.env {
             builder.env(name, value);
         }
@@ -119,6 +152,16 @@ impl ConfigureCommand for PtyCommand {
         self.env
             .insert(OsStr::new(name).to_owned(), value.to_owned());
     }
+
+    fn env_clear(&mut self) -> io::Result<()> {
+        self.env.clear();
+        self.env_clear = true;
+        Ok(())
+    }
+
+    fn program_name(&self) -> Option<&str> {
+        self.args.first()?.to_str()
+    }
 }
 
 impl SpawnShell for PtyCommand {
@@ -148,7 +191,10 @@ impl SpawnShell for PtyCommand {
             .take_writer()
             .map_err(|err| into_io_error(err.into()))?;
         Ok(SpawnedShell {
-            shell: PtyShell { child },
+            shell: PtyShell {
+                child,
+                is_echoing: !self.is_raw(),
+            },
             reader,
             writer,
         })
@@ -160,6 +206,7 @@ impl SpawnShell for PtyCommand {
 #[derive(Debug)]
 pub struct PtyShell {
     child: Box<dyn Child + Send + Sync>,
+    is_echoing: bool,
 }
 
 impl ShellProcess for PtyShell {
@@ -195,7 +242,7 @@ impl ShellProcess for PtyShell {
     }
 
     fn is_echoing(&self) -> bool {
-        true
+        self.is_echoing
     }
 }
 
@@ -274,4 +321,59 @@ mod tests {
         assert_eq!(output.trim(), "hello");
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn pty_transcript_in_raw_mode() -> anyhow::Result<()> {
+        // Since inputs are not echoed in the raw mode, the shell prompt would not be stripped
+        // from the output; thus, we set it to an empty string.
+        let mut options = ShellOptions::new(PtyCommand::default())
+            .with_raw_mode(true)
+            .with_env("PS1", "");
+        let inputs = vec![UserInput::command("echo hello && echo world")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 1);
+        let output = transcript.interactions()[0].output().as_ref();
+        assert_eq!(output.lines().collect::<Vec<_>>(), ["hello", "world"]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pty_transcript_with_stdin_data() -> anyhow::Result<()> {
+        let mut options = ShellOptions::new(PtyCommand::default());
+        let inputs = vec![
+            UserInput::command("wc -l").with_stdin_data("first\nsecond\n"),
+            UserInput::command("echo done"),
+        ];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 2);
+        let output = transcript.interactions()[0].output().as_ref();
+        let last_line = output.lines().last().unwrap();
+        assert_eq!(last_line.trim(), "2", "Unexpected output: {output:?}");
+        let output = transcript.interactions()[1].output().as_ref();
+        assert_eq!(output.trim(), "done");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pty_transcript_with_bracketed_paste() -> anyhow::Result<()> {
+        let mut command = PtyCommand::new("cat");
+        command.arg("-v");
+        let mut options = ShellOptions::new(command).with_bracketed_paste(true);
+        let inputs = vec![UserInput::command("hello")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        let output = transcript.interactions()[0].output().as_ref();
+        assert!(
+            output
+                .lines()
+                .any(|line| line.trim() == "^[[200~hello^[[201~"),
+            "Unexpected output: {output:?}"
+        );
+        Ok(())
+    }
 }
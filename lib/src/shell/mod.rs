@@ -3,7 +3,7 @@
 use std::{
     convert::Infallible,
     env, error,
-    ffi::OsStr,
+    ffi::{OsStr, OsString},
     fmt, io,
     path::{Path, PathBuf},
     process::Command,
@@ -14,15 +14,46 @@ mod standard;
 mod transcript_impl;
 
 pub use self::standard::StdShell;
+#[cfg(feature = "portable-pty")]
+use crate::PtyCommand;
 use crate::{
     traits::{ConfigureCommand, Echoing, SpawnShell, SpawnedShell},
-    Captured, ExitStatus,
+    Captured, ExitStatus, UserInput,
 };
 
 type StatusCheckerFn = dyn Fn(&Captured) -> Option<ExitStatus>;
+type PromptTemplateFn = dyn Fn(&ShellContext<'_>) -> String;
+
+/// Keywords of POSIX-like shells (including `bash`). Used in [`ShellOptions::verify_commands()`].
+const SHELL_KEYWORDS: &[&str] = &[
+    "!", "{", "}", "(", "[[", "case", "do", "done", "elif", "else", "esac", "fi", "for",
+    "function", "if", "in", "select", "then", "until", "while",
+];
+/// Builtins of POSIX-like shells (including common `bash` builtins) that are not resolved
+/// via `PATH`. Used in [`ShellOptions::verify_commands()`].
+const SHELL_BUILTINS: &[&str] = &[
+    ".", ":", "break", "continue", "eval", "exec", "exit", "export", "readonly", "return", "set",
+    "shift", "times", "trap", "unset", "alias", "bg", "cd", "command", "declare", "fg", "getopts",
+    "hash", "jobs", "local", "popd", "pushd", "read", "source", "type", "typeset", "ulimit",
+    "umask", "unalias", "wait",
+];
+
+/// Context available when computing an input prompt via
+/// [`ShellOptions::with_prompt_template()`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ShellContext<'a> {
+    /// Zero-based index of the input in the transcript.
+    pub index: usize,
+    /// Input being recorded. Its prompt is the prompt specified when creating the input.
+    pub input: &'a UserInput,
+    /// Current working directory of the shell. Only available if [`ShellOptions::with_cwd_prompt()`]
+    /// is enabled.
+    pub cwd: Option<&'a str>,
+}
 
 pub(crate) struct StatusCheck {
-    command: String,
+    commands: Vec<String>,
     response_checker: Box<StatusCheckerFn>,
 }
 
@@ -30,14 +61,14 @@ impl fmt::Debug for StatusCheck {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         formatter
             .debug_struct("StatusCheck")
-            .field("command", &self.command)
+            .field("commands", &self.commands)
             .finish_non_exhaustive()
     }
 }
 
 impl StatusCheck {
-    pub fn command(&self) -> &str {
-        &self.command
+    pub fn commands(&self) -> &[String] {
+        &self.commands
     }
 
     pub fn check(&self, response: &Captured) -> Option<ExitStatus> {
@@ -53,14 +84,26 @@ impl StatusCheck {
 ///
 /// [`TestConfig`]: crate::test::TestConfig
 /// [`Transcript::from_inputs()`]: crate::Transcript::from_inputs()
+#[allow(clippy::struct_excessive_bools)] // flags are independent options set via builder methods
 pub struct ShellOptions<Cmd = Command> {
     command: Cmd,
     path_additions: Vec<PathBuf>,
     io_timeout: Duration,
     init_timeout: Duration,
+    kill_timeout: Option<Duration>,
     init_commands: Vec<String>,
     line_decoder: Box<dyn FnMut(Vec<u8>) -> io::Result<String>>,
     status_check: Option<StatusCheck>,
+    echo_detection: bool,
+    init_banner: bool,
+    input_char_delay: Option<Duration>,
+    bracketed_paste: bool,
+    form_feed_clears: bool,
+    alt_screen_capture: bool,
+    max_output_bytes: Option<usize>,
+    cwd_prompt: bool,
+    prompt_template: Option<Box<PromptTemplateFn>>,
+    env_clear_error: Option<io::Error>,
 }
 
 impl<Cmd: fmt::Debug> fmt::Debug for ShellOptions<Cmd> {
@@ -71,8 +114,18 @@ impl<Cmd: fmt::Debug> fmt::Debug for ShellOptions<Cmd> {
             .field("path_additions", &self.path_additions)
             .field("io_timeout", &self.io_timeout)
             .field("init_timeout", &self.init_timeout)
+            .field("kill_timeout", &self.kill_timeout)
             .field("init_commands", &self.init_commands)
             .field("status_check", &self.status_check)
+            .field("echo_detection", &self.echo_detection)
+            .field("init_banner", &self.init_banner)
+            .field("input_char_delay", &self.input_char_delay)
+            .field("bracketed_paste", &self.bracketed_paste)
+            .field("form_feed_clears", &self.form_feed_clears)
+            .field("alt_screen_capture", &self.alt_screen_capture)
+            .field("max_output_bytes", &self.max_output_bytes)
+            .field("cwd_prompt", &self.cwd_prompt)
+            .field("env_clear_error", &self.env_clear_error)
             .finish_non_exhaustive()
     }
 }
@@ -91,6 +144,9 @@ impl<Cmd: ConfigureCommand> From<Cmd> for ShellOptions<Cmd> {
 }
 
 impl<Cmd: ConfigureCommand> ShellOptions<Cmd> {
+    /// Terminal height in lines set by [`Self::with_terminal_width()`].
+    const TERMINAL_HEIGHT: usize = 24;
+
     #[cfg(unix)]
     fn default_shell() -> Command {
         Command::new("sh")
@@ -111,12 +167,23 @@ impl<Cmd: ConfigureCommand> ShellOptions<Cmd> {
             path_additions: vec![],
             io_timeout: Duration::from_millis(500),
             init_timeout: Duration::from_millis(1_500),
+            kill_timeout: None,
             init_commands: vec![],
             line_decoder: Box::new(|line| {
                 String::from_utf8(line)
                     .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.utf8_error()))
             }),
             status_check: None,
+            echo_detection: false,
+            init_banner: false,
+            input_char_delay: None,
+            bracketed_paste: false,
+            form_feed_clears: false,
+            alt_screen_capture: false,
+            max_output_bytes: None,
+            cwd_prompt: false,
+            prompt_template: None,
+            env_clear_error: None,
         }
     }
 
@@ -128,12 +195,194 @@ impl<Cmd: ConfigureCommand> ShellOptions<Cmd> {
             path_additions: self.path_additions,
             io_timeout: self.io_timeout,
             init_timeout: self.init_timeout,
+            kill_timeout: self.kill_timeout,
             init_commands: self.init_commands,
             line_decoder: self.line_decoder,
             status_check: self.status_check,
+            echo_detection: self.echo_detection,
+            init_banner: self.init_banner,
+            input_char_delay: self.input_char_delay,
+            bracketed_paste: self.bracketed_paste,
+            form_feed_clears: self.form_feed_clears,
+            alt_screen_capture: self.alt_screen_capture,
+            max_output_bytes: self.max_output_bytes,
+            cwd_prompt: self.cwd_prompt,
+            prompt_template: self.prompt_template,
+            env_clear_error: self.env_clear_error,
         }
     }
 
+    /// Enables automatic detection whether the shell echoes inputs. If enabled, a probe input
+    /// (a `#`-prefixed comment line with a unique token) is sent to the shell
+    /// after [initialization commands](Self::with_init_command()), and the shell is considered
+    /// echoing if the probe is read back as a line on its own, optionally preceded by a prompt
+    /// (e.g., `$ ` or `>>> `). If the detection is inconclusive (e.g., the shell outputs
+    /// something, but not the probe), the echoing flag provided by the spawned shell is used.
+    /// Detection overrides the flag set via [`Self::echoing()`].
+    ///
+    /// Detection is disabled by default. The probe must be a no-op in the target shell,
+    /// i.e., it must not produce any output or have side effects. Since the probe
+    /// is a `#` comment, detection is only applicable to shells / REPLs treating `#`
+    /// as a comment start (e.g., `sh`, `bash`, PowerShell or Python).
+    #[must_use]
+    pub fn with_echo_detection(mut self) -> Self {
+        self.echo_detection = true;
+        self
+    }
+
+    /// Enables capturing the output produced by the shell on startup (e.g., a banner with
+    /// version info printed by a REPL before the first prompt). If the output is non-empty,
+    /// it is recorded as the first interaction in the transcript, with an empty
+    /// [hidden](crate::UserInput::hide()) input. By default, the startup output is discarded.
+    ///
+    /// Only the output produced before [initialization commands](Self::with_init_command())
+    /// is captured. Since the banner is not produced by an input, transcripts with it
+    /// cannot be directly [tested](crate::test::TestConfig) as snapshots.
+    #[must_use]
+    pub fn with_init_banner(mut self) -> Self {
+        self.init_banner = true;
+        self
+    }
+
+    /// Makes [`UserInput`]s be written to the shell one char at a time with the specified `delay`
+    /// between chars, emulating typing. This is useful for interactive programs that handle
+    /// input themselves (e.g., read it char by char from a PTY) and may drop chars if the entire
+    /// input line arrives at once.
+    ///
+    /// Initialization commands and other auxiliary inputs (e.g.,
+    /// [status checks](Self::with_status_check())) are still written line by line.
+    ///
+    /// # Performance
+    ///
+    /// Each user input takes at least `delay` multiplied by the number of chars in the input
+    /// to be written, so this can significantly slow down capturing and
+    /// [testing](crate::test::TestConfig) snapshots with long inputs. Delays of the order
+    /// of several milliseconds are usually enough.
+    #[must_use]
+    pub fn with_input_delay_between_chars(mut self, delay: Duration) -> Self {
+        self.input_char_delay = Some(delay);
+        self
+    }
+
+    /// Sets whether each line of [`UserInput`]s is wrapped into the bracketed paste sequences
+    /// (`\x1b[200~` and `\x1b[201~`) when written to the shell, as if the line was pasted
+    /// into the terminal. This can be used to script programs that enable bracketed paste mode
+    /// (e.g., some editors and REPLs), which otherwise may treat the input differently
+    /// (e.g., by auto-indenting it). The terminating newline is written outside the sequences.
+    ///
+    /// Initialization commands and other auxiliary inputs (e.g.,
+    /// [status checks](Self::with_status_check())) are not wrapped. Bracketed paste
+    /// is disabled by default.
+    ///
+    /// This option only makes sense for shells connected to a terminal, such as [`PtyCommand`].
+    /// For shells communicating via OS pipes, the sequences are passed to the executed programs
+    /// as-is, which can lead to unexpected results.
+    ///
+    /// [`PtyCommand`]: crate::PtyCommand
+    #[must_use]
+    pub fn with_bracketed_paste(mut self, bracketed_paste: bool) -> Self {
+        self.bracketed_paste = bracketed_paste;
+        self
+    }
+
+    /// Makes the form feed char (`\x0c`) in the captured output clear the output before it,
+    /// similar to how some terminals clear the screen on encountering it. This is useful
+    /// for programs that emit form feed to clear the screen; otherwise, the char is retained
+    /// in the output as-is (which can break the layout of the rendered transcript).
+    ///
+    /// Styles set in the cleared part of the output are retained. Clearing is applied
+    /// to the output of each interaction separately and is disabled by default.
+    #[must_use]
+    pub fn with_form_feed_clearing(mut self) -> Self {
+        self.form_feed_clears = true;
+        self
+    }
+
+    /// Captures the output written to the alternate screen buffer (e.g., by full-screen programs
+    /// like `less` or `vim`) instead of the primary screen. By default, the output between
+    /// switching to the alternate screen (`\x1b[?1049h`) and back (`\x1b[?1049l`) is discarded
+    /// when rendering or testing captured outputs; with this option, the primary screen output
+    /// is discarded instead.
+    ///
+    /// Similar to [form feed clearing](Self::with_form_feed_clearing()), styles set in
+    /// the discarded output are retained, and the option is applied to the output of each
+    /// interaction separately. Outputs not switching to the alternate screen are unaffected.
+    #[must_use]
+    pub fn with_alt_screen_capture(mut self) -> Self {
+        self.alt_screen_capture = true;
+        self
+    }
+
+    /// Sets the maximum size of the captured output for a single interaction in bytes.
+    /// If the output exceeds this limit, it is truncated (the remaining output is read and
+    /// discarded), and the interaction is [marked as truncated](crate::Interaction::is_truncated()).
+    /// This guards against runaway commands producing huge outputs.
+    ///
+    /// The limit applies to the decoded output, and is not set by default. Lines longer
+    /// than the limit are truncated as they are read, so the limit also bounds the memory used
+    /// for programs producing lots of output without newlines. Output is never cut in the middle
+    /// of a UTF-8 char or an escape sequence; if the truncated output contains escape sequences,
+    /// it is terminated with a reset sequence, so that it remains renderable.
+    ///
+    /// # Testing
+    ///
+    /// When [testing](crate::test::TestConfig) snapshots, the truncated output is compared
+    /// with the snapshot as-is. Thus, if the snapshot was recorded without a limit,
+    /// or with a different limit, the test will fail.
+    #[must_use]
+    pub fn with_max_output_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_output_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Makes input prompts reflect the current working directory of the shell, e.g. `/tmp $`.
+    /// To achieve this, the `pwd` command is executed in the shell before each [`UserInput`],
+    /// and its output is used as a part of the input prompt.
+    ///
+    /// This is mostly useful for rendering tutorial-like transcripts with `cd` commands.
+    ///
+    /// # Limitations
+    ///
+    /// - The shell must support the `pwd` command (e.g., `sh`, Bash or PowerShell).
+    /// - Since the prompts depend on the environment, they will generally not match the default
+    ///   prompts when [testing](crate::test::TestConfig) snapshots.
+    ///
+    /// [`UserInput`]: crate::UserInput
+    #[must_use]
+    pub fn with_cwd_prompt(mut self) -> Self {
+        self.cwd_prompt = true;
+        self
+    }
+
+    /// Sets the template computing the prompt for each recorded [`UserInput`] from
+    /// the [shell context](ShellContext) (e.g., the input index or the current working directory).
+    /// The computed prompt overrides the prompt specified for the input, and the default format
+    /// used by [`Self::with_cwd_prompt()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use term_transcript::{ShellOptions, Transcript, UserInput};
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut options = ShellOptions::default()
+    ///     .with_prompt_template(|ctx| format!("[{}] $", ctx.index + 1));
+    /// let transcript = Transcript::from_inputs(
+    ///     &mut options,
+    ///     vec![UserInput::command("echo hello"), UserInput::command("echo world")],
+    /// )?;
+    /// assert_eq!(transcript.interactions()[1].input().prompt(), Some("[2] $"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_prompt_template(
+        mut self,
+        template: impl Fn(&ShellContext<'_>) -> String + 'static,
+    ) -> Self {
+        self.prompt_template = Some(Box::new(template));
+        self
+    }
+
     /// Changes the current directory of the command.
     #[must_use]
     pub fn with_current_dir(mut self, current_dir: impl AsRef<Path>) -> Self {
@@ -166,6 +415,24 @@ impl<Cmd: ConfigureCommand> ShellOptions<Cmd> {
         self
     }
 
+    /// Sets the overall timeout for capturing a transcript via [`Transcript::from_inputs()`],
+    /// counted from spawning the shell. Once the timeout is exceeded, no further inputs
+    /// are sent to the shell, the shell is forcefully terminated and capturing fails
+    /// with an error of the [`TimedOut`](io::ErrorKind::TimedOut) kind. The partially captured
+    /// transcript is discarded.
+    ///
+    /// Unlike [`Self::with_io_timeout()`], this guards against commands that never stop
+    /// producing output. The timeout is enforced regardless of whether the output contains
+    /// newlines, and even if writing inputs to the shell is blocked. The kill timeout
+    /// is not set by default.
+    ///
+    /// [`Transcript::from_inputs()`]: crate::Transcript::from_inputs()
+    #[must_use]
+    pub fn with_kill_timeout(mut self, kill_timeout: Duration) -> Self {
+        self.kill_timeout = Some(kill_timeout);
+        self
+    }
+
     /// Adds an initialization command. Such commands are sent to the shell before executing
     /// any user input. The corresponding output from the shell is not captured.
     #[must_use]
@@ -181,6 +448,57 @@ impl<Cmd: ConfigureCommand> ShellOptions<Cmd> {
         self
     }
 
+    /// Sets the terminal width in chars for the shell via the `COLUMNS` env variable;
+    /// `LINES` is set to the standard 24 lines. Many programs respect these variables
+    /// when wrapping or truncating output, so this allows pipe-based captures to wrap predictably.
+    ///
+    /// This is a best-effort alternative to capturing via a [PTY](crate::PtyCommand)
+    /// (available with the `portable-pty` crate feature) with the specified size: programs
+    /// may ignore the env variables (e.g., if they query the terminal directly or disable
+    /// formatting when not writing to a terminal), and shells may override them.
+    #[must_use]
+    pub fn with_terminal_width(self, width: usize) -> Self {
+        self.with_env("COLUMNS", width.to_string())
+            .with_env("LINES", Self::TERMINAL_HEIGHT.to_string())
+    }
+
+    /// Clears the environment of the shell, so that it doesn't inherit any environment variables
+    /// from the parent process. This makes captures more reproducible across machines.
+    ///
+    /// Variables previously set via [`Self::with_env()`] are cleared as well, so this method
+    /// should be called before setting variables. Paths added via [`Self::with_additional_path()`]
+    /// are still appended to the `PATH` of the parent process. Since a completely empty
+    /// environment is usually too restrictive (e.g., executables cannot be located without
+    /// `PATH`), consider calling [`Self::with_baseline_env()`] afterwards.
+    ///
+    /// On Windows, shells may require certain variables (e.g., `SystemRoot`) to function
+    /// properly; these need to be set explicitly.
+    ///
+    /// If the command doesn't support clearing the environment (i.e., its
+    /// [`ConfigureCommand::env_clear()`] implementation returns an error), spawning the shell
+    /// will fail with this error.
+    #[must_use]
+    pub fn clear_env(mut self) -> Self {
+        if let Err(err) = self.command.env_clear() {
+            self.env_clear_error = Some(err);
+        }
+        self
+    }
+
+    /// Sets a minimal baseline environment for the shell, which is useful together
+    /// with [`Self::clear_env()`]:
+    ///
+    /// - `PATH` is copied from the parent process (if it is set)
+    /// - `LANG` is set to `C.UTF-8`, so that programs use UTF-8 output with the default locale
+    #[must_use]
+    pub fn with_baseline_env(mut self) -> Self {
+        if let Some(path) = env::var_os("PATH") {
+            self.command.env("PATH", &path);
+        }
+        self.command.env("LANG", OsStr::new("C.UTF-8"));
+        self
+    }
+
     /// Sets the line decoder for the shell. This allows for custom shell text encodings.
     ///
     /// The default decoder used is [the UTF-8 one](String::from_utf8()).
@@ -232,18 +550,48 @@ impl<Cmd: ConfigureCommand> ShellOptions<Cmd> {
     ///
     /// Panics if `command` contains newline chars (`'\n'` or `'\r'`).
     #[must_use]
-    pub fn with_status_check<F>(mut self, command: impl Into<String>, checker: F) -> Self
+    pub fn with_status_check<F>(self, command: impl Into<String>, checker: F) -> Self
     where
         F: Fn(&Captured) -> Option<ExitStatus> + 'static,
     {
-        let command = command.into();
-        assert!(
-            command.bytes().all(|ch| ch != b'\n' && ch != b'\r'),
-            "`command` contains a newline character ('\\n' or '\\r')"
-        );
+        self.with_status_check_commands([command], checker)
+    }
+
+    /// Sets a compound [`ExitStatus`] checker for the shell consisting of several commands.
+    /// This is useful for shells where a single command (like `echo $?`) is insufficient
+    /// to reliably determine the exit status, e.g., if an exit code needs to be complemented
+    /// with a sentinel.
+    ///
+    /// The `commands` are executed in order after each [`UserInput`], and their outputs
+    /// are captured separately and then joined with `\n` before being supplied
+    /// to the `checker`. Otherwise, the semantics is the same as for [`Self::with_status_check()`].
+    ///
+    /// [`UserInput`]: crate::UserInput
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `commands` are empty.
+    /// - Panics if any of `commands` contains newline chars (`'\n'` or `'\r'`).
+    #[must_use]
+    pub fn with_status_check_commands<F>(
+        mut self,
+        commands: impl IntoIterator<Item = impl Into<String>>,
+        checker: F,
+    ) -> Self
+    where
+        F: Fn(&Captured) -> Option<ExitStatus> + 'static,
+    {
+        let commands: Vec<String> = commands.into_iter().map(Into::into).collect();
+        assert!(!commands.is_empty(), "no status check commands");
+        for command in &commands {
+            assert!(
+                command.bytes().all(|ch| ch != b'\n' && ch != b'\r'),
+                "`command` contains a newline character ('\\n' or '\\r')"
+            );
+        }
 
         self.status_check = Some(StatusCheck {
-            command,
+            commands,
             response_checker: Box::new(checker),
         });
         self
@@ -288,43 +636,156 @@ impl<Cmd: ConfigureCommand> ShellOptions<Cmd> {
     }
 }
 
+#[cfg(feature = "portable-pty")]
+#[cfg_attr(docsrs, doc(cfg(feature = "portable-pty")))]
+impl ShellOptions<PtyCommand> {
+    /// Sets whether the pseudo-terminal should be switched to the raw mode before spawning
+    /// the shell. This is useful to capture programs that set up the terminal themselves
+    /// (e.g., editors or pagers). See [`PtyCommand::with_raw_mode()`] for details.
+    #[must_use]
+    pub fn with_raw_mode(mut self, raw_mode: bool) -> Self {
+        self.command.with_raw_mode(raw_mode);
+        self
+    }
+}
+
 impl<Cmd: SpawnShell> ShellOptions<Cmd> {
+    /// Returns the `PATH` of the parent process extended with [`Self::with_additional_path()`]
+    /// additions.
+    fn extended_path_var(&self) -> OsString {
+        #[cfg(unix)]
+        const PATH_SEPARATOR: &str = ":";
+        #[cfg(windows)]
+        const PATH_SEPARATOR: &str = ";";
+
+        let mut path_var = env::var_os("PATH").unwrap_or_default();
+        if !self.path_additions.is_empty() && !path_var.is_empty() {
+            path_var.push(PATH_SEPARATOR);
+        }
+        for (i, addition) in self.path_additions.iter().enumerate() {
+            path_var.push(addition);
+            if i + 1 < self.path_additions.len() {
+                path_var.push(PATH_SEPARATOR);
+            }
+        }
+        path_var
+    }
+
+    /// Checks that the programs invoked by `inputs` can be located without spawning the shell.
+    /// The program for each input is its first whitespace-separated token after leading
+    /// variable assignments (e.g., `cmd` for `FOO=1 cmd`); it is looked up
+    /// in the `PATH` of the parent process together with [`Self::with_additional_path()`]
+    /// additions (i.e., the same `PATH` the spawned shell would get). Programs containing
+    /// a path separator are checked directly. [Interrupts](crate::UserInput::interrupt()),
+    /// empty inputs, and inputs starting with a POSIX shell keyword (e.g., `for` or `if`)
+    /// or a builtin (e.g., `cd` or `export`) are skipped.
+    ///
+    /// This is useful as a preflight check, e.g. in CI, to fail fast with a clear error
+    /// instead of capturing "command not found" outputs.
+    ///
+    /// # Limitations
+    ///
+    /// Shell functions and aliases are not resolved via `PATH`, so they will be reported
+    /// as missing. Likewise, only keywords and builtins of POSIX-like shells are recognized.
+    ///
+    /// # Errors
+    ///
+    /// Returns the deduplicated list of programs that cannot be located, in the order
+    /// of their first occurrence.
+    pub fn verify_commands(
+        &self,
+        inputs: impl IntoIterator<Item = UserInput>,
+    ) -> Result<(), Vec<String>> {
+        let path_var = self.extended_path_var();
+        let search_dirs: Vec<_> = env::split_paths(&path_var).collect();
+
+        let mut missing = vec![];
+        for input in inputs {
+            if input.interrupt {
+                continue;
+            }
+            let Some(program) = input
+                .text
+                .split_whitespace()
+                .find(|token| !Self::is_variable_assignment(token))
+            else {
+                continue;
+            };
+            if SHELL_KEYWORDS.contains(&program) || SHELL_BUILTINS.contains(&program) {
+                continue;
+            }
+            if !Self::program_exists(program, &search_dirs)
+                && !missing.iter().any(|name| name == program)
+            {
+                missing.push(program.to_owned());
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Checks whether `token` is a variable assignment like `FOO=1`.
+    fn is_variable_assignment(token: &str) -> bool {
+        let Some((name, _)) = token.split_once('=') else {
+            return false;
+        };
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|ch| ch == '_' || ch.is_ascii_alphabetic())
+            && chars.all(|ch| ch == '_' || ch.is_ascii_alphanumeric())
+    }
+
+    fn program_exists(program: &str, search_dirs: &[PathBuf]) -> bool {
+        let is_file =
+            |path: &Path| path.is_file() || (cfg!(windows) && path.with_extension("exe").is_file());
+
+        if program.contains(['/', std::path::MAIN_SEPARATOR]) {
+            is_file(Path::new(program))
+        } else {
+            search_dirs.iter().any(|dir| is_file(&dir.join(program)))
+        }
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(
             level = "debug",
             skip(self),
             err,
-            fields(self.path_additions = ?self.path_additions)
+            fields(
+                self.program = self.command.program_name(),
+                self.path_additions = ?self.path_additions
+            )
         )
     )]
     fn spawn_shell(&mut self) -> io::Result<SpawnedShell<Cmd>> {
-        #[cfg(unix)]
-        const PATH_SEPARATOR: &str = ":";
-        #[cfg(windows)]
-        const PATH_SEPARATOR: &str = ";";
-
+        if let Some(err) = &self.env_clear_error {
+            let message = format!("cannot clear environment of the shell: {err}");
+            return Err(io::Error::new(err.kind(), message));
+        }
         if !self.path_additions.is_empty() {
-            let mut path_var = env::var_os("PATH").unwrap_or_default();
-            if !path_var.is_empty() {
-                path_var.push(PATH_SEPARATOR);
-            }
-            for (i, addition) in self.path_additions.iter().enumerate() {
-                path_var.push(addition);
-                if i + 1 < self.path_additions.len() {
-                    path_var.push(PATH_SEPARATOR);
-                }
-            }
+            let path_var = self.extended_path_var();
             self.command.env("PATH", &path_var);
         }
-        self.command.spawn_shell()
+        self.command.spawn_shell().map_err(|err| {
+            let Some(program) = self.command.program_name() else {
+                return err;
+            };
+            let message = format!("failed spawning shell `{program}`: {err}");
+            io::Error::new(err.kind(), message)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Transcript, UserInput};
+    use crate::{Interaction, Transcript, UserInput};
 
     #[cfg(any(unix, windows))]
     #[test]
@@ -367,4 +828,617 @@ mod tests {
         assert_eq!(output.trim(), "hello");
         Ok(())
     }
+
+    #[test]
+    fn transcript_with_multiline_helper() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default();
+        let inputs = vec![UserInput::multiline(["echo \\", "hello"])];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 1);
+        let interaction = &transcript.interactions()[0];
+        assert_eq!(interaction.input().as_ref(), "echo \\\nhello");
+        let output = interaction.output().as_ref();
+        assert_eq!(output.trim(), "hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_echo_detection() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default().with_echo_detection();
+        let inputs = vec![UserInput::command("echo hello")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 1);
+        let output = transcript.interactions()[0].output().as_ref();
+        assert_eq!(output.trim(), "hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn echo_detection_with_repl_quoting_inputs() -> anyhow::Result<()> {
+        // REPL emulation that quotes each input in an error
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(r#"while read -r line; do echo "error: unexpected input: $line"; done"#);
+        let mut options = ShellOptions::new(command).with_echo_detection();
+        let inputs = vec![UserInput::command("hello")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        let output = transcript.interactions()[0].output().as_ref();
+        assert_eq!(output.trim(), "error: unexpected input: hello");
+
+        // REPL emulation that echoes each input after a prompt
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(r#"while read -r line; do echo ">>> $line"; echo "out: $line"; done"#);
+        let mut options = ShellOptions::new(command).with_echo_detection();
+        let inputs = vec![UserInput::command("hello")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        let output = transcript.interactions()[0].output().as_ref();
+        assert_eq!(output.trim(), "out: hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_truncated_output() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default().with_max_output_bytes(100);
+        let inputs = vec![
+            UserInput::command("for i in $(seq 1 1000); do echo $i; done"),
+            UserInput::command("echo hello"),
+        ];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 2);
+        let interaction = &transcript.interactions()[0];
+        assert!(interaction.is_truncated());
+        let output = interaction.output().as_ref();
+        assert!(output.len() <= 100, "{output}");
+        assert!(output.starts_with("1\n2\n3\n"), "{output}");
+
+        let interaction = &transcript.interactions()[1];
+        assert!(!interaction.is_truncated());
+        assert_eq!(interaction.output().as_ref(), "hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_truncated_long_line() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default().with_max_output_bytes(100);
+        let inputs = vec![
+            UserInput::command("head -c 1000000 /dev/zero | tr '\\0' a; echo"),
+            UserInput::command("echo hello"),
+        ];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 2);
+        let interaction = &transcript.interactions()[0];
+        assert!(interaction.is_truncated());
+        let output = interaction.output().as_ref();
+        assert_eq!(output, "a".repeat(100));
+
+        let interaction = &transcript.interactions()[1];
+        assert!(!interaction.is_truncated());
+        assert_eq!(interaction.output().as_ref(), "hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_truncated_colored_output() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default().with_max_output_bytes(100);
+        let inputs = vec![
+            // Lines are truncated by the reader
+            UserInput::command(
+                r"for i in $(seq 1 100); do printf '\033[31m%s\033[0m ' $i; done; echo",
+            ),
+            // Output is truncated when combining lines
+            UserInput::command(r"for i in $(seq 1 100); do printf '\033[31m%s\n' $i; done"),
+            UserInput::command("echo hello"),
+        ];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 3);
+        for interaction in &transcript.interactions()[..2] {
+            assert!(interaction.is_truncated());
+            let output = interaction.output();
+            assert!(output.as_ref().len() <= 100, "{output:?}");
+            assert!(output.as_ref().ends_with("\u{1b}[0m"), "{output:?}");
+            let plaintext = output.to_plaintext()?;
+            assert!(plaintext.starts_with('1'), "{plaintext}");
+            output.to_html()?;
+        }
+
+        let interaction = &transcript.interactions()[2];
+        assert!(!interaction.is_truncated());
+        assert_eq!(interaction.output().as_ref(), "hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_sh_status_check_preset() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default().with_sh_status_check();
+        let inputs = vec![UserInput::command("true"), UserInput::command("false")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        let statuses: Vec<_> = transcript
+            .interactions()
+            .iter()
+            .map(Interaction::exit_status)
+            .collect();
+        assert_eq!(statuses, [Some(ExitStatus(0)), Some(ExitStatus(1))]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_cwd_prompt() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default().with_cwd_prompt();
+        let inputs = vec![UserInput::command("cd /"), UserInput::command("echo hello")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 2);
+        let first_prompt = transcript.interactions()[0].input().prompt().unwrap();
+        assert!(first_prompt.ends_with(" $"), "{first_prompt}");
+        let interaction = &transcript.interactions()[1];
+        assert_eq!(interaction.input().prompt(), Some("/ $"));
+        assert_eq!(interaction.output().as_ref(), "hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_prompt_template() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default()
+            .with_cwd_prompt()
+            .with_prompt_template(|ctx| {
+                let cwd = ctx.cwd.unwrap_or("?");
+                format!(
+                    "[{}] {cwd} {}",
+                    ctx.index,
+                    ctx.input.prompt().unwrap_or("$")
+                )
+            });
+        let inputs = vec![UserInput::command("cd /"), UserInput::repl("echo hello")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        assert_eq!(transcript.interactions().len(), 2);
+        let interaction = &transcript.interactions()[1];
+        assert_eq!(interaction.input().prompt(), Some("[1] / >>>"));
+        assert_eq!(interaction.output().as_ref().trim(), "hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_heavy_output_to_both_streams() -> anyhow::Result<()> {
+        // Both streams are redirected to the same OS pipe drained by a dedicated thread,
+        // so filling the pipe buffer with either stream must not lead to a deadlock.
+        let command = "yes out | head -n 20000; yes err | head -n 20000 >&2";
+        let mut options = ShellOptions::default();
+        let transcript = Transcript::from_inputs(&mut options, [UserInput::command(command)])?;
+
+        let output = transcript.interactions()[0].output().as_ref();
+        let count_lines = |line: &str| output.lines().filter(|&l| l == line).count();
+        assert_eq!(count_lines("out"), 20_000);
+        assert_eq!(count_lines("err"), 20_000);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_recorded_durations() -> anyhow::Result<()> {
+        let inputs = [
+            UserInput::command("sleep 0.2 && echo hello"),
+            UserInput::command("true"),
+        ];
+        let transcript = Transcript::from_inputs(&mut ShellOptions::default(), inputs)?;
+
+        let duration = transcript.interactions()[0].duration().unwrap();
+        assert!(duration >= Duration::from_millis(200), "{duration:?}");
+        // No output, so the duration is not recorded.
+        assert_eq!(transcript.interactions()[1].duration(), None);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_interrupt() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default().with_io_timeout(Duration::from_millis(200));
+        let inputs = [
+            UserInput::command("sleep 10; echo done"),
+            UserInput::interrupt(),
+            UserInput::command("echo after"),
+        ];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        let interactions = transcript.interactions();
+        assert_eq!(interactions[1].input().prompt(), None);
+        assert!(interactions[1].input().is_interrupt());
+        // `sleep` is interrupted, but the shell continues executing the command.
+        assert_eq!(interactions[1].output().as_ref().trim(), "done");
+        assert_eq!(interactions[2].output().as_ref().trim(), "after");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_kill_timeout() {
+        let mut options = ShellOptions::default()
+            .with_max_output_bytes(1_024)
+            .with_kill_timeout(Duration::from_secs(1));
+        let inputs = [UserInput::command("yes"), UserInput::command("echo after")];
+        let start = std::time::Instant::now();
+        let err = Transcript::from_inputs(&mut options, inputs).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        let err = err.to_string();
+        assert!(err.contains("kill timeout"), "{err}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_timeout_with_output_without_newlines() {
+        // The I/O timeout exceeds the kill timeout, so the latter must be enforced
+        // without waiting for the next line of output.
+        let mut options = ShellOptions::default()
+            .with_io_timeout(Duration::from_secs(10))
+            .with_max_output_bytes(1_024)
+            .with_kill_timeout(Duration::from_secs(1));
+        let inputs = [UserInput::command("cat /dev/zero")];
+        let start = std::time::Instant::now();
+        let err = Transcript::from_inputs(&mut options, inputs).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_timeout_with_blocked_input() {
+        let mut options = ShellOptions::default()
+            .with_io_timeout(Duration::from_millis(200))
+            .with_kill_timeout(Duration::from_secs(1));
+        // `sleep` doesn't consume the shell input, so writing a large input blocks.
+        let large_input = format!("# {}", "x".repeat(1 << 20));
+        let inputs = [
+            UserInput::command("sleep 10"),
+            UserInput::command(large_input),
+        ];
+        let start = std::time::Instant::now();
+        let err = Transcript::from_inputs(&mut options, inputs).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_init_banner() -> anyhow::Result<()> {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("echo 'Welcome!'; exec sh");
+        let mut options = ShellOptions::new(command).with_init_banner();
+        let transcript = Transcript::from_inputs(&mut options, [UserInput::command("echo hello")])?;
+
+        let interactions = transcript.interactions();
+        assert_eq!(interactions.len(), 2);
+        assert!(interactions[0].input().hidden);
+        assert_eq!(interactions[0].output().as_ref(), "Welcome!");
+        assert_eq!(interactions[1].output().as_ref().trim(), "hello");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_input_delay_between_chars() -> anyhow::Result<()> {
+        let mut options = ShellOptions::new(Command::new("sh"))
+            .with_input_delay_between_chars(Duration::from_millis(5));
+        let inputs = [
+            UserInput::command("echo hello"),
+            UserInput::command("echo \"Привет, мир!\""),
+        ];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        let interactions = transcript.interactions();
+        assert_eq!(interactions.len(), 2);
+        assert_eq!(interactions[0].output().as_ref().trim(), "hello");
+        assert_eq!(interactions[1].output().as_ref().trim(), "Привет, мир!");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_form_feed_clearing() -> anyhow::Result<()> {
+        let input = UserInput::command(r"printf 'old\fnew\n'");
+        let mut options = ShellOptions::new(Command::new("sh"));
+        let transcript = Transcript::from_inputs(&mut options, [input.clone()])?;
+        assert_eq!(
+            transcript.interactions()[0].output().as_ref(),
+            "old\u{c}new"
+        );
+
+        let mut options = ShellOptions::new(Command::new("sh")).with_form_feed_clearing();
+        let transcript = Transcript::from_inputs(&mut options, [input])?;
+        assert_eq!(transcript.interactions()[0].output().as_ref(), "new");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_alt_screen_capture() -> anyhow::Result<()> {
+        let input = UserInput::command(r"printf 'before\033[?1049hfull screen\033[?1049l\n'");
+        let mut options = ShellOptions::new(Command::new("sh"));
+        let transcript = Transcript::from_inputs(&mut options, [input.clone()])?;
+        let output = transcript.interactions()[0].output();
+        assert_eq!(output.to_plaintext()?, "before");
+
+        let mut options = ShellOptions::new(Command::new("sh")).with_alt_screen_capture();
+        let transcript = Transcript::from_inputs(&mut options, [input])?;
+        let output = transcript.interactions()[0].output();
+        assert_eq!(output.as_ref(), "full screen");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_cleared_env() -> anyhow::Result<()> {
+        let mut options = ShellOptions::new(Command::new("sh"))
+            .with_env("TEST_VAR", "test")
+            .clear_env()
+            .with_baseline_env()
+            .with_env("OTHER_VAR", "other");
+        let inputs = [
+            UserInput::command(r#"echo "[$TEST_VAR] [$OTHER_VAR] [$LANG]""#),
+            UserInput::command(r#"[ -n "$PATH" ] && echo "has PATH""#),
+        ];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        let interactions = transcript.interactions();
+        assert_eq!(
+            interactions[0].output().as_ref().trim(),
+            "[] [other] [C.UTF-8]"
+        );
+        assert_eq!(interactions[1].output().as_ref().trim(), "has PATH");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn clearing_env_for_command_without_support() {
+        /// Command that doesn't override `ConfigureCommand::env_clear()`.
+        #[derive(Debug)]
+        struct LegacyCommand(Command);
+
+        impl ConfigureCommand for LegacyCommand {
+            fn current_dir(&mut self, dir: &Path) {
+                self.0.current_dir(dir);
+            }
+
+            fn env(&mut self, name: &str, value: &OsStr) {
+                self.0.env(name, value);
+            }
+        }
+
+        impl SpawnShell for LegacyCommand {
+            type ShellProcess = <Command as SpawnShell>::ShellProcess;
+            type Reader = <Command as SpawnShell>::Reader;
+            type Writer = <Command as SpawnShell>::Writer;
+
+            fn spawn_shell(&mut self) -> io::Result<SpawnedShell<Self>> {
+                let SpawnedShell {
+                    shell,
+                    reader,
+                    writer,
+                } = self.0.spawn_shell()?;
+                Ok(SpawnedShell {
+                    shell,
+                    reader,
+                    writer,
+                })
+            }
+        }
+
+        let mut options = ShellOptions::new(LegacyCommand(Command::new("sh")));
+        let transcript =
+            Transcript::from_inputs(&mut options, [UserInput::command("echo test")]).unwrap();
+        assert_eq!(transcript.interactions()[0].output().as_ref(), "test");
+
+        let mut options = options.clear_env();
+        let err =
+            Transcript::from_inputs(&mut options, [UserInput::command("echo test")]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(err.to_string().contains("clear environment"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_terminal_width() -> anyhow::Result<()> {
+        let mut options = ShellOptions::new(Command::new("sh")).with_terminal_width(100);
+        let inputs = [UserInput::command(r#"echo "$COLUMNS x $LINES""#)];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+        assert_eq!(
+            transcript.interactions()[0].output().as_ref().trim(),
+            "100 x 24"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run_lists_shell_lines() {
+        let options = ShellOptions::sh()
+            .with_init_command("export TEST_VAR=1")
+            .with_echo_detection()
+            .with_cwd_prompt();
+        let inputs = [
+            UserInput::command("echo \"$TEST_VAR\""),
+            UserInput::command("echo 1 &&\necho 2"),
+            UserInput::command("wc -l").with_stdin_data("first\nsecond"),
+            UserInput::interrupt(),
+        ];
+        let lines = options.dry_run(inputs);
+
+        assert_eq!(
+            lines,
+            [
+                "export TEST_VAR=1",
+                "# term-transcript-echo-probe-<nonce>",
+                "pwd",
+                "echo \"$TEST_VAR\"",
+                "echo $?",
+                "pwd",
+                "echo 1 &&",
+                "echo 2",
+                "echo $?",
+                "pwd",
+                "wc -l",
+                "first",
+                "second",
+                "^D",
+                "echo $?",
+                "^C",
+                "echo $?",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_with_compound_status_check() -> anyhow::Result<()> {
+        let mut options = ShellOptions::default().with_status_check_commands(
+            ["echo $?", "echo sentinel"],
+            |response| {
+                let response = response.to_plaintext().ok()?;
+                let (code, sentinel) = response.trim().split_once('\n')?;
+                (sentinel.trim() == "sentinel").then_some(ExitStatus(code.trim().parse().ok()?))
+            },
+        );
+        let inputs = [UserInput::command("true"), UserInput::command("false")];
+        let transcript = Transcript::from_inputs(&mut options, inputs)?;
+
+        let statuses: Vec<_> = transcript
+            .interactions()
+            .iter()
+            .map(Interaction::exit_status)
+            .collect();
+        assert_eq!(statuses, [Some(ExitStatus(0)), Some(ExitStatus(1))]);
+        Ok(())
+    }
+
+    #[test]
+    fn detecting_shell_defaults() {
+        let inputs = || [UserInput::command("test")];
+
+        let options = ShellOptions::new(Command::new("zsh")).with_detected_shell_defaults();
+        assert_eq!(options.dry_run(inputs()), ["test", "echo $?"]);
+
+        let options =
+            ShellOptions::new(Command::new("/usr/local/bin/pwsh")).with_detected_shell_defaults();
+        assert_eq!(
+            options.dry_run(inputs()),
+            [
+                "function prompt { }",
+                "# term-transcript-echo-probe-<nonce>",
+                "test",
+                "echo $?"
+            ]
+        );
+
+        let options = ShellOptions::new(Command::new("python3")).with_detected_shell_defaults();
+        assert_eq!(options.dry_run(inputs()), ["test"]);
+    }
+
+    #[test]
+    fn dry_run_with_bracketed_paste() {
+        let options = ShellOptions::new(Command::new("bash")).with_bracketed_paste(true);
+        let inputs = [UserInput::command("cat").with_stdin_data("data")];
+        assert_eq!(
+            options.dry_run(inputs),
+            ["\u{1b}[200~cat\u{1b}[201~", "data", "^D"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stdin_data_is_not_supported_with_pipes() {
+        let marker_path =
+            env::temp_dir().join(format!("term-transcript-stdin-{}", std::process::id()));
+        let mut options = ShellOptions::default();
+        let inputs = [
+            UserInput::command(format!("touch '{}'", marker_path.display())),
+            UserInput::command("cat").with_stdin_data("test"),
+        ];
+        let err = Transcript::from_inputs(&mut options, inputs).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        // The shell should not receive any inputs.
+        assert!(!marker_path.exists());
+    }
+
+    #[test]
+    fn spawn_error_mentions_program_name() {
+        let command = Command::new("term-transcript-non-existing-shell");
+        assert_eq!(
+            command.program_name(),
+            Some("term-transcript-non-existing-shell")
+        );
+        assert_eq!(ShellOptions::sh().command.program_name(), Some("sh"));
+
+        let mut options = ShellOptions::new(command);
+        let err = Transcript::from_inputs(&mut options, [UserInput::command("echo")]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let err = err.to_string();
+        assert!(
+            err.contains("failed spawning shell `term-transcript-non-existing-shell`"),
+            "{err}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn verifying_commands() {
+        let options = ShellOptions::default();
+        let inputs = [
+            UserInput::command("ls -l"),
+            UserInput::interrupt(),
+            UserInput::command("/bin/sh -c 'echo test'"),
+        ];
+        options.verify_commands(inputs).unwrap();
+
+        let inputs = [
+            UserInput::command("term-transcript-non-existing --help"),
+            UserInput::command("ls"),
+            UserInput::command("term-transcript-non-existing"),
+            UserInput::command("./non-existing"),
+        ];
+        let missing = options.verify_commands(inputs).unwrap_err();
+        assert_eq!(missing, ["term-transcript-non-existing", "./non-existing"]);
+
+        let inputs = [
+            UserInput::command("for i in $(seq 1 10); do echo $i; done"),
+            UserInput::command("if true; then echo yes; fi"),
+            UserInput::command("cd /"),
+            UserInput::command("export FOO=1"),
+            UserInput::command("FOO=1"),
+            UserInput::command("FOO=1 BAR_2=x ls"),
+        ];
+        options.verify_commands(inputs).unwrap();
+        let inputs = [UserInput::command("FOO=1 term-transcript-non-existing")];
+        let missing = options.verify_commands(inputs).unwrap_err();
+        assert_eq!(missing, ["term-transcript-non-existing"]);
+
+        let test_exe = env::current_exe().unwrap();
+        let test_exe_name = test_exe.file_name().unwrap().to_str().unwrap();
+        let inputs = || [UserInput::command(test_exe_name)];
+        assert!(options.verify_commands(inputs()).is_err());
+        let options = options.with_additional_path(test_exe.parent().unwrap());
+        options.verify_commands(inputs()).unwrap();
+    }
 }
@@ -23,6 +23,20 @@ enum StdShellType {
     PowerShell,
 }
 
+impl StdShellType {
+    /// Detects the shell type from the name of the executed `program`. Only well-known
+    /// shell names are recognized.
+    fn detect(program: &OsStr) -> Option<Self> {
+        let name = Path::new(program).file_stem()?.to_str()?;
+        Some(match name.to_ascii_lowercase().as_str() {
+            "sh" | "dash" | "ash" | "ksh" | "zsh" => Self::Sh,
+            "bash" => Self::Bash,
+            "pwsh" | "powershell" => Self::PowerShell,
+            _ => return None,
+        })
+    }
+}
+
 /// Shell interpreter that brings additional functionality for [`ShellOptions`].
 #[derive(Debug)]
 pub struct StdShell {
@@ -38,6 +52,15 @@ impl ConfigureCommand for StdShell {
     fn env(&mut self, name: &str, value: &OsStr) {
         self.command.env(name, value);
     }
+
+    fn env_clear(&mut self) -> io::Result<()> {
+        self.command.env_clear();
+        Ok(())
+    }
+
+    fn program_name(&self) -> Option<&str> {
+        self.command.get_program().to_str()
+    }
 }
 
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", ret))]
@@ -56,23 +79,81 @@ fn check_ps_exit_code(response: &Captured) -> Option<ExitStatus> {
     }
 }
 
+impl<Cmd: ConfigureCommand> ShellOptions<Cmd> {
+    /// Sets the [status check](Self::with_status_check()) for `sh`-like shells (e.g., `sh`,
+    /// Bash or Zsh). The check executes `echo $?` and parses the exit code from its output.
+    ///
+    /// This is useful for shells not covered by [`StdShell`], e.g. ones spawned
+    /// in a pseudo-terminal.
+    #[must_use]
+    pub fn with_sh_status_check(self) -> Self {
+        self.with_status_check("echo $?", check_sh_exit_code)
+    }
+
+    /// Sets the [status check](Self::with_status_check()) for PowerShell. The check executes
+    /// `echo $?` which outputs `True` if the latest command has succeeded and `False` otherwise.
+    #[must_use]
+    pub fn with_pwsh_status_check(self) -> Self {
+        self.with_status_check("echo $?", check_ps_exit_code)
+    }
+}
+
+impl ShellOptions {
+    /// Sets defaults appropriate for the shell based on the program name of the shell command.
+    /// This is a best-effort alternative to [`StdShell`]-based options (e.g., [`Self::sh()`])
+    /// for custom commands. The detection is conservative; only the following programs
+    /// are recognized:
+    ///
+    /// - `sh`, `dash`, `ash`, `ksh`, `zsh` and `bash`: sets the [`sh` status check].
+    /// - `pwsh` and `powershell`: sets the [PowerShell status check], disables the prompt
+    ///   via an init command, and enables [echo detection](Self::with_echo_detection()).
+    ///
+    /// For other programs, the options are returned unchanged. The defaults can be overridden
+    /// by calling the corresponding methods after this one.
+    ///
+    /// [`sh` status check]: Self::with_sh_status_check()
+    /// [PowerShell status check]: Self::with_pwsh_status_check()
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::process::Command;
+    /// # use term_transcript::{ShellOptions, UserInput};
+    /// let options = ShellOptions::new(Command::new("/usr/bin/bash"))
+    ///     .with_detected_shell_defaults();
+    /// let lines = options.dry_run([UserInput::command("ls")]);
+    /// assert_eq!(lines, ["ls", "echo $?"]);
+    /// ```
+    #[must_use]
+    pub fn with_detected_shell_defaults(self) -> Self {
+        match StdShellType::detect(self.command.get_program()) {
+            Some(StdShellType::Sh | StdShellType::Bash) => self.with_sh_status_check(),
+            Some(StdShellType::PowerShell) => self
+                .with_init_command("function prompt { }")
+                .with_echo_detection()
+                .with_pwsh_status_check(),
+            None => self,
+        }
+    }
+}
+
 impl ShellOptions<StdShell> {
     /// Creates options for an `sh` shell.
     pub fn sh() -> Self {
-        let this = Self::new(StdShell {
+        Self::new(StdShell {
             shell_type: StdShellType::Sh,
             command: Command::new("sh"),
-        });
-        this.with_status_check("echo $?", check_sh_exit_code)
+        })
+        .with_standard_status_check()
     }
 
     /// Creates options for a Bash shell.
     pub fn bash() -> Self {
-        let this = Self::new(StdShell {
+        Self::new(StdShell {
             shell_type: StdShellType::Bash,
             command: Command::new("bash"),
-        });
-        this.with_status_check("echo $?", check_sh_exit_code)
+        })
+        .with_standard_status_check()
     }
 
     /// Creates options for PowerShell 6+ (the one with the `pwsh` executable).
@@ -86,7 +167,21 @@ impl ShellOptions<StdShell> {
         };
         Self::new(command)
             .with_init_command("function prompt { }")
-            .with_status_check("echo $?", check_ps_exit_code)
+            .with_standard_status_check()
+    }
+
+    /// Sets the [status check](Self::with_status_check()) appropriate for the shell type:
+    /// [`Self::with_sh_status_check()`] for `sh` and Bash, and [`Self::with_pwsh_status_check()`]
+    /// for PowerShell.
+    ///
+    /// This check is set by default in the [`Self::sh()`], [`Self::bash()`] and [`Self::pwsh()`]
+    /// constructors, so this method is only useful to restore the check after it was overridden.
+    #[must_use]
+    pub fn with_standard_status_check(self) -> Self {
+        match self.command.shell_type {
+            StdShellType::Sh | StdShellType::Bash => self.with_sh_status_check(),
+            StdShellType::PowerShell => self.with_pwsh_status_check(),
+        }
     }
 
     /// Creates an alias for the binary at `path_to_bin`, which should be an absolute path.
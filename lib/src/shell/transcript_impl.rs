@@ -1,20 +1,61 @@
 //! Shell-related `Transcript` methods.
 
 use std::{
-    io::{self, BufRead, BufReader, LineWriter, Read},
-    iter,
+    borrow::Cow,
+    io::{self, LineWriter, Read, Write},
+    iter, mem,
     process::{Command, Stdio},
     sync::mpsc,
     thread,
-    time::Duration,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use super::ShellOptions;
+use super::{ShellContext, ShellOptions};
 use crate::{
+    term::{clear_before_form_feed, retain_alt_screen},
     traits::{ShellProcess, SpawnShell, SpawnedShell},
     Captured, Interaction, Transcript, UserInput,
 };
 
+/// Command used to query the current working dir of the shell.
+const CWD_COMMAND: &str = "pwd";
+/// Prefix of the comment line used to detect whether the shell echoes its input.
+const ECHO_PROBE_PREFIX: &str = "# term-transcript-echo-probe-";
+/// Sequence starting bracketed paste.
+const BRACKETED_PASTE_START: &str = "\u{1b}[200~";
+/// Sequence ending bracketed paste.
+const BRACKETED_PASTE_END: &str = "\u{1b}[201~";
+/// SGR sequence resetting all styles; appended to the truncated output.
+const SGR_RESET: &str = "\u{1b}[0m";
+
+/// Step of sending a user input to the shell. Shared by capturing and
+/// [dry runs](ShellOptions::dry_run()), so that they cannot diverge.
+#[derive(Debug, Clone, Copy)]
+enum InputStep<'a> {
+    /// Line of the input text, subject to bracketed paste and char delays.
+    Line(&'a str),
+    /// Line of the [stdin data](UserInput::with_stdin_data()).
+    DataLine(&'a str),
+    /// End of the stdin data.
+    Eof,
+    /// Interrupt signal.
+    Interrupt,
+}
+
+impl<'a> InputStep<'a> {
+    fn sequence(input: &'a UserInput) -> Vec<Self> {
+        if input.interrupt {
+            return vec![Self::Interrupt];
+        }
+        let mut steps: Vec<_> = input.text.split('\n').map(Self::Line).collect();
+        if let Some(data) = &input.stdin_data {
+            steps.extend(data.lines().map(Self::DataLine));
+            steps.push(Self::Eof);
+        }
+        steps
+    }
+}
+
 #[derive(Debug)]
 struct Timeouts {
     inner: iter::Chain<iter::Once<Duration>, iter::Repeat<Duration>>,
@@ -33,6 +74,147 @@ impl Timeouts {
     }
 }
 
+/// Line output by the shell.
+#[derive(Debug)]
+struct OutputLine {
+    bytes: Vec<u8>,
+    /// Was the line truncated because it has exceeded the output size limit?
+    is_truncated: bool,
+}
+
+/// Receiver of lines output by the shell, which doesn't block past the kill timeout.
+#[derive(Debug)]
+struct LinesReceiver {
+    inner: mpsc::Receiver<OutputLine>,
+    deadline: Option<Instant>,
+}
+
+impl LinesReceiver {
+    fn recv_timeout(&self, timeout: Duration) -> Result<OutputLine, mpsc::RecvTimeoutError> {
+        let timeout = if let Some(deadline) = self.deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(mpsc::RecvTimeoutError::Timeout);
+            }
+            timeout.min(remaining)
+        } else {
+            timeout
+        };
+        self.inner.recv_timeout(timeout)
+    }
+}
+
+#[derive(Debug)]
+enum WriteCommand {
+    Write(Vec<u8>),
+    Flush,
+}
+
+/// Writer to the shell input that performs writes in a separate thread and doesn't block
+/// past the kill timeout. A write can block indefinitely if the shell doesn't consume its input
+/// (e.g., because it executes a long-running command); in this case, the writer thread
+/// is abandoned.
+#[derive(Debug)]
+struct DeadlineWriter {
+    commands: mpsc::Sender<WriteCommand>,
+    results: mpsc::Receiver<io::Result<()>>,
+    deadline: Instant,
+}
+
+impl DeadlineWriter {
+    fn spawn(writer: impl io::Write + Send + 'static, deadline: Instant) -> Self {
+        let (commands, commands_recv) = mpsc::channel();
+        let (results_send, results) = mpsc::channel();
+        thread::spawn(move || {
+            #[cfg(feature = "tracing")]
+            let _entered = tracing::debug_span!("writer_thread").entered();
+
+            let mut writer = LineWriter::new(writer);
+            for command in commands_recv {
+                let result = match command {
+                    WriteCommand::Write(data) => writer.write_all(&data),
+                    WriteCommand::Flush => writer.flush(),
+                };
+                if results_send.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            commands,
+            results,
+            deadline,
+        }
+    }
+
+    fn execute(&self, command: WriteCommand) -> io::Result<()> {
+        if self.commands.send(command).is_err() {
+            let message = "writer thread for the shell input has exited";
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, message));
+        }
+        let timeout = self.deadline.saturating_duration_since(Instant::now());
+        self.results.recv_timeout(timeout).unwrap_or_else(|_| {
+            let message = "writing to the shell input was blocked past the kill timeout";
+            Err(io::Error::new(io::ErrorKind::TimedOut, message))
+        })
+    }
+}
+
+impl io::Write for DeadlineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.execute(WriteCommand::Write(buf.to_vec()))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.execute(WriteCommand::Flush)
+    }
+}
+
+impl<Cmd: SpawnShell> ShellOptions<Cmd> {
+    /// Returns the ordered list of raw lines that would be sent to the shell when
+    /// [capturing a transcript](Transcript::from_inputs()) for the provided `inputs`,
+    /// without spawning the shell. This includes initialization commands, echo detection probes,
+    /// working dir queries and exit status checks. This can be useful to debug
+    /// misbehaving captures.
+    ///
+    /// The output is approximate in the following ways:
+    ///
+    /// - The random nonce in the echo detection probe is replaced with `<nonce>`.
+    /// - [Interrupts](UserInput::interrupt()) are represented as `^C` lines.
+    /// - The end of [stdin data](UserInput::with_stdin_data()) is represented as a `^D` line.
+    ///
+    /// If [bracketed paste](Self::with_bracketed_paste()) is enabled, input lines are wrapped
+    /// in the corresponding escape sequences.
+    pub fn dry_run(&self, inputs: impl IntoIterator<Item = UserInput>) -> Vec<String> {
+        let mut lines = self.init_commands.clone();
+        if self.echo_detection {
+            lines.push(format!("{ECHO_PROBE_PREFIX}<nonce>"));
+        }
+
+        for input in inputs {
+            if self.cwd_prompt && !input.interrupt {
+                lines.push(CWD_COMMAND.to_owned());
+            }
+            for step in InputStep::sequence(&input) {
+                lines.push(match step {
+                    InputStep::Line(line) if self.bracketed_paste => {
+                        format!("{BRACKETED_PASTE_START}{line}{BRACKETED_PASTE_END}")
+                    }
+                    InputStep::Line(line) | InputStep::DataLine(line) => line.to_owned(),
+                    InputStep::Eof => "^D".to_owned(),
+                    InputStep::Interrupt => UserInput::INTERRUPT_TEXT.to_owned(),
+                });
+            }
+            if let Some(status_check) = &self.status_check {
+                lines.extend_from_slice(status_check.commands());
+            }
+        }
+        lines
+    }
+}
+
 impl Transcript {
     #[cfg(not(windows))]
     #[cfg_attr(
@@ -53,6 +235,45 @@ impl Transcript {
         writeln!(writer, "{line}\r")
     }
 
+    /// Writes a line of the user input, taking input-related `options` into account.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(options, writer), err)
+    )]
+    fn write_input_line<Cmd: SpawnShell>(
+        options: &ShellOptions<Cmd>,
+        writer: &mut impl io::Write,
+        line: &str,
+    ) -> io::Result<()> {
+        if options.bracketed_paste {
+            writer.write_all(BRACKETED_PASTE_START.as_bytes())?;
+        }
+        if let Some(delay) = options.input_char_delay {
+            Self::write_with_delay(writer, line, delay)?;
+        } else {
+            writer.write_all(line.as_bytes())?;
+        }
+        if options.bracketed_paste {
+            writer.write_all(BRACKETED_PASTE_END.as_bytes())?;
+        }
+        Self::write_line(writer, "")
+    }
+
+    /// Writes the text char by char, flushing the writer and sleeping for `delay` after each char.
+    fn write_with_delay(
+        writer: &mut impl io::Write,
+        text: &str,
+        delay: Duration,
+    ) -> io::Result<()> {
+        let mut buffer = [0_u8; 4];
+        for ch in text.chars() {
+            writer.write_all(ch.encode_utf8(&mut buffer).as_bytes())?;
+            writer.flush()?;
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "debug", skip(lines_recv), err)
@@ -61,12 +282,15 @@ impl Transcript {
     // ^ The received `line` is used only for debug purposes
     fn read_echo(
         input_line: &str,
-        lines_recv: &mpsc::Receiver<Vec<u8>>,
+        lines_recv: &LinesReceiver,
         io_timeout: Duration,
     ) -> io::Result<()> {
         if let Ok(line) = lines_recv.recv_timeout(io_timeout) {
             #[cfg(feature = "tracing")]
-            tracing::debug!(line_utf8 = std::str::from_utf8(&line).ok(), "received line");
+            tracing::debug!(
+                line_utf8 = std::str::from_utf8(&line.bytes).ok(),
+                "received line"
+            );
             Ok(())
         } else {
             let err =
@@ -80,13 +304,25 @@ impl Transcript {
         tracing::instrument(level = "debug", skip_all, ret, err)
     )]
     fn read_output(
-        lines_recv: &mpsc::Receiver<Vec<u8>>,
+        lines_recv: &LinesReceiver,
         mut timeouts: Timeouts,
         line_decoder: &mut dyn FnMut(Vec<u8>) -> io::Result<String>,
-    ) -> io::Result<String> {
+        max_bytes: Option<usize>,
+    ) -> io::Result<(String, bool, Option<Instant>)> {
         let mut output = String::new();
-
-        while let Ok(mut line) = lines_recv.recv_timeout(timeouts.next()) {
+        let mut is_truncated = false;
+        let mut last_line_instant = None;
+
+        while let Ok(OutputLine {
+            bytes: mut line,
+            is_truncated: is_line_truncated,
+        }) = lines_recv.recv_timeout(timeouts.next())
+        {
+            last_line_instant = Some(Instant::now());
+            if is_truncated {
+                // Drain the remaining output without buffering it.
+                continue;
+            }
             if line.last() == Some(&b'\r') {
                 // Normalize `\r\n` line ending to `\n`.
                 line.pop();
@@ -99,12 +335,22 @@ impl Transcript {
             tracing::debug!(?mapped_line, "mapped received line");
             output.push_str(&mapped_line);
             output.push('\n');
+
+            if let Some(max_bytes) = max_bytes {
+                if is_line_truncated || output.len() > max_bytes {
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(max_bytes, "truncating output");
+
+                    Self::truncate_output(&mut output, max_bytes);
+                    is_truncated = true;
+                }
+            }
         }
 
         if output.ends_with('\n') {
             output.truncate(output.len() - 1);
         }
-        Ok(output)
+        Ok((output, is_truncated, last_line_instant))
     }
 
     /// Constructs a transcript from the sequence of given user `input`s.
@@ -117,6 +363,7 @@ impl Transcript {
     /// - Returns an error if spawning the shell or any operations with it fail (such as reading
     ///   stdout / stderr, or writing commands to stdin), or if the shell exits before all commands
     ///   are executed.
+    /// - Returns an error if the [kill timeout](ShellOptions::with_kill_timeout()) is exceeded.
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(
@@ -140,42 +387,251 @@ impl Transcript {
             writer,
         } = options.spawn_shell()?;
 
-        let stdout = BufReader::new(reader);
+        let start_instant = Instant::now();
+        let deadline = options.kill_timeout.map(|timeout| start_instant + timeout);
+        let max_line_len = options.max_output_bytes;
         let (out_lines_send, out_lines_recv) = mpsc::channel();
         let io_handle = thread::spawn(move || {
             #[cfg(feature = "tracing")]
             let _entered = tracing::debug_span!("reader_thread").entered();
+            Self::read_lines(reader, &out_lines_send, max_line_len, deadline);
+        });
 
-            let mut lines = stdout.split(b'\n');
-            while let Some(Ok(line)) = lines.next() {
+        let lines_recv = LinesReceiver {
+            inner: out_lines_recv,
+            deadline,
+        };
+        // Dropping `stdin` after recording signals to the shell that we're done.
+        let result = if let Some(deadline) = deadline {
+            let mut stdin = DeadlineWriter::spawn(writer, deadline);
+            Self::record_inputs(options, inputs, &lines_recv, &mut shell, &mut stdin)
+        } else {
+            let mut stdin = LineWriter::new(writer);
+            Self::record_inputs(options, inputs, &lines_recv, &mut shell, &mut stdin)
+        };
+
+        if let Some(kill_timeout) = options.kill_timeout {
+            if start_instant.elapsed() >= kill_timeout {
                 #[cfg(feature = "tracing")]
-                tracing::debug!(line_utf8 = std::str::from_utf8(&line).ok(), "received line");
+                tracing::warn!(?kill_timeout, "kill timeout exceeded, terminating shell");
+
+                shell.terminate()?;
+                // The reader thread is not joined: it may be blocked if the shell output
+                // is held open by a still running child process of the shell.
+                drop(io_handle);
+                let message = format!(
+                    "capturing transcript took longer than the kill timeout ({kill_timeout:?}); \
+                     the shell was terminated"
+                );
+                return Err(io::Error::new(io::ErrorKind::TimedOut, message));
+            }
+        }
+        let transcript = result?;
 
-                if out_lines_send.send(line).is_err() {
-                    #[cfg(feature = "tracing")]
-                    tracing::debug!("receiver dropped, breaking reader loop");
-                    break;
+        // Give a chance for the shell process to exit. This will reduce kill errors later.
+        thread::sleep(options.io_timeout / 4);
+
+        shell.terminate()?;
+        io_handle.join().ok(); // the I/O thread should not panic, so we ignore errors here
+        Ok(transcript)
+    }
+
+    /// Reads lines from the shell `reader` and sends them via `lines_send`. Lines exceeding
+    /// `max_line_len` bytes are truncated while being read, so that a program producing
+    /// lots of output without newlines doesn't make the reader buffer all of it.
+    fn read_lines(
+        mut reader: impl Read,
+        lines_send: &mpsc::Sender<OutputLine>,
+        max_line_len: Option<usize>,
+        deadline: Option<Instant>,
+    ) {
+        let mut buffer = [0_u8; 4_096];
+        let mut line = vec![];
+        let mut is_line_truncated = false;
+        loop {
+            let read_len = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                // Disconnecting the channel unblocks reading output in the main thread.
+                #[cfg(feature = "tracing")]
+                tracing::debug!("kill timeout exceeded, breaking reader loop");
+                return;
+            }
+
+            for chunk in buffer[..read_len].split_inclusive(|&byte| byte == b'\n') {
+                let (chunk, ends_line) = match chunk.strip_suffix(b"\n") {
+                    Some(chunk) => (chunk, true),
+                    None => (chunk, false),
+                };
+                if !is_line_truncated {
+                    line.extend_from_slice(chunk);
+                    if let Some(max_len) = max_line_len.filter(|&max_len| line.len() > max_len) {
+                        // The line is sent right away; its remainder is discarded.
+                        Self::truncate_line(&mut line, max_len);
+                        is_line_truncated = true;
+                        if !Self::send_line(lines_send, mem::take(&mut line), true) {
+                            return;
+                        }
+                    }
+                }
+                if ends_line {
+                    if is_line_truncated {
+                        is_line_truncated = false;
+                    } else if !Self::send_line(lines_send, mem::take(&mut line), false) {
+                        return;
+                    }
                 }
             }
-        });
+        }
 
-        let mut stdin = LineWriter::new(writer);
-        Self::push_init_commands(options, &out_lines_recv, &mut shell, &mut stdin)?;
+        if !line.is_empty() && !is_line_truncated {
+            Self::send_line(lines_send, line, false);
+        }
+    }
 
-        let mut transcript = Self::new();
-        for input in inputs {
-            let interaction =
-                Self::record_interaction(options, input, &out_lines_recv, &mut shell, &mut stdin)?;
-            transcript.interactions.push(interaction);
+    /// Truncates the line to at most `len` bytes, not leaving an incomplete UTF-8 char
+    /// or an incomplete escape sequence at its end.
+    fn truncate_line(line: &mut Vec<u8>, len: usize) {
+        line.truncate(len);
+        if let Err(err) = std::str::from_utf8(line) {
+            if err.error_len().is_none() {
+                line.truncate(err.valid_up_to());
+            }
+        }
+        if let Some(escape_start) = Self::unterminated_escape_start(line) {
+            line.truncate(escape_start);
         }
+    }
 
-        drop(stdin); // signals to shell that we're done
+    /// Truncates the output to at most `max_bytes` bytes, not leaving an incomplete UTF-8 char
+    /// or an incomplete escape sequence at its end. If the output contains escape sequences,
+    /// it is terminated with an SGR reset, so that styles don't leak into the following content.
+    fn truncate_output(output: &mut String, max_bytes: usize) {
+        let has_escapes = output.contains('\u{1b}');
+        let mut truncated_len = if has_escapes {
+            max_bytes.saturating_sub(SGR_RESET.len())
+        } else {
+            max_bytes
+        };
+        truncated_len = truncated_len.min(output.len());
+        while !output.is_char_boundary(truncated_len) {
+            truncated_len -= 1;
+        }
+        if let Some(escape_start) =
+            Self::unterminated_escape_start(&output.as_bytes()[..truncated_len])
+        {
+            truncated_len = escape_start;
+        }
+        output.truncate(truncated_len);
 
-        // Give a chance for the shell process to exit. This will reduce kill errors later.
-        thread::sleep(options.io_timeout / 4);
+        if has_escapes {
+            // Keep the trailing newline (if any) last, so that it is handled as usual.
+            let ends_with_newline = output.ends_with('\n');
+            if ends_with_newline {
+                output.pop();
+            }
+            output.push_str(SGR_RESET);
+            if ends_with_newline {
+                output.push('\n');
+            }
+        }
+    }
 
-        shell.terminate()?;
-        io_handle.join().ok(); // the I/O thread should not panic, so we ignore errors here
+    /// Returns the start of the incomplete escape sequence at the end of `bytes`, if any.
+    /// Only CSI and OSC sequences (i.e., ones that can be parsed from the captured output)
+    /// are considered.
+    fn unterminated_escape_start(bytes: &[u8]) -> Option<usize> {
+        const ESC: u8 = 0x1b;
+        const BEL: u8 = 0x07;
+
+        let mut end = bytes.len();
+        let mut escape_start = None;
+        while let Some(pos) = bytes[..end].iter().rposition(|&byte| byte == ESC) {
+            let is_complete = pos + 1 < end
+                && match bytes[pos + 1] {
+                    // CSI sequences end with a byte in the 0x40..=0x7e range.
+                    b'[' => bytes[pos + 2..end]
+                        .iter()
+                        .any(|byte| (0x40..=0x7e).contains(byte)),
+                    b']' => bytes[pos + 2..end].contains(&BEL),
+                    _ => true,
+                };
+            if is_complete {
+                break;
+            }
+            // The incomplete sequence may be a partial `ESC \` terminator of an OSC sequence,
+            // so the preceding sequences need to be checked as well.
+            escape_start = Some(pos);
+            end = pos;
+        }
+        escape_start
+    }
+
+    /// Returns `false` if the receiver has been dropped.
+    fn send_line(lines_send: &mpsc::Sender<OutputLine>, line: Vec<u8>, is_truncated: bool) -> bool {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(line_utf8 = std::str::from_utf8(&line).ok(), "received line");
+
+        let line = OutputLine {
+            bytes: line,
+            is_truncated,
+        };
+        if lines_send.send(line).is_err() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("receiver dropped, breaking reader loop");
+            return false;
+        }
+        true
+    }
+
+    /// Records interactions for the provided `inputs` in a spawned shell. Stops recording
+    /// once the kill timeout (if any) has passed.
+    fn record_inputs<Cmd: SpawnShell>(
+        options: &mut ShellOptions<Cmd>,
+        inputs: impl IntoIterator<Item = UserInput>,
+        lines_recv: &LinesReceiver,
+        shell: &mut Cmd::ShellProcess,
+        stdin: &mut impl io::Write,
+    ) -> io::Result<Self> {
+        let inputs: Vec<_> = inputs.into_iter().collect();
+        // Check this before writing anything to the shell, so that commands consuming stdin data
+        // are not executed with the data left unterminated.
+        if inputs.iter().any(|input| input.stdin_data.is_some()) && !shell.supports_eof() {
+            let message = "stdin data is not supported by the shell since it cannot signal \
+                end of input; consider using a PTY";
+            return Err(io::Error::new(io::ErrorKind::Unsupported, message));
+        }
+
+        let banner = Self::push_init_commands(options, lines_recv, shell, stdin)?;
+        let is_echoing = if options.echo_detection {
+            Self::detect_echoing(options, lines_recv, shell, stdin)?
+        } else {
+            shell.is_echoing()
+        };
+
+        let mut transcript = Self::new();
+        if let Some(banner) = banner {
+            let input = UserInput::command("").hide();
+            transcript
+                .interactions
+                .push(Interaction::new(input, banner));
+        }
+        for (index, input) in inputs.into_iter().enumerate() {
+            if lines_recv
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                break;
+            }
+            let interaction = Self::record_interaction(
+                options, index, input, lines_recv, shell, is_echoing, stdin,
+            )?;
+            transcript.interactions.push(interaction);
+        }
         Ok(transcript)
     }
 
@@ -189,16 +645,27 @@ impl Transcript {
         )
     )]
     fn push_init_commands<Cmd: SpawnShell>(
-        options: &ShellOptions<Cmd>,
-        lines_recv: &mpsc::Receiver<Vec<u8>>,
+        options: &mut ShellOptions<Cmd>,
+        lines_recv: &LinesReceiver,
         shell: &mut Cmd::ShellProcess,
         stdin: &mut impl io::Write,
-    ) -> io::Result<()> {
+    ) -> io::Result<Option<String>> {
         // Drain all output left after commands and let the shell get fully initialized.
-        let mut timeouts = Timeouts::new(options);
-        while lines_recv.recv_timeout(timeouts.next()).is_ok() {
-            // Intentionally empty.
-        }
+        let banner = if options.init_banner {
+            let (banner, ..) = Self::read_output(
+                lines_recv,
+                Timeouts::new(options),
+                options.line_decoder.as_mut(),
+                options.max_output_bytes,
+            )?;
+            Some(banner).filter(|banner| !banner.is_empty())
+        } else {
+            let mut timeouts = Timeouts::new(options);
+            while lines_recv.recv_timeout(timeouts.next()).is_ok() {
+                // Intentionally empty.
+            }
+            None
+        };
 
         // Push initialization commands.
         for cmd in &options.init_commands {
@@ -213,7 +680,52 @@ impl Transcript {
                 // Intentionally empty.
             }
         }
-        Ok(())
+        Ok(banner)
+    }
+
+    /// Sends a probe comment line to the shell and checks whether it is echoed back.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, ret, err)
+    )]
+    fn detect_echoing<Cmd: SpawnShell>(
+        options: &ShellOptions<Cmd>,
+        lines_recv: &LinesReceiver,
+        shell: &Cmd::ShellProcess,
+        stdin: &mut impl io::Write,
+    ) -> io::Result<bool> {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+        let probe = format!("{ECHO_PROBE_PREFIX}{nonce:x}");
+        Self::write_line(stdin, &probe)?;
+
+        let mut received_output = false;
+        let mut is_echoed = false;
+        let mut timeouts = Timeouts::new(options);
+        while let Ok(line) = lines_recv.recv_timeout(timeouts.next()) {
+            received_output = true;
+            is_echoed = is_echoed || Self::is_echoed_probe(&line.bytes, &probe);
+        }
+
+        // If the shell has output something, but not the probe, the detection is inconclusive,
+        // so we fall back to the shell default.
+        Ok(is_echoed || (received_output && shell.is_echoing()))
+    }
+
+    /// Checks whether `line` output by the shell is the echoed `probe`, i.e., it consists
+    /// of the probe optionally preceded by a prompt (e.g., `$ ` or `>>> `). Lines merely
+    /// containing the probe (e.g., in a syntax error quoting the input) are not matched.
+    fn is_echoed_probe(line: &[u8], probe: &str) -> bool {
+        let line = String::from_utf8_lossy(line).into_owned();
+        // Strip escape sequences, e.g. in colored prompts.
+        let line = Captured::from(line.clone()).to_plaintext().unwrap_or(line);
+        let Some(prompt) = line.trim().strip_suffix(probe) else {
+            return false;
+        };
+        prompt.is_empty()
+            || (prompt.ends_with(char::is_whitespace)
+                && prompt.trim_end().ends_with(['$', '#', '>', '%']))
     }
 
     #[cfg_attr(
@@ -222,50 +734,126 @@ impl Transcript {
     )]
     fn record_interaction<Cmd: SpawnShell>(
         options: &mut ShellOptions<Cmd>,
-        input: UserInput,
-        lines_recv: &mpsc::Receiver<Vec<u8>>,
+        index: usize,
+        mut input: UserInput,
+        lines_recv: &LinesReceiver,
         shell: &mut Cmd::ShellProcess,
+        is_echoing: bool,
         stdin: &mut impl io::Write,
     ) -> io::Result<Interaction> {
         // Check if the shell is still alive. It seems that older Rust versions allow
         // to write to `stdin` even after the shell exits.
         shell.check_is_alive()?;
 
-        let input_lines = input.text.split('\n');
-        for input_line in input_lines {
-            Self::write_line(stdin, input_line)?;
-            if shell.is_echoing() {
-                Self::read_echo(input_line, lines_recv, options.io_timeout)?;
+        let cwd = if options.cwd_prompt && !input.interrupt {
+            let cwd = Self::query_cwd(options, lines_recv, is_echoing, stdin)?;
+            Some(cwd).filter(|cwd| !cwd.is_empty())
+        } else {
+            None
+        };
+        let prompt_template = options.prompt_template.as_ref();
+        // Interrupts are always rendered without a prompt.
+        if let Some(template) = prompt_template.filter(|_| !input.interrupt) {
+            let context = ShellContext {
+                index,
+                input: &input,
+                cwd: cwd.as_deref(),
+            };
+            input.prompt = Some(Cow::Owned(template(&context)));
+        } else if let Some(cwd) = &cwd {
+            input.prompt = Some(Cow::Owned(format!("{cwd} $")));
+        }
+
+        let start_instant = Instant::now();
+        for step in InputStep::sequence(&input) {
+            match step {
+                InputStep::Line(input_line) => {
+                    Self::write_input_line(options, stdin, input_line)?;
+                    if is_echoing {
+                        Self::read_echo(input_line, lines_recv, options.io_timeout)?;
+                    }
+                }
+                // Echoed data (if any) is not consumed, so that it becomes a part of the output.
+                InputStep::DataLine(data_line) => Self::write_line(stdin, data_line)?,
+                InputStep::Eof => shell.send_eof(stdin)?,
+                // The echo (if any) is not terminated with a newline, so we don't try to read it.
+                InputStep::Interrupt => shell.interrupt(stdin)?,
             }
         }
 
-        let output = Self::read_output(
+        let (mut output, is_truncated, last_line_instant) = Self::read_output(
             lines_recv,
             Timeouts::new(options),
             options.line_decoder.as_mut(),
+            options.max_output_bytes,
         )?;
+        if options.alt_screen_capture {
+            if let Cow::Owned(retained) = retain_alt_screen(&output) {
+                output = retained;
+            }
+        }
+        if options.form_feed_clears {
+            if let Cow::Owned(cleared) = clear_before_form_feed(&output) {
+                output = cleared;
+            }
+        }
 
         let exit_status = if let Some(status_check) = &options.status_check {
-            let command = status_check.command();
-            Self::write_line(stdin, command)?;
-            if shell.is_echoing() {
-                Self::read_echo(command, lines_recv, options.io_timeout)?;
+            let mut responses = Vec::with_capacity(status_check.commands().len());
+            for command in status_check.commands() {
+                Self::write_line(stdin, command)?;
+                if is_echoing {
+                    Self::read_echo(command, lines_recv, options.io_timeout)?;
+                }
+                let (response, ..) = Self::read_output(
+                    lines_recv,
+                    Timeouts::new(options),
+                    options.line_decoder.as_mut(),
+                    None,
+                )?;
+                responses.push(response);
             }
-            let response = Self::read_output(
-                lines_recv,
-                Timeouts::new(options),
-                options.line_decoder.as_mut(),
-            )?;
-            status_check.check(&Captured::from(response))
+            status_check.check(&Captured::from(responses.join("\n")))
         } else {
             None
         };
 
         let mut interaction = Interaction::new(input, output);
         interaction.exit_status = exit_status;
+        interaction.duration =
+            last_line_instant.map(|instant| instant.saturating_duration_since(start_instant));
+        interaction.is_truncated = is_truncated;
         Ok(interaction)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, ret, err)
+    )]
+    fn query_cwd<Cmd: SpawnShell>(
+        options: &mut ShellOptions<Cmd>,
+        lines_recv: &LinesReceiver,
+        is_echoing: bool,
+        stdin: &mut impl io::Write,
+    ) -> io::Result<String> {
+        Self::write_line(stdin, CWD_COMMAND)?;
+        if is_echoing {
+            Self::read_echo(CWD_COMMAND, lines_recv, options.io_timeout)?;
+        }
+        let (response, ..) = Self::read_output(
+            lines_recv,
+            Timeouts::new(options),
+            options.line_decoder.as_mut(),
+            None,
+        )?;
+        let response = Captured::from(response)
+            .to_plaintext()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        // PowerShell outputs a table with the path in the last line.
+        let cwd = response.trim().lines().last().unwrap_or_default();
+        Ok(cwd.trim().to_owned())
+    }
+
     /// Captures stdout / stderr of the provided `command` and adds it to [`Self::interactions()`].
     ///
     /// The `command` is spawned with the closed stdin. This method blocks until the command exits.
@@ -2,7 +2,11 @@
 
 use serde::Serialize;
 
-use crate::{svg::TemplateOptions, write::SvgLine, UserInput};
+use crate::{
+    svg::{Palette, RgbColor, TemplateOptions},
+    write::SvgLine,
+    UserInput,
+};
 
 /// Root data structure sent to the Handlebars template.
 ///
@@ -53,10 +57,24 @@ use crate::{svg::TemplateOptions, write::SvgLine, UserInput};
 ///     },
 ///     "font_family": "Consolas, Menlo, monospace",
 ///     "window_frame": false,
+///     "corner_radius": null,
+///     "window_shadow": false,
+///     "responsive": false,
+///     "scale": null,
 ///     "wrap": {
 ///         "hard_break_at": 80,
 ///     },
+///     "hard_break_marker": "↓",
 ///     "line_numbers": null,
+///     "interaction_separator": null,
+///     "caption": null,
+///     "accessible": false,
+///     "show_whitespace": false,
+///     "grid_layout": false,
+///     "show_timings": false,
+///     "collapse_repeats": false,
+///     "include_legend": false,
+///     "minify": false,
 ///     "has_failures": false,
 ///     "interactions": [{
 ///         "input": {
@@ -65,14 +83,23 @@ use crate::{svg::TemplateOptions, write::SvgLine, UserInput};
 ///             "hidden": false,
 ///         },
 ///         "output_html": "Hello, <span class=\"fg2\">world</span>!",
+///         "unfolded_html": null,
 /// #       "output_svg": [{
 /// #           "background": null,
 /// #           "foreground": "Hello,\u{a0}<tspan class=\"fg2\">world</tspan>!",
+/// #           "whitespace": null,
 /// #       }],
 /// #       // ^ Implementation detail for now
 ///         "failure": false,
 ///         "exit_status": null,
-///     }]
+///         "duration_ms": null,
+///         "highlighted_lines": [],
+///         "typing": null,
+///         "palette": null,
+///         "summary": null,
+///         "repeat_count": 1,
+///     }],
+///     "legend": [],
 /// });
 /// assert_eq!(serde_json::to_value(data).unwrap(), expected_json);
 /// ```
@@ -89,6 +116,65 @@ pub struct HandlebarsData<'r> {
     pub interactions: Vec<SerializedInteraction<'r>>,
     /// Has any of terminal interactions failed?
     pub has_failures: bool,
+    /// Legend of palette colors used in the interaction outputs, ordered by the color index.
+    /// Empty unless [`TemplateOptions::include_legend`] is set.
+    pub legend: Vec<LegendEntry>,
+    /// If set, the template should output only the height of the terminal screen in pixels
+    /// instead of the image. See [`Template::render_each()`] for details.
+    ///
+    /// [`Template::render_each()`]: super::Template::render_each()
+    #[serde(skip_serializing_if = "core::ops::Not::not")]
+    pub(crate) measure_screen_height: bool,
+}
+
+/// Entry in the [color legend](HandlebarsData::legend).
+#[derive(Debug, Serialize)]
+#[non_exhaustive]
+pub struct LegendEntry {
+    /// Index of the color in the palette. Indices 0–7 correspond to ordinary colors,
+    /// and 8–15 to their intense variations.
+    pub index: usize,
+    /// Human-readable color name, e.g. `green` or `intense green`.
+    pub name: &'static str,
+    /// Color value from the palette.
+    pub color: RgbColor,
+}
+
+impl LegendEntry {
+    const NAMES: [&'static str; 16] = [
+        "black",
+        "red",
+        "green",
+        "yellow",
+        "blue",
+        "magenta",
+        "cyan",
+        "white",
+        "intense black",
+        "intense red",
+        "intense green",
+        "intense yellow",
+        "intense blue",
+        "intense magenta",
+        "intense cyan",
+        "intense white",
+    ];
+
+    /// Creates legend entries for the palette colors flagged in `is_used`, which is collected
+    /// when writing outputs.
+    pub(super) fn for_used_colors(
+        is_used: [bool; 16],
+        palette_colors: &[RgbColor; 16],
+    ) -> Vec<Self> {
+        (0..16)
+            .filter(|&index| is_used[index])
+            .map(|index| Self {
+                index,
+                name: Self::NAMES[index],
+                color: palette_colors[index],
+            })
+            .collect()
+    }
 }
 
 /// Information about software used for rendering (i.e., this crate).
@@ -147,10 +233,46 @@ pub struct SerializedInteraction<'a> {
     pub input: &'a UserInput,
     /// Terminal output in the [HTML format](#html-output).
     pub output_html: String,
+    /// Full terminal output in the HTML format if some of its lines are
+    /// [folded](crate::Interaction::fold_lines()) in [`Self::output_html`].
+    pub unfolded_html: Option<String>,
     /// Terminal output in the SVG format.
     pub(crate) output_svg: Vec<SvgLine>,
     /// Exit status of the latest executed program, or `None` if it cannot be determined.
     pub exit_status: Option<i32>,
+    /// Wall-clock [duration](crate::Interaction::duration()) of executing the input
+    /// in milliseconds, or `None` if it was not recorded.
+    pub duration_ms: Option<u64>,
     /// Was execution unsuccessful judging by the [`ExitStatus`](crate::ExitStatus)?
     pub failure: bool,
+    /// Zero-based indices of [highlighted](crate::Interaction::with_highlighted_lines())
+    /// output lines. Only indices of the displayed lines are retained.
+    pub highlighted_lines: Vec<usize>,
+    /// Timeline of the typing animation for this interaction, or `None`
+    /// if the [animation](crate::svg::TypeAnimation) is disabled.
+    pub typing: Option<TypingTimeline>,
+    /// [Palette override](crate::Interaction::with_palette()) for this interaction, if any.
+    pub palette: Option<Palette>,
+    /// Short plaintext summary of this interaction for screen readers, or `None`
+    /// if [`TemplateOptions::accessible`] is not set.
+    pub summary: Option<String>,
+    /// Number of consecutive identical interactions [collapsed](TemplateOptions::collapse_repeats)
+    /// into this one. This is 1 if the interaction is not repeated.
+    pub repeat_count: usize,
+}
+
+/// Timeline of the [typing animation](crate::svg::TypeAnimation) for a single interaction.
+/// All times are measured in seconds since the start of the animation.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[non_exhaustive]
+pub struct TypingTimeline {
+    /// Moment when the input is displayed and starts being typed.
+    pub input_start: f64,
+    /// Duration of typing the input.
+    pub input_duration: f64,
+    /// Number of typed columns, i.e., the maximum display width of the input lines in chars.
+    /// This is 0 for [hidden](crate::UserInput::hide()) inputs.
+    pub columns: usize,
+    /// Moment when the output is displayed.
+    pub output_start: f64,
 }
@@ -0,0 +1,134 @@
+//! Whitespace-aware minification of rendered SVG markup.
+
+/// Tags whose contents are whitespace-sensitive.
+const PRESERVING_TAGS: &[&str] = &["pre", "text", "style", "textarea"];
+
+/// Kind of a markup token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    Start {
+        preserves_whitespace: bool,
+    },
+    End,
+    /// Self-closing tags, comments, processing instructions, CDATA etc.
+    Other,
+}
+
+/// Minifies the provided markup by removing whitespace-only text nodes outside of
+/// whitespace-sensitive elements (`<pre>`, `<text>`, elements with `xml:space="preserve"`, etc.).
+/// Text content and tags are preserved as-is.
+pub(super) fn minify(markup: &str) -> String {
+    let mut output = String::with_capacity(markup.len());
+    // Stack of open elements; each element records whether it preserves whitespace.
+    let mut preserve_stack: Vec<bool> = vec![];
+    let mut remaining = markup;
+
+    while !remaining.is_empty() {
+        let text_len = remaining.find('<').unwrap_or(remaining.len());
+        let (text, rest) = remaining.split_at(text_len);
+        let preserves_whitespace = preserve_stack.last().copied().unwrap_or(false);
+        if preserves_whitespace || !text.trim().is_empty() {
+            output.push_str(text);
+        }
+        if rest.is_empty() {
+            break;
+        }
+
+        let tag_len = tag_len(rest);
+        let (tag, rest) = rest.split_at(tag_len);
+        output.push_str(tag);
+        match classify_tag(tag) {
+            TagKind::Start {
+                preserves_whitespace: preserves,
+            } => {
+                preserve_stack.push(preserves_whitespace || preserves);
+            }
+            TagKind::End => {
+                preserve_stack.pop();
+            }
+            TagKind::Other => { /* do nothing */ }
+        }
+        remaining = rest;
+    }
+    output
+}
+
+/// Returns the length of the tag at the start of `markup` (which must start with `<`),
+/// taking quoted attribute values into account.
+fn tag_len(markup: &str) -> usize {
+    for (opening, closing) in [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>")] {
+        if markup.starts_with(opening) {
+            return markup
+                .find(closing)
+                .map_or(markup.len(), |pos| pos + closing.len());
+        }
+    }
+
+    let mut quote = None;
+    for (i, ch) in markup.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), _) if q == ch => quote = None,
+            (None, '>') => return i + 1,
+            _ => { /* continue */ }
+        }
+    }
+    markup.len()
+}
+
+fn classify_tag(tag: &str) -> TagKind {
+    if tag.starts_with("</") {
+        TagKind::End
+    } else if tag.starts_with("<!") || tag.starts_with("<?") || tag.ends_with("/>") {
+        TagKind::Other
+    } else {
+        let name_len = tag[1..]
+            .find(|ch: char| ch.is_ascii_whitespace() || ch == '>')
+            .unwrap_or(tag.len() - 1);
+        let name = &tag[1..=name_len];
+        let preserves_whitespace = PRESERVING_TAGS.contains(&name)
+            || tag.contains(r#"xml:space="preserve""#)
+            || tag.contains("xml:space='preserve'");
+        TagKind::Start {
+            preserves_whitespace,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minifying_structural_whitespace() {
+        let markup = "<svg>\n  <g class=\"a\">\n    <rect x=\"0\" />\n  </g>\n</svg>\n";
+        assert_eq!(
+            minify(markup),
+            "<svg><g class=\"a\"><rect x=\"0\" /></g></svg>"
+        );
+    }
+
+    #[test]
+    fn minifying_preserves_text_content() {
+        let markup = "<div>\n  <pre>  Hello,\n  <span> world</span> !\n</pre>\n</div>";
+        assert_eq!(
+            minify(markup),
+            "<div><pre>  Hello,\n  <span> world</span> !\n</pre></div>"
+        );
+
+        let markup = "<g>\n <text> <tspan xml:space=\"preserve\"> 1 </tspan>\n</text>\n</g>";
+        assert_eq!(
+            minify(markup),
+            "<g><text> <tspan xml:space=\"preserve\"> 1 </tspan>\n</text></g>"
+        );
+    }
+
+    #[test]
+    fn minifying_with_comments_and_quoted_brackets() {
+        let markup = "<!-- <g> -->\n<svg>\n  <g title=\"a > b\">\n  </g>\n</svg>";
+        assert_eq!(
+            minify(markup),
+            "<!-- <g> --><svg><g title=\"a > b\"></g></svg>"
+        );
+    }
+}
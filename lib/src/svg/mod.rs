@@ -11,24 +11,30 @@
 //!
 //! See [`Template`] for examples of usage.
 
-use std::{fmt, io::Write};
+use std::{borrow::Cow, fmt, io::Write, num::NonZeroUsize};
 
 use handlebars::{Handlebars, RenderError, RenderErrorReason, Template as HandlebarsTemplate};
 use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
 
 mod data;
 mod helpers;
+mod minify;
 mod palette;
 #[cfg(test)]
 mod tests;
 
-use self::helpers::register_helpers;
 pub use self::{
-    data::{CreatorData, HandlebarsData, SerializedInteraction},
+    data::{CreatorData, HandlebarsData, LegendEntry, SerializedInteraction, TypingTimeline},
     palette::{NamedPalette, NamedPaletteParseError, Palette, TermColors},
 };
+use self::{helpers::register_helpers, minify::minify};
 pub use crate::utils::{RgbColor, RgbColorParseError};
-use crate::{write::SvgLine, TermError, Transcript};
+use crate::{
+    term::{fold_lines, OutputWriteOptions},
+    write::{self, SvgLine, SvgWriter},
+    Captured, Interaction, TermError, Transcript, UserInput,
+};
 
 const DEFAULT_TEMPLATE: &str = include_str!("default.svg.handlebars");
 const PURE_TEMPLATE: &str = include_str!("pure.svg.handlebars");
@@ -96,54 +102,188 @@ pub enum LineNumbers {
 /// # anyhow::Ok(())
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // options are (de)serialized as a flat set of flags
 pub struct TemplateOptions {
     /// Width of the rendered terminal window in pixels. The default value is `720`.
     #[serde(default = "TemplateOptions::default_width")]
     pub width: usize,
+    /// Width of the rendered terminal window in chars. If set, this overrides [`Self::width`]
+    /// in the default templates: the pixel width is computed by the template from its advance width
+    /// of chars (8.4px, i.e. a 14px monospace font), window padding and the line numbers column,
+    /// if any. Must not be less than the hard break width
+    /// specified in [`Self::wrap`]. Default value is `None`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub width_chars: Option<NonZeroUsize>,
     /// Palette of terminal colors. The default value of [`Palette`] is used by default.
     #[serde(default)]
     pub palette: Palette,
+    /// Colors of the 8-bit grayscale ramp (indices 232..=255 in the 256-color palette)
+    /// overriding the standard ones. This may be useful to reproduce themes tweaking grays.
+    /// If set to `None` (which is the default), grays are expanded to the standard values.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub grayscale_ramp: Option<[RgbColor; 24]>,
     /// CSS instructions to add at the beginning of the SVG `<style>` tag. This is mostly useful
     /// to import fonts in conjunction with `font_family`.
     ///
-    /// The value is not validated in any way, so supplying invalid CSS instructions can lead
-    /// to broken SVG rendering.
+    /// The value is only checked for balanced curly braces, so supplying invalid CSS instructions
+    /// can lead to broken SVG rendering.
     #[serde(skip_serializing_if = "str::is_empty", default)]
     pub additional_styles: String,
-    /// Font family specification in the CSS format. Should be monospace.
+    /// Font family specification in the CSS format. Should be monospace. Must not be empty.
     #[serde(default = "TemplateOptions::default_font_family")]
     pub font_family: String,
     /// Indicates whether to display a window frame around the shell. Default value is `false`.
     #[serde(default)]
     pub window_frame: bool,
+    /// Corner radius of the terminal window in pixels. If set to `None` (which is the default),
+    /// the template-specific default radius is used.
+    #[serde(default)]
+    pub corner_radius: Option<usize>,
+    /// Indicates whether to render a drop shadow around the terminal window. The rendered image
+    /// is expanded to fit the shadow. Default value is `false`.
+    #[serde(default)]
+    pub window_shadow: bool,
+    /// Indicates whether to omit absolute `width` and `height` on the root `<svg>` element,
+    /// so that the image relies solely on its `viewBox` and scales to its container.
+    /// The scroll animation timing is unaffected. Default value is `false`.
+    #[serde(default)]
+    pub responsive: bool,
+    /// Scale factor (aka device pixel ratio) applied to the absolute `width` and `height`
+    /// of the root `<svg>` element. The `viewBox` remains in logical units, so the image
+    /// is rendered crisply when rasterized at a higher resolution (e.g., when converting
+    /// the image to PNG). Scaled dimensions are rounded to whole pixels. Has no effect
+    /// if [`Self::responsive`] is set. Must be positive and not exceed 10.
+    /// If set to `None` (which is the default), the image is not scaled.
+    #[serde(default)]
+    pub scale: Option<f64>,
     /// Options for the scroll animation. If set to `None` (which is the default),
     /// no scrolling will be enabled, and the height of the generated image is not limited.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub scroll: Option<ScrollOptions>,
+    /// Minimum height of the console in pixels. Shorter transcripts are padded with empty space
+    /// at the bottom, which is useful to render visually consistent sets of images. Must not
+    /// exceed the [maximum scroll height](ScrollOptions::max_height) if the scroll animation
+    /// is enabled. If set to `None` (which is the default), the height is not padded.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub min_height: Option<NonZeroUsize>,
+    /// Maximum number of lines displayed for each interaction output. Longer outputs
+    /// are truncated to this number of lines, followed by a dimmed marker line specifying
+    /// the number of hidden lines. Lines are counted before [wrapping](Self::wrap).
+    /// Similar to [folded lines](crate::Interaction::fold_lines()), the default template
+    /// embeds the full output into the rendered image, so truncated snapshots can be used
+    /// for [testing](crate::test). This is a simpler static alternative
+    /// to the [scroll animation](Self::scroll); the two options are mutually exclusive.
+    /// If set to `None` (which is the default), outputs are not truncated.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_lines: Option<NonZeroUsize>,
+    /// Options for the typing animation of user inputs. If set to `None` (which is the default),
+    /// all interactions are displayed at once.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub type_animation: Option<TypeAnimation>,
     /// Text wrapping options. The default value of [`WrapOptions`] is used by default.
     #[serde(default = "TemplateOptions::default_wrap")]
     pub wrap: Option<WrapOptions>,
+    /// Char displayed at the end of hard-wrapped lines. The default value is `↓`
+    /// (rendered rotated, so that it points to the start of the next line). Other markers
+    /// are not rotated. The marker cannot be a control char, `'` or `\`.
+    #[serde(default = "TemplateOptions::default_hard_break_marker")]
+    pub hard_break_marker: char,
     /// Line numbering options.
     #[serde(default)]
     pub line_numbers: Option<LineNumbers>,
+    /// Separator rendered between consecutive interactions. If set to `None` (which is
+    /// the default), interactions are only separated by the standard vertical margin.
+    #[serde(default)]
+    pub interaction_separator: Option<SeparatorStyle>,
+    /// Caption rendered below the terminal window. The rendered image is expanded to fit
+    /// the caption. If set to `None` (which is the default), no caption is rendered.
+    #[serde(default)]
+    pub caption: Option<String>,
+    /// Indicates whether to emit accessibility elements for screen readers: `<title>` for
+    /// the image (the [caption](Self::caption), or a generic title if the caption is not set),
+    /// and `<desc>` with a short plaintext summary of each interaction
+    /// (e.g., `Input: ls; Output: Cargo.toml src`). Default value is `false`.
+    #[serde(default)]
+    pub accessible: bool,
+    /// Indicates whether to visualize whitespace in outputs, rendering spaces as middle dots
+    /// and tabs as arrows. The glyphs are not selectable (they are added via CSS in the default
+    /// template, and rendered as a separate text layer in the pure SVG template), so the copied
+    /// text retains the original whitespace. Default value is `false`.
+    #[serde(default)]
+    pub show_whitespace: bool,
+    /// Indicates whether to align box-drawing and block chars (e.g., `─│┌█`) in outputs
+    /// to an exact monospace grid, so that box-drawn tables and other TUI art line up
+    /// even if the font lacks these glyphs or renders them with a different advance width.
+    /// Such chars are wrapped into fixed-size inline blocks; this does not affect the copied text.
+    /// Has no effect on the pure SVG template, in which lines are positioned explicitly.
+    /// Default value is `false`.
+    #[serde(default)]
+    pub grid_layout: bool,
+    /// Indicates whether to display the [duration](crate::Interaction::duration()) of executing
+    /// each input next to it. Durations are only recorded in the rendered SVG if this option is set,
+    /// since they generally differ between captures. Default value is `false`.
+    #[serde(default)]
+    pub show_timings: bool,
+    /// Indicates whether to collapse consecutive identical interactions (e.g., produced
+    /// by polling a command) into a single interaction marked with a `×N` badge. Interactions
+    /// are considered identical if they have the same input, output, stderr and exit status;
+    /// durations are not compared. Since collapsed interactions are not recorded in the rendered
+    /// image, such images are not suitable for [snapshot testing](crate::test).
+    /// Default value is `false`.
+    #[serde(default)]
+    pub collapse_repeats: bool,
+    /// Indicates whether to render a legend beneath the terminal window, listing palette colors
+    /// used in the outputs together with their names. The rendered image is expanded to fit
+    /// the legend. Default value is `false`.
+    #[serde(default)]
+    pub include_legend: bool,
+    /// Indicates whether to minify the rendered output by removing insignificant whitespace
+    /// between tags. Text content (e.g., inside `<pre>` or `<text>` elements) is preserved as-is.
+    /// Default value is `false`.
+    #[serde(default)]
+    pub minify: bool,
 }
 
 impl Default for TemplateOptions {
     fn default() -> Self {
         Self {
             width: Self::default_width(),
+            width_chars: None,
             palette: Palette::default(),
+            grayscale_ramp: None,
             additional_styles: String::new(),
             font_family: Self::default_font_family(),
             window_frame: false,
+            corner_radius: None,
+            window_shadow: false,
+            responsive: false,
+            scale: None,
             scroll: None,
+            min_height: None,
+            max_lines: None,
+            type_animation: None,
             wrap: Self::default_wrap(),
+            hard_break_marker: Self::default_hard_break_marker(),
             line_numbers: None,
+            interaction_separator: None,
+            caption: None,
+            accessible: false,
+            show_whitespace: false,
+            grid_layout: false,
+            show_timings: false,
+            collapse_repeats: false,
+            include_legend: false,
+            minify: false,
         }
     }
 }
 
 impl TemplateOptions {
+    /// Maximum supported value for [`Self::scale`].
+    const MAX_SCALE: f64 = 10.0;
+    /// Maximum number of chars in the input / output parts of an accessible summary.
+    const MAX_SUMMARY_CHARS: usize = 80;
+
     fn default_width() -> usize {
         720
     }
@@ -157,6 +297,82 @@ impl TemplateOptions {
         Some(WrapOptions::default())
     }
 
+    fn default_hard_break_marker() -> char {
+        SvgWriter::DEFAULT_HARD_BREAK_MARKER
+    }
+
+    fn validate(&self) -> Result<(), RenderError> {
+        if let Some(scroll) = &self.scroll {
+            scroll.validate()?;
+            if self.max_lines.is_some() {
+                let message = "scroll animation and max lines cannot be enabled simultaneously";
+                return Err(RenderErrorReason::Other(message.to_owned()).into());
+            }
+            if let Some(min_height) = self.min_height {
+                if min_height.get() > scroll.max_height {
+                    let message = format!(
+                        "minimum height {min_height} exceeds the maximum scroll height {}",
+                        scroll.max_height
+                    );
+                    return Err(RenderErrorReason::Other(message).into());
+                }
+            }
+        }
+        if let Some(type_animation) = &self.type_animation {
+            type_animation.validate()?;
+        }
+        if self.font_family.trim().is_empty() {
+            let message = "font family is empty".to_owned();
+            return Err(RenderErrorReason::Other(message).into());
+        }
+        if !Self::has_balanced_braces(&self.additional_styles) {
+            let message = "additional styles contain unbalanced curly braces".to_owned();
+            return Err(RenderErrorReason::Other(message).into());
+        }
+        if let Some(scale) = self.scale {
+            if !scale.is_finite() || scale <= 0.0 || scale > Self::MAX_SCALE {
+                let message = format!(
+                    "scale {scale} is outside the supported range (0, {}]",
+                    Self::MAX_SCALE
+                );
+                return Err(RenderErrorReason::Other(message).into());
+            }
+        }
+        let marker = self.hard_break_marker;
+        if marker.is_control() || marker == '\'' || marker == '\\' {
+            let message = format!("unsupported hard break marker: {marker:?}");
+            return Err(RenderErrorReason::Other(message).into());
+        }
+        if let (Some(width_chars), Some(WrapOptions::HardBreakAt(wrap_width))) =
+            (self.width_chars, &self.wrap)
+        {
+            if width_chars.get() < *wrap_width {
+                let message = format!(
+                    "window width {width_chars} chars is less than the hard break width {wrap_width} chars"
+                );
+                return Err(RenderErrorReason::Other(message).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn has_balanced_braces(css: &str) -> bool {
+        let mut depth = 0_usize;
+        for ch in css.chars() {
+            match ch {
+                '{' => depth += 1,
+                '}' => {
+                    let Some(new_depth) = depth.checked_sub(1) else {
+                        return false;
+                    };
+                    depth = new_depth;
+                }
+                _ => { /* Do nothing. */ }
+            }
+        }
+        depth == 0
+    }
+
     /// Generates data for rendering.
     ///
     /// # Errors
@@ -173,60 +389,209 @@ impl TemplateOptions {
     ) -> Result<HandlebarsData<'s>, TermError> {
         let rendered_outputs = self.render_outputs(transcript)?;
         let mut has_failures = false;
+        let mut used_colors = [false; 16];
+        let mut elapsed_secs = 0.0;
 
         let interactions: Vec<_> = transcript
             .interactions()
             .iter()
+            .zip(self.repeat_counts(transcript))
             .zip(rendered_outputs)
-            .map(|(interaction, (output_html, output_svg))| {
+            .filter(|((_, repeat_count), _)| *repeat_count > 0)
+            .map(|((interaction, repeat_count), rendered_output)| {
+                let RenderedOutput {
+                    html: output_html,
+                    unfolded_html,
+                    svg_lines: output_svg,
+                    used_colors: output_colors,
+                } = rendered_output;
+                for (is_used, is_used_in_output) in used_colors.iter_mut().zip(output_colors) {
+                    *is_used |= is_used_in_output;
+                }
                 let failure = interaction
                     .exit_status()
                     .map_or(false, |status| !status.is_success());
                 has_failures = has_failures || failure;
-                SerializedInteraction {
+                let highlighted_lines = interaction
+                    .highlighted_lines()
+                    .iter()
+                    .copied()
+                    .filter(|&idx| idx < output_svg.len())
+                    .collect();
+                let summary = if self.accessible {
+                    Some(Self::summarize(interaction)?)
+                } else {
+                    None
+                };
+                let typing = self
+                    .type_animation
+                    .as_ref()
+                    .map(|animation| animation.timeline(interaction.input(), &mut elapsed_secs));
+                Ok(SerializedInteraction {
                     input: interaction.input(),
                     output_html,
+                    unfolded_html,
                     output_svg,
                     exit_status: interaction.exit_status().map(|status| status.0),
+                    duration_ms: interaction
+                        .duration()
+                        .map(|duration| u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)),
                     failure,
-                }
+                    highlighted_lines,
+                    typing,
+                    palette: interaction.palette().copied(),
+                    summary,
+                    repeat_count,
+                })
             })
-            .collect();
+            .collect::<Result<_, TermError>>()?;
+
+        let legend = if self.include_legend {
+            LegendEntry::for_used_colors(used_colors, &self.palette.to_indexed_colors())
+        } else {
+            vec![]
+        };
 
         Ok(HandlebarsData {
             creator: CreatorData::default(),
             interactions,
             options: self,
             has_failures,
+            legend,
+            measure_screen_height: false,
+        })
+    }
+
+    /// Returns the number of times each interaction should be displayed: the length of the run
+    /// of identical interactions for the first interaction in the run, and 0 for the others.
+    fn repeat_counts(&self, transcript: &Transcript) -> Vec<usize> {
+        let interactions = transcript.interactions();
+        let mut counts = vec![1; interactions.len()];
+        if !self.collapse_repeats {
+            return counts;
+        }
+
+        let mut run_start = 0;
+        for i in 1..interactions.len() {
+            if Self::is_repeat(&interactions[run_start], &interactions[i]) {
+                counts[run_start] += 1;
+                counts[i] = 0;
+            } else {
+                run_start = i;
+            }
+        }
+        counts
+    }
+
+    fn is_repeat(prev: &Interaction, next: &Interaction) -> bool {
+        prev == next
+            && prev.highlighted_lines == next.highlighted_lines
+            && prev.folded_lines == next.folded_lines
+            && prev.palette == next.palette
+    }
+
+    /// Produces a short plaintext summary of an `interaction` for accessibility purposes.
+    fn summarize(interaction: &Interaction) -> Result<String, TermError> {
+        let input = interaction.input();
+        let output = Self::summarize_text(&interaction.output().to_plaintext()?);
+        Ok(if input.hidden {
+            format!("Output: {output}")
+        } else {
+            let input = Self::summarize_text(&input.text);
+            format!("Input: {input}; Output: {output}")
         })
     }
 
+    /// Collapses whitespace in `text` and truncates it to [`Self::MAX_SUMMARY_CHARS`].
+    fn summarize_text(text: &str) -> String {
+        let mut summary = String::new();
+        for (i, word) in text.split_whitespace().enumerate() {
+            if i > 0 {
+                summary.push(' ');
+            }
+            summary.push_str(word);
+        }
+        if summary.is_empty() {
+            return "(empty)".to_owned();
+        }
+        if summary.chars().count() > Self::MAX_SUMMARY_CHARS {
+            summary = summary.chars().take(Self::MAX_SUMMARY_CHARS - 1).collect();
+            summary.push('…');
+        }
+        summary
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "debug", skip_all, err)
     )]
-    fn render_outputs(
-        &self,
-        transcript: &Transcript,
-    ) -> Result<Vec<(String, Vec<SvgLine>)>, TermError> {
-        let max_width = self.wrap.as_ref().map(|wrap_options| match wrap_options {
-            WrapOptions::HardBreakAt(width) => *width,
-        });
+    fn render_outputs(&self, transcript: &Transcript) -> Result<Vec<RenderedOutput>, TermError> {
+        let write_options = OutputWriteOptions {
+            wrap_width: self.wrap.as_ref().map(|wrap_options| match wrap_options {
+                WrapOptions::HardBreakAt(width) => *width,
+            }),
+            show_whitespace: self.show_whitespace,
+            grid_layout: self.grid_layout,
+            hard_break_marker: self.hard_break_marker,
+            grayscale_ramp: self.grayscale_ramp,
+        };
 
         transcript
             .interactions
             .iter()
             .map(|interaction| {
-                let output = interaction.output();
+                let merged_output;
+                let output = if let Some(stderr) = interaction.stderr() {
+                    merged_output = interaction.output().append_lines(stderr);
+                    &merged_output
+                } else {
+                    interaction.output()
+                };
+
+                let mut folded_ranges = Cow::Borrowed(interaction.folded_lines());
+                if let Some(max_lines) = self.max_lines {
+                    let line_count = output.as_ref().split_inclusive('\n').count();
+                    if line_count > max_lines.get() {
+                        folded_ranges.to_mut().push(max_lines.get()..line_count);
+                    }
+                }
+
+                let folded_output;
+                let (output, unfolded_html) = if folded_ranges.is_empty() {
+                    (output, None)
+                } else {
+                    let mut buffer = String::with_capacity(output.as_ref().len());
+                    output.write_as_html(&mut buffer, &write_options)?;
+                    let folded = fold_lines(output.as_ref(), &folded_ranges);
+                    folded_output = Captured::from(folded);
+                    (&folded_output, Some(buffer))
+                };
+
                 let mut buffer = String::with_capacity(output.as_ref().len());
-                output.write_as_html(&mut buffer, max_width)?;
-                let svg_lines = output.write_as_svg(max_width)?;
-                Ok((buffer, svg_lines))
+                output.write_as_html(&mut buffer, &write_options)?;
+                let (svg_lines, used_colors) = output.write_as_svg(&write_options)?;
+                Ok(RenderedOutput {
+                    html: buffer,
+                    unfolded_html,
+                    svg_lines,
+                    used_colors,
+                })
             })
             .collect()
     }
 }
 
+/// Output of a single interaction rendered by [`TemplateOptions::render_outputs()`].
+#[derive(Debug)]
+struct RenderedOutput {
+    html: String,
+    /// HTML for the output without folding applied; only set if some lines are folded.
+    unfolded_html: Option<String>,
+    svg_lines: Vec<SvgLine>,
+    /// Flags for the base palette colors used in the output.
+    used_colors: [bool; 16],
+}
+
 /// Options that influence the scrolling animation.
 ///
 /// The animation is only displayed if the console exceeds [`Self::max_height`]. In this case,
@@ -242,6 +607,21 @@ pub struct ScrollOptions {
     pub pixels_per_scroll: usize,
     /// Interval between keyframes in seconds. The default value is `4`.
     pub interval: f32,
+    /// Total duration of the scroll animation in seconds. If set, this overrides [`Self::interval`]:
+    /// the duration is evenly divided across keyframes, so that the animation completes
+    /// in the same time regardless of the content height. The duration must be positive.
+    /// Default value is `None`.
+    #[serde(default)]
+    pub total_duration: Option<f32>,
+    /// Color of the scrollbar. If not set (which is the default), the scrollbar is rendered
+    /// as semi-transparent white.
+    #[serde(default)]
+    pub scrollbar_color: Option<RgbColor>,
+    /// Width of the scrollbar in pixels. If not set (which is the default), the width is 5 pixels.
+    /// The width must not exceed [`Self::MAX_SCROLLBAR_WIDTH`] so that the scrollbar fits
+    /// into the right window padding.
+    #[serde(default)]
+    pub scrollbar_width: Option<NonZeroUsize>,
 }
 
 impl Default for ScrollOptions {
@@ -251,6 +631,113 @@ impl Default for ScrollOptions {
             max_height: DEFAULT_LINE_HEIGHT * 19,
             pixels_per_scroll: DEFAULT_LINE_HEIGHT * 4,
             interval: 4.0,
+            total_duration: None,
+            scrollbar_color: None,
+            scrollbar_width: None,
+        }
+    }
+}
+
+impl ScrollOptions {
+    /// Maximum supported [scrollbar width](Self::scrollbar_width) in pixels. Corresponds
+    /// to the right window padding in the default templates minus the right scrollbar margin.
+    pub const MAX_SCROLLBAR_WIDTH: usize = 8;
+
+    fn validate(&self) -> Result<(), RenderError> {
+        if let Some(width) = self.scrollbar_width {
+            if width.get() > Self::MAX_SCROLLBAR_WIDTH {
+                let message = format!(
+                    "scrollbar width {width} exceeds the maximum supported value {}",
+                    Self::MAX_SCROLLBAR_WIDTH
+                );
+                return Err(RenderErrorReason::Other(message).into());
+            }
+        }
+        if let Some(duration) = self.total_duration {
+            if !duration.is_finite() || duration <= 0.0 {
+                let message = format!("scroll animation duration {duration} is not positive");
+                return Err(RenderErrorReason::Other(message).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options for the typing animation of user inputs.
+///
+/// If enabled, interactions are revealed one by one: each user input is typed char by char
+/// with the interval of [`Self::char_interval`] seconds between chars, and its output is shown
+/// after a [pause](Self::pause). The next input starts being typed after another pause.
+/// The animation is played once; after it ends, the transcript looks the same as a static one.
+///
+/// # Tradeoffs
+///
+/// The animation goes against the static-first design of the crate: it adds visual noise,
+/// and the transcript is not fully visible until the animation ends. Thus, it's mostly useful
+/// for demos rather than documentation.
+///
+/// In the default template, the animation is implemented with CSS; the text remains
+/// in the document and can be selected and copied at any time (including before it's revealed).
+/// Multi-line inputs are revealed column by column rather than char by char.
+/// The pure SVG template doesn't support per-char typing; it reveals each input and output
+/// as a whole at the corresponding moment instead.
+///
+/// The animation is not coordinated with the [scroll animation](ScrollOptions), so it's
+/// not recommended to use both at the same time.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TypeAnimation {
+    /// Interval between typing consecutive chars in seconds. The default value is `0.05`.
+    pub char_interval: f32,
+    /// Pause after typing an input before showing its output, and after showing an output
+    /// before starting to type the next input, in seconds. The default value is `0.5`.
+    pub pause: f32,
+}
+
+impl Default for TypeAnimation {
+    fn default() -> Self {
+        Self {
+            char_interval: 0.05,
+            pause: 0.5,
+        }
+    }
+}
+
+impl TypeAnimation {
+    fn validate(&self) -> Result<(), RenderError> {
+        for (name, value) in [("char interval", self.char_interval), ("pause", self.pause)] {
+            if !value.is_finite() || value < 0.0 {
+                let message =
+                    format!("typing animation {name} {value} is not a non-negative number");
+                return Err(RenderErrorReason::Other(message).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the timeline for the specified `input` and advances the `elapsed_secs` counter.
+    #[allow(clippy::cast_precision_loss)] // input width is small
+    fn timeline(&self, input: &UserInput, elapsed_secs: &mut f64) -> TypingTimeline {
+        // Rounds seconds to milliseconds so that timings are rendered reasonably.
+        fn round(secs: f64) -> f64 {
+            (secs * 1_000.0).round() / 1_000.0
+        }
+
+        let (columns, pause) = if input.hidden {
+            (0, 0.0)
+        } else {
+            let columns = input.text.lines().map(str::width).max().unwrap_or(0);
+            (columns, f64::from(self.pause))
+        };
+        let input_start = *elapsed_secs;
+        let input_duration = columns as f64 * f64::from(self.char_interval);
+        let output_start = input_start + input_duration + pause;
+        *elapsed_secs = output_start + pause;
+
+        TypingTimeline {
+            input_start: round(input_start),
+            input_duration: round(input_duration),
+            columns,
+            output_start: round(output_start),
         }
     }
 }
@@ -271,6 +758,17 @@ impl Default for WrapOptions {
     }
 }
 
+/// Style of separators between interactions in the rendered transcript.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "snake_case")]
+pub enum SeparatorStyle {
+    /// Extra vertical space with the specified height in pixels.
+    Space(usize),
+    /// Horizontal line with the specified color, surrounded by the standard vertical margins.
+    Line(RgbColor),
+}
+
 /// Template for rendering [`Transcript`]s, e.g. into an [SVG] image.
 ///
 /// # Available templates
@@ -280,14 +778,17 @@ impl Default for WrapOptions {
 /// at laying out multiline texts and text backgrounds, while HTML excels at both.
 /// As a downside of this approach, the resulting SVG requires for its viewer to support
 /// HTML embedding; while web browsers *a priori* support such embedding, some other SVG viewers
-/// may not.
+/// may not. Since inputs and outputs are embedded as `<pre>` elements, copying text from
+/// the rendered image (e.g., in a web browser) preserves whitespace verbatim.
 ///
 /// A template created with [`Self::pure_svg()`] renders a transcript into pure SVG,
 /// in which text is laid out manually and backgrounds use a hack (lines of text with
 /// appropriately colored `█` chars placed behind the content lines). The resulting SVG is
 /// supported by more viewers, but it may look incorrectly in certain corner cases. For example,
 /// if the font family used in the template does not contain `█` or some chars
-/// used in the transcript, the background may be mispositioned.
+/// used in the transcript, the background may be mispositioned. Also, spaces are rendered
+/// as non-breaking spaces, and each line is a separate text element, so copying text
+/// from the image may not reproduce the original text exactly.
 ///
 /// [Snapshot testing](crate::test) functionality produces snapshots using [`Self::new()`]
 /// (i.e., with HTML embedding); pure SVG templates cannot be tested.
@@ -398,9 +899,7 @@ impl Default for WrapOptions {
 ///     palette: NamedPalette::Dracula.into(),
 ///     ..TemplateOptions::default()
 /// };
-/// let mut buffer = vec![];
-/// Template::new(template_options).render(&transcript, &mut buffer)?;
-/// let buffer = String::from_utf8(buffer)?;
+/// let buffer = Template::new(template_options).render_to_string(&transcript)?;
 /// assert!(buffer.contains(r#"Hello, <span class="fg2">world</span>!"#));
 /// # Ok(())
 /// # }
@@ -470,14 +969,180 @@ impl Template {
         transcript: &Transcript,
         destination: W,
     ) -> Result<(), RenderError> {
-        let data = self
-            .options
+        self.render_with_options(&self.options, transcript, false, destination)
+    }
+
+    fn render_with_options<W: Write>(
+        &self,
+        options: &TemplateOptions,
+        transcript: &Transcript,
+        measure_screen_height: bool,
+        mut destination: W,
+    ) -> Result<(), RenderError> {
+        options.validate()?;
+        let mut data = options
             .render_data(transcript)
             .map_err(|err| RenderErrorReason::NestedError(Box::new(err)))?;
+        data.measure_screen_height = measure_screen_height;
 
         #[cfg(feature = "tracing")]
         let _entered = tracing::debug_span!("render_to_write").entered();
-        self.handlebars
-            .render_to_write(MAIN_TEMPLATE_NAME, &data, destination)
+        if options.minify && !measure_screen_height {
+            let rendered = self.handlebars.render(MAIN_TEMPLATE_NAME, &data)?;
+            destination.write_all(minify(&rendered).as_bytes())?;
+            Ok(())
+        } else {
+            self.handlebars
+                .render_to_write(MAIN_TEMPLATE_NAME, &data, destination)
+        }
     }
+
+    /// Renders the `transcript` using the template and compresses the output with gzip.
+    /// The output can be parsed back with [`Transcript::from_svg_gz()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Self::render()`].
+    ///
+    /// [`Transcript::from_svg_gz()`]: crate::Transcript::from_svg_gz()
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    pub fn render_gz<W: Write>(
+        &self,
+        transcript: &Transcript,
+        destination: W,
+    ) -> Result<(), RenderError> {
+        let mut encoder =
+            flate2::write::GzEncoder::new(destination, flate2::Compression::default());
+        self.render(transcript, &mut encoder)?;
+        encoder.finish()?;
+        Ok(())
+    }
+
+    /// Renders the `transcript` using the template into a string. This is a convenience wrapper
+    /// around [`Self::render()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a Handlebars rendering error, if any. Besides errors returned by [`Self::render()`],
+    /// this includes the case when the rendered output is not valid UTF-8 (which is impossible
+    /// for the default templates).
+    pub fn render_to_string(&self, transcript: &Transcript) -> Result<String, RenderError> {
+        let mut buffer = vec![];
+        self.render(transcript, &mut buffer)?;
+        String::from_utf8(buffer)
+            .map_err(|err| RenderErrorReason::NestedError(Box::new(err)).into())
+    }
+
+    /// Renders each interaction from the `transcript` as a standalone image (e.g., to build
+    /// a slideshow). All images are rendered with the same options, and the terminal screen
+    /// in each image is padded to the height of the tallest interaction (similar to
+    /// [`TemplateOptions::min_height`]), so that the images have consistent dimensions.
+    ///
+    /// Screen heights are measured by rendering the template with the `measure_screen_height`
+    /// flag set in the template data; in this case, the template must output only the height
+    /// of the terminal screen in pixels. Both default templates support this; a custom template
+    /// not supporting it leads to an error.
+    ///
+    /// Rendering is performed lazily as the returned iterator is advanced (besides measuring
+    /// screen heights, which happens on the first iteration).
+    ///
+    /// # Errors
+    ///
+    /// Iterator items are errors under the same conditions as for [`Self::render_to_string()`].
+    /// If measuring screen heights fails (e.g., because the template doesn't support it),
+    /// the iterator yields a single error and terminates.
+    #[allow(clippy::missing_panics_doc)] // Panic should never be triggered
+    pub fn render_each<'a>(
+        &'a self,
+        transcript: &'a Transcript,
+    ) -> impl Iterator<Item = Result<String, RenderError>> + 'a {
+        let single_transcripts = transcript
+            .interactions
+            .iter()
+            .map(|interaction| Transcript {
+                interactions: vec![interaction.clone()],
+            });
+        let mut options = None;
+        let mut measurement_failed = false;
+        single_transcripts.map_while(move |single_transcript| {
+            if measurement_failed {
+                return None;
+            }
+            if options.is_none() {
+                match self.uniform_height_options(transcript) {
+                    Ok(uniform_options) => options = Some(uniform_options),
+                    Err(err) => {
+                        measurement_failed = true;
+                        return Some(Err(err));
+                    }
+                }
+            }
+            let options = options.as_ref().unwrap();
+            let mut buffer = vec![];
+            let rendered = self
+                .render_with_options(options, &single_transcript, false, &mut buffer)
+                .and_then(|()| {
+                    String::from_utf8(buffer)
+                        .map_err(|err| RenderErrorReason::NestedError(Box::new(err)).into())
+                });
+            Some(rendered)
+        })
+    }
+
+    /// Returns options with the minimum height set to the maximum screen height
+    /// among all interactions in the `transcript`.
+    fn uniform_height_options(
+        &self,
+        transcript: &Transcript,
+    ) -> Result<TemplateOptions, RenderError> {
+        let mut max_height = self.options.min_height.map_or(0, NonZeroUsize::get);
+        for interaction in &transcript.interactions {
+            let single_transcript = Transcript {
+                interactions: vec![interaction.clone()],
+            };
+            let mut buffer = vec![];
+            self.render_with_options(&self.options, &single_transcript, true, &mut buffer)?;
+            let Some(height) = std::str::from_utf8(&buffer)
+                .ok()
+                .and_then(|output| output.trim().parse::<usize>().ok())
+            else {
+                let message = "template does not support measuring screen heights \
+                    (cannot parse the output rendered with the `measure_screen_height` flag)";
+                return Err(RenderErrorReason::Other(message.to_owned()).into());
+            };
+            max_height = max_height.max(height);
+        }
+        Ok(TemplateOptions {
+            min_height: NonZeroUsize::new(max_height),
+            ..self.options.clone()
+        })
+    }
+}
+
+/// Produces CSS declarations for the text `style`, resolving base (indexed 0..=15) colors
+/// using `palette`, and the colors of the 8-bit grayscale ramp using `grayscale_ramp`
+/// (see [`TemplateOptions::grayscale_ramp`]). This uses the same style resolution as the writer
+/// of HTML / SVG output, so it can be used to style output in custom templates or other tooling
+/// consistently with the rendered transcripts.
+///
+/// Declarations are separated by `; ` and are not terminated with a semicolon.
+/// If `style` contains colors not supported by the crate, an empty string is returned.
+///
+/// # Examples
+///
+/// ```
+/// # use term_transcript::svg::{style_to_css, NamedPalette, Palette};
+/// # use termcolor::{Color, ColorSpec};
+/// let palette = Palette::from(NamedPalette::Xterm);
+/// let mut style = ColorSpec::new();
+/// style.set_bold(true).set_fg(Some(Color::Rgb(255, 0, 0)));
+/// assert_eq!(style_to_css(&style, &palette, None), "font-weight: bold; color: #ff0000");
+/// ```
+pub fn style_to_css(
+    style: &termcolor::ColorSpec,
+    palette: &Palette,
+    grayscale_ramp: Option<&[RgbColor; 24]>,
+) -> String {
+    write::style_to_css(style, &palette.to_indexed_colors(), grayscale_ramp)
 }
@@ -9,7 +9,7 @@ use crate::utils::RgbColor;
 /// Palette of [16 standard terminal colors][colors] (8 ordinary colors + 8 intense variations).
 ///
 /// [colors]: https://en.wikipedia.org/wiki/ANSI_escape_code#3-bit_and_4-bit
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Palette {
     /// Ordinary colors.
     pub colors: TermColors,
@@ -25,6 +25,78 @@ impl Default for Palette {
 }
 
 impl Palette {
+    /// Returns all 16 palette colors in the order of their ANSI indices.
+    pub(crate) fn to_indexed_colors(self) -> [RgbColor; 16] {
+        let [colors, intense] = [&self.colors, &self.intense_colors].map(|colors| {
+            [
+                colors.black,
+                colors.red,
+                colors.green,
+                colors.yellow,
+                colors.blue,
+                colors.magenta,
+                colors.cyan,
+                colors.white,
+            ]
+        });
+        let mut all_colors = [RgbColor(0, 0, 0); 16];
+        all_colors[..8].copy_from_slice(&colors);
+        all_colors[8..].copy_from_slice(&intense);
+        all_colors
+    }
+
+    /// Creates a palette from 16 colors in the order of their ANSI indices.
+    pub(crate) fn from_indexed_colors(colors: [RgbColor; 16]) -> Self {
+        let [colors, intense_colors] = [&colors[..8], &colors[8..]].map(|colors| TermColors {
+            black: colors[0],
+            red: colors[1],
+            green: colors[2],
+            yellow: colors[3],
+            blue: colors[4],
+            magenta: colors[5],
+            cyan: colors[6],
+            white: colors[7],
+        });
+        Self {
+            colors,
+            intense_colors,
+        }
+    }
+
+    fn map_channels(&self, mut map: impl FnMut(f64) -> f64) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        // ^ The value is clamped to the `u8` range
+        let mut map_channel = |channel: u8| map(f64::from(channel)).round().clamp(0.0, 255.0) as u8;
+        let colors = self
+            .to_indexed_colors()
+            .map(|RgbColor(r, g, b)| RgbColor(map_channel(r), map_channel(g), map_channel(b)));
+        Self::from_indexed_colors(colors)
+    }
+
+    /// Returns a palette with each color channel multiplied by the specified `factor`, e.g.
+    /// to produce a dimmed (`factor < 1`) or brightened (`factor > 1`) variant of this palette.
+    /// Channel values are rounded and clamped to the valid range.
+    #[must_use]
+    pub fn adjust_brightness(&self, factor: f64) -> Self {
+        self.map_channels(|channel| channel * factor)
+    }
+
+    /// Returns a palette with gamma correction applied to each color channel:
+    /// `channel = 255 * (channel / 255) ^ (1 / gamma)`. Thus, `gamma > 1` brightens colors,
+    /// and `gamma < 1` darkens them; pure black and white are unaffected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `gamma` is not a positive finite number.
+    #[must_use]
+    pub fn adjust_gamma(&self, gamma: f64) -> Self {
+        assert!(
+            gamma.is_finite() && gamma > 0.0,
+            "gamma must be a positive finite number"
+        );
+        self.map_channels(|channel| 255.0 * (channel / 255.0).powf(gamma.recip()))
+    }
+
     const fn dracula() -> Self {
         Self {
             colors: TermColors {
@@ -149,12 +221,37 @@ impl Palette {
             },
         }
     }
+
+    const fn high_contrast() -> Self {
+        Self {
+            colors: TermColors {
+                black: RgbColor(0, 0, 0),
+                red: RgbColor(0xff, 0x6b, 0x6b),
+                green: RgbColor(0x5f, 0xd7, 0x5f),
+                yellow: RgbColor(0xff, 0xd7, 0x5f),
+                blue: RgbColor(0x6c, 0xb6, 0xff),
+                magenta: RgbColor(0xff, 0x87, 0xff),
+                cyan: RgbColor(0x5f, 0xd7, 0xd7),
+                white: RgbColor(0xe4, 0xe4, 0xe4),
+            },
+            intense_colors: TermColors {
+                black: RgbColor(0x9e, 0x9e, 0x9e),
+                red: RgbColor(0xff, 0x91, 0x91),
+                green: RgbColor(0x87, 0xff, 0x87),
+                yellow: RgbColor(0xff, 0xff, 0x87),
+                blue: RgbColor(0x9e, 0xcb, 0xff),
+                magenta: RgbColor(0xff, 0xaf, 0xff),
+                cyan: RgbColor(0x87, 0xff, 0xff),
+                white: RgbColor(0xff, 0xff, 0xff),
+            },
+        }
+    }
 }
 
 /// Values of [8 base terminal colors][colors].
 ///
 /// [colors]: https://en.wikipedia.org/wiki/ANSI_escape_code#3-bit_and_4-bit
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TermColors {
     /// Black color.
     pub black: RgbColor,
@@ -191,7 +288,7 @@ impl<'de> Deserialize<'de> for RgbColor {
             type Value = RgbColor;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-                formatter.write_str("hex color, such as #fed or #a757ff")
+                formatter.write_str("hex color, such as #fed or #a757ff, or rgb(r, g, b) color")
             }
 
             fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
@@ -217,6 +314,10 @@ pub enum NamedPalette {
     Ubuntu,
     /// [gjm8 color scheme](https://terminal.sexy/).
     Gjm8,
+    /// High-contrast color scheme for a black background. All colors except for black
+    /// have a contrast ratio of at least 4.5:1 with the background, meeting
+    /// [WCAG AA requirements](https://www.w3.org/TR/WCAG21/#contrast-minimum) for text.
+    HighContrast,
 }
 
 impl Default for NamedPalette {
@@ -233,6 +334,7 @@ impl From<NamedPalette> for Palette {
             NamedPalette::Xterm => Self::xterm(),
             NamedPalette::Ubuntu => Self::ubuntu(),
             NamedPalette::Gjm8 => Self::gjm8(),
+            NamedPalette::HighContrast => Self::high_contrast(),
         }
     }
 }
@@ -247,6 +349,7 @@ impl FromStr for NamedPalette {
             "xterm" => Ok(Self::Xterm),
             "ubuntu" => Ok(Self::Ubuntu),
             "gjm8" => Ok(Self::Gjm8),
+            "high-contrast" => Ok(Self::HighContrast),
             _ => Err(NamedPaletteParseError(())),
         }
     }
@@ -260,7 +363,7 @@ impl fmt::Display for NamedPaletteParseError {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         formatter.write_str(
             "Invalid palette name; allowed names are `dracula`, `powershell`, `xterm`, \
-             `ubuntu` and `gjm8`",
+             `ubuntu`, `gjm8` and `high-contrast`",
         )
     }
 }
@@ -1,7 +1,30 @@
 //! Tests for the SVG rendering logic.
 
+use std::time::Duration;
+
+use test_casing::test_casing;
+
 use super::*;
-use crate::{ExitStatus, Interaction, UserInput};
+use crate::{Captured, ExitStatus, Interaction, UserInput};
+
+/// Creates the pure SVG template if `pure_svg` is set, or the default template otherwise.
+fn create_template(pure_svg: bool, options: TemplateOptions) -> Template {
+    if pure_svg {
+        Template::pure_svg(options)
+    } else {
+        Template::new(options)
+    }
+}
+
+/// Converts spaces in the `text` to non-breaking ones if `pure_svg` is set, similar to how
+/// output text is rendered by the pure SVG template.
+fn output_text(pure_svg: bool, text: &str) -> String {
+    if pure_svg {
+        text.replace(' ', "\u{a0}")
+    } else {
+        text.to_owned()
+    }
+}
 
 #[test]
 fn rendering_simple_transcript() {
@@ -26,6 +49,7 @@ fn rendering_simple_transcript() {
     assert!(buffer.contains(r#"Hello, <span class="fg2">world</span>!"#));
     assert!(!buffer.contains("data-exit-status"));
     assert!(!buffer.contains("<circle"));
+    assert!(!buffer.contains("line-highlight"));
 
     assert!(!buffer.contains("input-failure"));
     assert!(!buffer.contains("title=\"This command exited with non-zero code\""));
@@ -61,6 +85,22 @@ fn rendering_simple_transcript_to_pure_svg() {
     assert!(buffer.contains(second_output_text), "{buffer}");
 }
 
+#[test_casing(2, [false, true])]
+fn rendering_multiline_input_with_continuation_prompts(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::multiline(["echo \\", "  hello"]), "hello");
+
+    let buffer = create_template(pure_svg, TemplateOptions::default())
+        .render_to_string(&transcript)
+        .unwrap();
+    let expected_input = if pure_svg {
+        "<tspan class=\"prompt\">&gt;</tspan>   hello"
+    } else {
+        "<span class=\"prompt\">$</span> echo \\\n<span class=\"prompt\">&gt;</span>   hello</pre>"
+    };
+    assert!(buffer.contains(expected_input), "{buffer}");
+}
+
 #[test]
 fn rendering_transcript_with_hidden_input() {
     let mut transcript = Transcript::new();
@@ -139,6 +179,31 @@ fn rendering_transcript_with_empty_output_to_pure_svg() {
     assert!(buffer.contains(second_output_bg), "{buffer}");
 }
 
+#[test]
+fn rendering_visible_whitespace_to_pure_svg() {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+    let options = TemplateOptions {
+        show_whitespace: true,
+        ..TemplateOptions::default()
+    };
+
+    let mut buffer = vec![];
+    Template::pure_svg(options)
+        .render(&transcript, &mut buffer)
+        .unwrap();
+    let buffer = String::from_utf8(buffer).unwrap();
+
+    // The output text must retain the original whitespace.
+    assert!(buffer.contains("Hello,\u{a0}world!"), "{buffer}");
+    let whitespace_span = r#"<tspan xml:space="preserve" x="10" y="42" class="output-ws">"#;
+    let whitespace = format!(
+        "{whitespace_span}{}<tspan class=\"space\">·</tspan></tspan>",
+        "\u{a0}".repeat(6)
+    );
+    assert!(buffer.contains(&whitespace), "{buffer}");
+}
+
 #[test]
 fn rendering_transcript_with_explicit_success() {
     let mut transcript = Transcript::new();
@@ -255,6 +320,7 @@ fn rendering_transcript_with_animation() {
             max_height: 240,
             pixels_per_scroll: 52,
             interval: 3.0,
+            ..ScrollOptions::default()
         }),
         ..TemplateOptions::default()
     };
@@ -281,6 +347,7 @@ fn rendering_pure_svg_transcript_with_animation() {
             max_height: 240,
             pixels_per_scroll: 52,
             interval: 3.0,
+            ..ScrollOptions::default()
         }),
         ..TemplateOptions::default()
     };
@@ -600,3 +667,1095 @@ fn rendering_transcript_with_styles() {
         "{buffer}"
     );
 }
+
+#[test_casing(2, [false, true])]
+fn rendering_minified_transcript(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test"),
+        "Hello, \u{1b}[32mworld\u{1b}[0m!\n  Indented line",
+    );
+
+    let mut buffer = vec![];
+    create_template(pure_svg, TemplateOptions::default())
+        .render(&transcript, &mut buffer)
+        .unwrap();
+    let buffer = String::from_utf8(buffer).unwrap();
+
+    let options = TemplateOptions {
+        minify: true,
+        ..TemplateOptions::default()
+    };
+    let mut minified_buffer = vec![];
+    create_template(pure_svg, options)
+        .render(&transcript, &mut minified_buffer)
+        .unwrap();
+    let minified_buffer = String::from_utf8(minified_buffer).unwrap();
+
+    assert!(
+        minified_buffer.len() < buffer.len(),
+        "minified: {}, original: {}",
+        minified_buffer.len(),
+        buffer.len()
+    );
+    assert!(minified_buffer.ends_with("</svg>"), "{minified_buffer}");
+    if pure_svg {
+        assert!(
+            minified_buffer.contains("\u{a0}\u{a0}Indented\u{a0}line"),
+            "{minified_buffer}"
+        );
+    } else {
+        assert!(
+            minified_buffer.contains(r#"Hello, <span class="fg2">world</span>!"#),
+            "{minified_buffer}"
+        );
+        assert!(
+            minified_buffer.contains("\n  Indented line"),
+            "{minified_buffer}"
+        );
+    }
+}
+
+#[test]
+fn rendering_transcript_with_highlighted_lines() {
+    let mut transcript = Transcript::new();
+    let interaction =
+        Interaction::new("test", "first\nsecond\nthird").with_highlighted_lines([1, 5]);
+    transcript.add_existing_interaction(interaction);
+
+    let mut buffer = vec![];
+    Template::new(TemplateOptions::default())
+        .render(&transcript, &mut buffer)
+        .unwrap();
+    let buffer = String::from_utf8(buffer).unwrap();
+
+    assert_eq!(
+        buffer.matches(r#"<div class="line-highlight""#).count(),
+        1,
+        "{buffer}"
+    );
+    assert!(
+        buffer.contains(r#"<div class="line-highlight" style="top: 18px;"></div>"#),
+        "{buffer}"
+    );
+    assert!(buffer.contains(".line-highlight {"), "{buffer}");
+
+    let mut buffer = vec![];
+    Template::pure_svg(TemplateOptions::default())
+        .render(&transcript, &mut buffer)
+        .unwrap();
+    let buffer = String::from_utf8(buffer).unwrap();
+
+    assert_eq!(
+        buffer.matches(r#"class="line-highlight""#).count(),
+        1,
+        "{buffer}"
+    );
+    // The output starts after the input block (18px line + 2 * 2px padding + 6px margin = 28px),
+    // and the highlighted line is offset by a single line height.
+    let expected_rect = r#"<rect x="0" y="46" width="100%" height="18" class="line-highlight" />"#;
+    assert!(buffer.contains(expected_rect), "{buffer}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_folded_lines(pure_svg: bool) {
+    let output = (0..10)
+        .map(|i| format!("line #{i}\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    let interaction = Interaction::new("test", output.trim_end()).fold_lines(2..8);
+    let mut transcript = Transcript::new();
+    transcript.add_existing_interaction(interaction);
+
+    let buffer = create_template(pure_svg, TemplateOptions::default())
+        .render_to_string(&transcript)
+        .unwrap();
+
+    let marker = output_text(pure_svg, "6 lines hidden");
+    assert!(buffer.contains(&marker), "{buffer}");
+    assert!(
+        buffer.contains(&output_text(pure_svg, "line #1")),
+        "{buffer}"
+    );
+    assert!(
+        buffer.contains(&output_text(pure_svg, "line #8")),
+        "{buffer}"
+    );
+    let hidden_line_count = buffer.matches(&output_text(pure_svg, "line #5")).count();
+    // The default template retains the full output in a `data-` attribute.
+    assert_eq!(hidden_line_count, usize::from(!pure_svg), "{buffer}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_max_lines(pure_svg: bool) {
+    let output = (0..10)
+        .map(|i| format!("line #{i}\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), output.trim_end());
+    transcript.add_interaction(UserInput::command("short"), "line #0\nline #1");
+
+    let options = TemplateOptions {
+        max_lines: NonZeroUsize::new(3),
+        ..TemplateOptions::default()
+    };
+    let buffer = create_template(pure_svg, options)
+        .render_to_string(&transcript)
+        .unwrap();
+
+    let marker_count = buffer
+        .matches(&output_text(pure_svg, "lines hidden"))
+        .count();
+    assert_eq!(marker_count, 1, "{buffer}");
+    let marker = output_text(pure_svg, "7 lines hidden");
+    assert!(buffer.contains(&marker), "{buffer}");
+    assert!(
+        buffer.contains(&output_text(pure_svg, "line #2")),
+        "{buffer}"
+    );
+    let hidden_line_count = buffer.matches(&output_text(pure_svg, "line #3")).count();
+    assert_eq!(hidden_line_count, usize::from(!pure_svg), "{buffer}");
+
+    let options = TemplateOptions {
+        max_lines: NonZeroUsize::new(3),
+        scroll: Some(ScrollOptions::default()),
+        ..TemplateOptions::default()
+    };
+    let err = Template::new(options)
+        .render_to_string(&transcript)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("cannot be enabled simultaneously"), "{err}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_grayscale_ramp(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test"),
+        "\u{1b}[38;5;232mdark\u{1b}[0m \u{1b}[48;5;255mlight\u{1b}[0m",
+    );
+
+    let mut grayscale_ramp = [RgbColor(0, 0, 0); 24];
+    grayscale_ramp[0] = RgbColor(0x10, 0x12, 0x14);
+    grayscale_ramp[23] = RgbColor(0xfa, 0xf8, 0xf0);
+    let options = TemplateOptions {
+        grayscale_ramp: Some(grayscale_ramp),
+        ..TemplateOptions::default()
+    };
+    let buffer = create_template(pure_svg, options)
+        .render_to_string(&transcript)
+        .unwrap();
+
+    assert!(buffer.contains("#101214"), "{buffer}");
+    assert!(buffer.contains("#faf8f0"), "{buffer}");
+    assert!(!buffer.contains("#080808"), "{buffer}");
+}
+
+#[test]
+fn rendering_transcript_to_string() {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test"),
+        "Hello, \u{1b}[32mworld\u{1b}[0m!",
+    );
+
+    let template = Template::new(TemplateOptions::default());
+    let mut buffer = vec![];
+    template.render(&transcript, &mut buffer).unwrap();
+    let rendered = template.render_to_string(&transcript).unwrap();
+    assert_eq!(rendered.as_bytes(), buffer);
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_themed_scrollbar(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test"),
+        "Hello, \u{1b}[32mworld\u{1b}[0m!\n".repeat(22),
+    );
+
+    let options = TemplateOptions {
+        scroll: Some(ScrollOptions {
+            scrollbar_color: Some(RgbColor(0xc0, 0xff, 0xee)),
+            scrollbar_width: NonZeroUsize::new(3),
+            ..ScrollOptions::default()
+        }),
+        ..TemplateOptions::default()
+    };
+    let buffer = create_template(pure_svg, options)
+        .render_to_string(&transcript)
+        .unwrap();
+    assert!(buffer.contains(".scrollbar { fill: #c0ffee; }"), "{buffer}");
+    assert!(
+        buffer.contains(r#"<rect class="scrollbar" x="715" y="10" width="3" height="40">"#),
+        "{buffer}"
+    );
+}
+
+#[test]
+fn rendering_transcript_with_overly_wide_scrollbar() {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+
+    let options = TemplateOptions {
+        scroll: Some(ScrollOptions {
+            scrollbar_width: NonZeroUsize::new(20),
+            ..ScrollOptions::default()
+        }),
+        ..TemplateOptions::default()
+    };
+    let err = Template::new(options)
+        .render_to_string(&transcript)
+        .unwrap_err();
+    let err = err.to_string();
+    assert!(err.contains("scrollbar width 20 exceeds"), "{err}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_min_height(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+    let output = (0..30)
+        .map(|i| format!("line #{i}\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    let mut long_transcript = Transcript::new();
+    long_transcript.add_interaction(UserInput::command("test"), output.trim_end());
+
+    let options = TemplateOptions {
+        min_height: NonZeroUsize::new(300),
+        ..TemplateOptions::default()
+    };
+    let template = create_template(pure_svg, options);
+    let buffer = template.render_to_string(&transcript).unwrap();
+    assert!(
+        buffer.contains(r#"width="720" height="300" viewBox="0 0 720 300""#),
+        "{buffer}"
+    );
+
+    let buffer = template.render_to_string(&long_transcript).unwrap();
+    assert!(!buffer.contains(r#"height="300""#), "{buffer}");
+
+    let options = TemplateOptions {
+        min_height: NonZeroUsize::new(300),
+        scroll: Some(ScrollOptions {
+            max_height: 200,
+            ..ScrollOptions::default()
+        }),
+        ..TemplateOptions::default()
+    };
+    let err = Template::new(options)
+        .render_to_string(&transcript)
+        .unwrap_err();
+    let err = err.to_string();
+    assert!(
+        err.contains("minimum height 300 exceeds the maximum scroll height 200"),
+        "{err}"
+    );
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_corner_radius_and_shadow(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test").hide(),
+        "Hello, \u{1b}[32mworld\u{1b}[0m!",
+    );
+
+    let options = TemplateOptions {
+        window_frame: true,
+        corner_radius: Some(6),
+        window_shadow: true,
+        ..TemplateOptions::default()
+    };
+    let buffer = create_template(pure_svg, options)
+        .render_to_string(&transcript)
+        .unwrap();
+
+    assert!(
+        buffer.contains(r#"viewBox="-8 -30 736 76" width="736" height="76""#),
+        "{buffer}"
+    );
+    assert!(buffer.contains(r#"<filter id="window-shadow""#), "{buffer}");
+    assert!(
+        buffer.contains(r#"rx="6" filter="url(#window-shadow)""#),
+        "{buffer}"
+    );
+    assert!(buffer.contains("round 6)"), "{buffer}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_each_interaction_separately(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+    transcript.add_interaction(UserInput::command("other"), "Hi!\nHi again!");
+
+    let template = create_template(pure_svg, TemplateOptions::default());
+    let images: Vec<_> = template
+        .render_each(&transcript)
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(images.len(), 2);
+    let first_output = output_text(pure_svg, "Hello, world!");
+    assert!(images[0].contains(&first_output), "{}", images[0]);
+    assert!(!images[0].contains("other"), "{}", images[0]);
+    let second_output = output_text(pure_svg, "Hi again!");
+    assert!(images[1].contains(&second_output), "{}", images[1]);
+    assert!(!images[1].contains("Hello"), "{}", images[1]);
+    for image in &images {
+        assert!(image.contains(r#"width="720""#), "{image}");
+    }
+    // The shorter interaction is padded to the height of the taller one.
+    assert_eq!(root_height(&images[0]), root_height(&images[1]));
+    let full_image = template.render_to_string(&transcript).unwrap();
+    assert!(root_height(&images[0]) < root_height(&full_image));
+}
+
+#[test]
+fn rendering_each_interaction_with_template_not_measuring_height() {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+    transcript.add_interaction(UserInput::command("other"), "Hi!");
+
+    let template = HandlebarsTemplate::compile("{{#each interactions}}{{input.text}}{{/each}}");
+    let template = Template::custom(template.unwrap(), TemplateOptions::default());
+    let results: Vec<_> = template.render_each(&transcript).collect();
+    assert_eq!(results.len(), 1);
+    let err = results[0].as_ref().unwrap_err().to_string();
+    assert!(err.contains("measuring screen heights"), "{err}");
+}
+
+fn root_height(rendered: &str) -> usize {
+    let height_start = rendered.find(r#" height=""#).unwrap() + r#" height=""#.len();
+    let height_len = rendered[height_start..].find('"').unwrap();
+    rendered[height_start..height_start + height_len]
+        .parse()
+        .unwrap()
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_interaction_separators(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+    transcript.add_interaction(UserInput::command("other"), "Hi!");
+    transcript.add_interaction(UserInput::command("more"), "Bye!");
+
+    let render = |interaction_separator| {
+        let options = TemplateOptions {
+            interaction_separator,
+            ..TemplateOptions::default()
+        };
+        create_template(pure_svg, options)
+            .render_to_string(&transcript)
+            .unwrap()
+    };
+
+    let plain = render(None);
+    assert!(!plain.contains("separator"), "{plain}");
+
+    let spaced = render(Some(SeparatorStyle::Space(10)));
+    assert_eq!(root_height(&spaced), root_height(&plain) + 20);
+
+    let lined = render(Some(SeparatorStyle::Line(RgbColor(0xff, 0, 0))));
+    assert_eq!(root_height(&lined), root_height(&plain) + 14);
+    assert!(lined.contains("#ff0000"), "{lined}");
+    assert_eq!(lined.matches(r#"class="separator""#).count(), 2, "{lined}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_caption(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+
+    let render = |caption: Option<&str>| {
+        let options = TemplateOptions {
+            window_frame: true,
+            caption: caption.map(str::to_owned),
+            ..TemplateOptions::default()
+        };
+        create_template(pure_svg, options)
+            .render_to_string(&transcript)
+            .unwrap()
+    };
+
+    let plain = render(None);
+    assert!(!plain.contains("caption"), "{plain}");
+
+    let captioned = render(Some("Rainbow <demo>"));
+    assert_eq!(root_height(&captioned), root_height(&plain) + 30);
+    assert!(
+        captioned.contains(r#"class="caption">Rainbow &lt;demo&gt;</text>"#),
+        "{captioned}"
+    );
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_responsive_transcript(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+
+    let options = TemplateOptions {
+        responsive: true,
+        ..TemplateOptions::default()
+    };
+    let buffer = create_template(pure_svg, options)
+        .render_to_string(&transcript)
+        .unwrap();
+
+    let root_start = buffer.find("<svg ").unwrap();
+    let root_len = buffer[root_start..].find('>').unwrap();
+    let root = &buffer[root_start..root_start + root_len];
+    assert!(root.contains("viewBox="), "{root}");
+    assert!(!root.contains(" width="), "{root}");
+    assert!(!root.contains(" height="), "{root}");
+}
+
+fn root_svg_attr<'a>(root: &'a str, name: &str) -> &'a str {
+    let start = root.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+    let len = root[start..].find('"').unwrap();
+    &root[start..start + len]
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_scaled_transcript(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+
+    let [root, scaled_root] = [None, Some(2.0)].map(|scale| {
+        let options = TemplateOptions {
+            window_shadow: true,
+            scale,
+            ..TemplateOptions::default()
+        };
+        let buffer = create_template(pure_svg, options)
+            .render_to_string(&transcript)
+            .unwrap();
+        let root_start = buffer.find("<svg ").unwrap();
+        let root_len = buffer[root_start..].find('>').unwrap();
+        buffer[root_start..root_start + root_len].to_owned()
+    });
+
+    assert_eq!(
+        root_svg_attr(&root, "viewBox"),
+        root_svg_attr(&scaled_root, "viewBox")
+    );
+    for dimension in ["width", "height"] {
+        let value: usize = root_svg_attr(&root, dimension).parse().unwrap();
+        let scaled_value: usize = root_svg_attr(&scaled_root, dimension).parse().unwrap();
+        assert_eq!(scaled_value, value * 2, "{scaled_root}");
+    }
+}
+
+#[test]
+fn invalid_scale() {
+    let transcript = Transcript::new();
+    for scale in [0.0, -1.0, 100.0, f64::NAN] {
+        let options = TemplateOptions {
+            scale: Some(scale),
+            ..TemplateOptions::default()
+        };
+        let err = Template::new(options)
+            .render_to_string(&transcript)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("scale"), "{err}");
+    }
+}
+
+#[test]
+fn rendering_transcript_with_stderr() {
+    let mut transcript = Transcript::new();
+    let interaction = Interaction::new(UserInput::command("test"), "Hello, \u{1b}[32mworld")
+        .with_stderr(Captured::from("\u{1b}[31merror:\u{1b}[0m oops".to_owned()));
+    transcript.add_existing_interaction(interaction);
+
+    let stderr = transcript.interactions()[0].stderr().unwrap();
+    assert_eq!(stderr.to_plaintext().unwrap(), "error: oops");
+
+    let options = TemplateOptions::default();
+    let data = options.render_data(&transcript).unwrap();
+    let output_html = &data.interactions[0].output_html;
+    assert!(output_html.starts_with("Hello, "), "{output_html}");
+    assert!(
+        output_html.ends_with("<span class=\"fg1\">error:</span> oops"),
+        "{output_html}"
+    );
+
+    let buffer = Template::pure_svg(TemplateOptions::default())
+        .render_to_string(&transcript)
+        .unwrap();
+    assert!(
+        buffer.contains("Hello,\u{a0}<tspan class=\"fg2\">world"),
+        "{buffer}"
+    );
+    assert!(
+        buffer.contains("<tspan class=\"fg1\">error:</tspan>\u{a0}oops"),
+        "{buffer}"
+    );
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_timings(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    let interaction = Interaction::new(UserInput::command("test"), "Hello, world!")
+        .with_duration(Duration::from_millis(2_049));
+    transcript.add_existing_interaction(interaction);
+
+    let render = |show_timings| {
+        let options = TemplateOptions {
+            show_timings,
+            ..TemplateOptions::default()
+        };
+        create_template(pure_svg, options)
+            .render_to_string(&transcript)
+            .unwrap()
+    };
+
+    let hidden = render(false);
+    assert!(!hidden.contains("timing"), "{hidden}");
+    let shown = render(true);
+    let expected_timing = if pure_svg {
+        r#"class="timing" data-duration-ms="2049">(2.0s)<"#
+    } else {
+        r#"class="timing">(2.0s)<"#
+    };
+    assert!(shown.contains(expected_timing), "{shown}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_grid_layout(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("table"),
+        "┌───┬───┐\n│ \u{1b}[32ma\u{1b}[0m │ b │\n└───┴───┘\n██ <ok>",
+    );
+
+    let options = TemplateOptions {
+        grid_layout: true,
+        ..TemplateOptions::default()
+    };
+    let data = options.render_data(&transcript).unwrap();
+    let output_html = &data.interactions[0].output_html;
+    let first_line = output_html.lines().next().unwrap();
+    assert_eq!(
+        first_line,
+        "<b class=\"grid\">┌</b><b class=\"grid\">─</b><b class=\"grid\">─</b>\
+         <b class=\"grid\">─</b><b class=\"grid\">┬</b><b class=\"grid\">─</b>\
+         <b class=\"grid\">─</b><b class=\"grid\">─</b><b class=\"grid\">┐</b>"
+    );
+    assert!(
+        output_html.contains("<b class=\"grid\">│</b> <span class=\"fg2\">a</span> "),
+        "{output_html}"
+    );
+    assert!(
+        output_html.ends_with("<b class=\"grid\">█</b><b class=\"grid\">█</b> &lt;ok&gt;"),
+        "{output_html}"
+    );
+
+    let buffer = create_template(pure_svg, options)
+        .render_to_string(&transcript)
+        .unwrap();
+    assert_eq!(buffer.contains(".grid {"), !pure_svg, "{buffer}");
+    assert_eq!(buffer.contains(r#"class="grid""#), !pure_svg, "{buffer}");
+
+    let buffer = Template::new(TemplateOptions::default())
+        .render_to_string(&transcript)
+        .unwrap();
+    assert!(!buffer.contains("grid"), "{buffer}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_accessible_transcript(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("cargo build"),
+        "   \u{1b}[32mCompiling\u{1b}[0m foo <v0.1.0>\n    Finished\n",
+    );
+    transcript.add_interaction(UserInput::command("secret").hide(), "");
+    transcript.add_interaction(UserInput::command("long"), "x".repeat(100));
+
+    let default_options = TemplateOptions::default();
+    let data = default_options.render_data(&transcript).unwrap();
+    assert!(data.interactions[0].summary.is_none());
+
+    let options = TemplateOptions {
+        accessible: true,
+        ..TemplateOptions::default()
+    };
+    let data = options.render_data(&transcript).unwrap();
+    let summaries: Vec<_> = data
+        .interactions
+        .iter()
+        .map(|interaction| interaction.summary.as_deref().unwrap())
+        .collect();
+    let long_summary = format!("Input: long; Output: {}…", "x".repeat(79));
+    assert_eq!(
+        summaries,
+        [
+            "Input: cargo build; Output: Compiling foo <v0.1.0> Finished",
+            "Output: (empty)",
+            long_summary.as_str(),
+        ]
+    );
+
+    let buffer = create_template(pure_svg, options.clone())
+        .render_to_string(&transcript)
+        .unwrap();
+    assert!(buffer.contains(r#"xmlns="http://www.w3.org/2000/svg" role="img">"#));
+    assert!(
+        buffer.contains("<title>Terminal transcript</title>"),
+        "{buffer}"
+    );
+    assert!(
+        buffer.contains(
+            "<desc>Input: cargo build; Output: Compiling foo &lt;v0.1.0&gt; Finished\n\
+             Output: (empty)\n"
+        ),
+        "{buffer}"
+    );
+
+    let options = TemplateOptions {
+        caption: Some("Building".to_owned()),
+        ..options
+    };
+    let buffer = Template::new(options)
+        .render_to_string(&transcript)
+        .unwrap();
+    assert!(buffer.contains("<title>Building</title>"), "{buffer}");
+
+    let buffer = Template::new(TemplateOptions::default())
+        .render_to_string(&transcript)
+        .unwrap();
+    assert!(!buffer.contains("<desc>"), "{buffer}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_collapsed_repeats(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    for i in 0..3 {
+        let interaction = Interaction::new(UserInput::command("status"), "pending")
+            .with_duration(Duration::from_millis(100 * i));
+        transcript.add_existing_interaction(interaction);
+    }
+    transcript.add_interaction(UserInput::command("status"), "done");
+    transcript.add_interaction(UserInput::command("status"), "pending");
+
+    let default_options = TemplateOptions::default();
+    let data = default_options.render_data(&transcript).unwrap();
+    assert_eq!(data.interactions.len(), 5);
+    assert!(data
+        .interactions
+        .iter()
+        .all(|interaction| interaction.repeat_count == 1));
+
+    let options = TemplateOptions {
+        collapse_repeats: true,
+        ..TemplateOptions::default()
+    };
+    let data = options.render_data(&transcript).unwrap();
+    let repeats: Vec<_> = data
+        .interactions
+        .iter()
+        .map(|interaction| (interaction.output_html.as_str(), interaction.repeat_count))
+        .collect();
+    assert_eq!(repeats, [("pending", 3), ("done", 1), ("pending", 1)]);
+    assert_eq!(data.interactions[0].duration_ms, Some(0));
+
+    let buffer = create_template(pure_svg, options)
+        .render_to_string(&transcript)
+        .unwrap();
+    assert_eq!(
+        buffer.matches(r#"class="repeat-count""#).count(),
+        1,
+        "{buffer}"
+    );
+    assert!(buffer.contains("×3</"), "{buffer}");
+    assert!(!buffer.contains("×1"), "{buffer}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_custom_hard_break_marker(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+
+    let render = |hard_break_marker| {
+        let options = TemplateOptions {
+            wrap: Some(WrapOptions::HardBreakAt(5)),
+            hard_break_marker,
+            ..TemplateOptions::default()
+        };
+        create_template(pure_svg, options).render_to_string(&transcript)
+    };
+
+    let buffer = render('↩').unwrap();
+    if pure_svg {
+        assert!(
+            buffer.contains(r#"<tspan class="hard-br" dx=".1em">↩</tspan>"#),
+            "{buffer}"
+        );
+    } else {
+        assert!(buffer.contains("content: '↩';"), "{buffer}");
+    }
+    assert!(!buffer.contains("rotate"), "{buffer}");
+
+    let err = render('\'').unwrap_err().to_string();
+    assert!(err.contains("unsupported hard break marker"), "{err}");
+}
+
+#[test]
+fn invalid_font_family_or_additional_styles() {
+    let transcript = Transcript::new();
+    let render = |options: TemplateOptions| {
+        Template::new(options)
+            .render_to_string(&transcript)
+            .unwrap_err()
+            .to_string()
+    };
+
+    let err = render(TemplateOptions {
+        font_family: " ".to_owned(),
+        ..TemplateOptions::default()
+    });
+    assert!(err.contains("font family is empty"), "{err}");
+
+    for styles in [".test { color: red;", "} .test { color: red; }"] {
+        let err = render(TemplateOptions {
+            additional_styles: styles.to_owned(),
+            ..TemplateOptions::default()
+        });
+        assert!(err.contains("unbalanced curly braces"), "{err}");
+    }
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_width_in_chars(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello, world!");
+
+    let render = |width_chars, line_numbers| {
+        let options = TemplateOptions {
+            width_chars: NonZeroUsize::new(width_chars),
+            line_numbers,
+            ..TemplateOptions::default()
+        };
+        create_template(pure_svg, options).render_to_string(&transcript)
+    };
+
+    // 80 chars * 8.4px + 2 * 10px padding
+    let buffer = render(80, None).unwrap();
+    assert!(buffer.contains(r#" width="692" "#), "{buffer}");
+    let buffer = render(100, Some(LineNumbers::EachOutput)).unwrap();
+    assert!(buffer.contains(r#" width="892" "#), "{buffer}");
+
+    let err = render(60, None).unwrap_err().to_string();
+    assert!(err.contains("less than the hard break width"), "{err}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_total_scroll_duration(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test"),
+        "Hello, \u{1b}[32mworld\u{1b}[0m!\n".repeat(22),
+    );
+
+    let render = |total_duration| {
+        let options = TemplateOptions {
+            scroll: Some(ScrollOptions {
+                max_height: 240,
+                pixels_per_scroll: 52,
+                total_duration,
+                ..ScrollOptions::default()
+            }),
+            ..TemplateOptions::default()
+        };
+        create_template(pure_svg, options).render_to_string(&transcript)
+    };
+
+    let buffer = render(None).unwrap();
+    assert!(buffer.contains("<animateTransform"), "{buffer}");
+    assert!(!buffer.contains(r#"dur="5.5s""#), "{buffer}");
+    let buffer = render(Some(5.5)).unwrap();
+    assert_eq!(buffer.matches(r#"dur="5.5s""#).count(), 2, "{buffer}");
+
+    let err = render(Some(0.0)).unwrap_err().to_string();
+    assert!(err.contains("is not positive"), "{err}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_type_animation(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("echo hi"), "hi");
+    transcript.add_interaction(UserInput::command("ls"), "");
+
+    let render = |type_animation| {
+        let options = TemplateOptions {
+            type_animation,
+            ..TemplateOptions::default()
+        };
+        create_template(pure_svg, options).render_to_string(&transcript)
+    };
+
+    let buffer = render(None).unwrap();
+    assert!(!buffer.contains("animation-delay"), "{buffer}");
+    assert!(!buffer.contains("<set "), "{buffer}");
+
+    let buffer = render(Some(TypeAnimation::default())).unwrap();
+    if pure_svg {
+        assert_eq!(buffer.matches(r#"begin="0.85s""#).count(), 1, "{buffer}");
+        assert_eq!(buffer.matches(r#"begin="1.35s""#).count(), 2, "{buffer}");
+    } else {
+        assert!(
+            buffer.contains(
+                r#"<span class="typed" style="width: 2ch; animation-delay: 1.35s; animation-duration: 0.1s; animation-timing-function: steps(2, end);">ls</span>"#
+            ),
+            "{buffer}"
+        );
+        assert!(
+            buffer.contains(
+                r#"<span class="typed" style="width: 7ch; animation-delay: 0.0s; animation-duration: 0.35s; animation-timing-function: steps(7, end);">echo hi</span>"#
+            ),
+            "{buffer}"
+        );
+        assert!(
+            buffer.contains(r#"<div class="output appear" style="animation-delay: 0.85s;">"#),
+            "{buffer}"
+        );
+    }
+
+    let err = render(Some(TypeAnimation {
+        pause: -1.0,
+        ..TypeAnimation::default()
+    }))
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("is not a non-negative number"), "{err}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_palette_override(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "\u{1b}[31mred\u{1b}[0m");
+    let interaction = Interaction::new(UserInput::command("test"), "\u{1b}[31mred\u{1b}[0m")
+        .with_palette(NamedPalette::Xterm);
+    transcript.add_existing_interaction(interaction);
+
+    let buffer = create_template(pure_svg, TemplateOptions::default())
+        .render_to_string(&transcript)
+        .unwrap();
+
+    let color_property = if pure_svg { "fill" } else { "color" };
+    let expected_style = format!(".palette-1 .fg1 {{ {color_property}: #cd0000; }}");
+    assert!(buffer.contains(&expected_style), "{buffer}");
+    assert!(buffer.contains(r#"class="output palette-1""#), "{buffer}");
+    assert!(!buffer.contains("palette-0"), "{buffer}");
+}
+
+#[test_casing(2, [false, true])]
+fn rendering_transcript_with_legend(pure_svg: bool) {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test"),
+        "Hello, \u{1b}[32mworld\u{1b}[0m! \u{1b}[1;91mError\u{1b}[0m",
+    );
+
+    let render = |include_legend| {
+        let options = TemplateOptions {
+            include_legend,
+            ..TemplateOptions::default()
+        };
+        create_template(pure_svg, options)
+            .render_to_string(&transcript)
+            .unwrap()
+    };
+
+    let plain = render(false);
+    assert!(!plain.contains(r#"class="legend""#), "{plain}");
+
+    let with_legend = render(true);
+    // 2 rows * 18px + 2 * 10px padding
+    assert_eq!(root_height(&with_legend), root_height(&plain) + 56);
+    assert_eq!(with_legend.matches(r#"class="legend""#).count(), 2);
+    assert!(
+        with_legend.contains(r#"class="legend">green #cee318</text>"#),
+        "{with_legend}"
+    );
+    assert!(
+        with_legend.contains(r#"class="legend">intense red #"#),
+        "{with_legend}"
+    );
+}
+
+#[test]
+fn collecting_used_colors_for_legend() {
+    let output = Captured::from(
+        "\u{1b}[1;32mA\u{1b}[0m\u{1b}[92;44mB\u{1b}[0m fg3 \u{1b}[38;5;16mC\u{1b}[0m".to_owned(),
+    );
+    let (_, used_colors) = output.write_as_svg(&OutputWriteOptions::default()).unwrap();
+    let colors = Palette::default().to_indexed_colors();
+    let legend = LegendEntry::for_used_colors(used_colors, &colors);
+    let indices: Vec<_> = legend.iter().map(|entry| entry.index).collect();
+    assert_eq!(indices, [2, 4, 10]);
+    assert_eq!(legend[2].name, "intense green");
+    assert_eq!(legend[2].color, colors[10]);
+}
+
+/// Computes relative luminance of a color as per WCAG 2.1.
+fn relative_luminance(color: RgbColor) -> f64 {
+    let linearize = |channel: u8| {
+        let channel = f64::from(channel) / 255.0;
+        if channel <= 0.039_28 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linearize(color.0) + 0.7152 * linearize(color.1) + 0.0722 * linearize(color.2)
+}
+
+fn contrast_ratio(first: RgbColor, second: RgbColor) -> f64 {
+    let [first, second] = [first, second].map(relative_luminance);
+    (first.max(second) + 0.05) / (first.min(second) + 0.05)
+}
+
+#[test]
+fn high_contrast_palette_meets_wcag_aa() {
+    let palette = Palette::from(NamedPalette::HighContrast);
+    let background = palette.colors.black;
+    for (i, color) in palette.to_indexed_colors().into_iter().enumerate().skip(1) {
+        let ratio = contrast_ratio(color, background);
+        assert!(
+            ratio >= 4.5,
+            "color #{i} ({color:x}) has contrast ratio {ratio:.2}"
+        );
+    }
+
+    let parsed: NamedPalette = "high-contrast".parse().unwrap();
+    assert_eq!(parsed, NamedPalette::HighContrast);
+}
+
+#[test]
+fn adjusting_palette_brightness() {
+    let palette = Palette::from(NamedPalette::Gjm8);
+    assert_eq!(palette.adjust_brightness(1.0), palette);
+
+    let dimmed = palette.adjust_brightness(0.5);
+    assert_eq!(dimmed.colors.red, RgbColor(0x80, 0x00, 0x2e));
+    assert_eq!(dimmed.intense_colors.white, RgbColor(0x7a, 0x7a, 0x7a));
+    assert_eq!(
+        palette.adjust_brightness(0.0).colors.white,
+        RgbColor(0, 0, 0)
+    );
+
+    let brightened = palette.adjust_brightness(2.0);
+    // Channels are clamped to the `u8` range.
+    assert_eq!(brightened.colors.red, RgbColor(0xff, 0x00, 0xb6));
+    assert_eq!(brightened.intense_colors.white, RgbColor(0xff, 0xff, 0xff));
+}
+
+#[test]
+fn adjusting_palette_gamma() {
+    let palette = Palette::from(NamedPalette::Gjm8);
+    assert_eq!(palette.adjust_gamma(1.0), palette);
+
+    let brightened = palette.adjust_gamma(2.0);
+    let darkened = palette.adjust_gamma(0.5);
+    for ((color, bright), dark) in palette
+        .to_indexed_colors()
+        .into_iter()
+        .zip(brightened.to_indexed_colors())
+        .zip(darkened.to_indexed_colors())
+    {
+        assert!(bright.0 >= color.0 && bright.1 >= color.1 && bright.2 >= color.2);
+        assert!(dark.0 <= color.0 && dark.1 <= color.1 && dark.2 <= color.2);
+    }
+
+    let mut colors = [RgbColor(0x80, 0x80, 0x80); 16];
+    colors[0] = RgbColor(0, 0, 0);
+    colors[15] = RgbColor(0xff, 0xff, 0xff);
+    let brightened = Palette::from_indexed_colors(colors).adjust_gamma(2.0);
+    assert_eq!(brightened.colors.black, RgbColor(0, 0, 0));
+    assert_eq!(brightened.intense_colors.white, RgbColor(0xff, 0xff, 0xff));
+    // 255 * (128 / 255) ^ 0.5 = 180.7
+    assert_eq!(brightened.colors.white, RgbColor(0xb5, 0xb5, 0xb5));
+}
+
+#[test]
+#[should_panic(expected = "gamma must be a positive")]
+fn adjusting_palette_gamma_with_invalid_value() {
+    let _ = Palette::default().adjust_gamma(0.0);
+}
+
+#[test]
+fn rendering_transcript_via_shortcut() {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test"),
+        "Hello, \u{1b}[32mworld\u{1b}[0m!",
+    );
+
+    let expected = Template::new(TemplateOptions::default())
+        .render_to_string(&transcript)
+        .unwrap();
+    let actual = transcript
+        .render_to_svg_string(TemplateOptions::default())
+        .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn converting_styles_to_css() {
+    use termcolor::{Color, ColorSpec};
+
+    let palette = Palette::from(NamedPalette::Xterm);
+
+    let mut base = ColorSpec::new();
+    base.set_fg(Some(Color::Red)).set_bg(Some(Color::Blue));
+    assert_eq!(
+        style_to_css(&base, &palette, None),
+        "color: #cd0000; background: #0000ee"
+    );
+
+    let mut intense = ColorSpec::new();
+    intense
+        .set_fg(Some(Color::Red))
+        .set_intense(true)
+        .set_bold(true)
+        .set_underline(true);
+    assert_eq!(
+        style_to_css(&intense, &palette, None),
+        "font-weight: bold; text-decoration: underline; color: #ff0000"
+    );
+
+    let mut indexed = ColorSpec::new();
+    indexed
+        .set_fg(Some(Color::Ansi256(12)))
+        .set_bg(Some(Color::Ansi256(196)))
+        .set_italic(true);
+    assert_eq!(
+        style_to_css(&indexed, &palette, None),
+        "font-style: italic; color: #5c5cff; background: #ff0000"
+    );
+
+    let mut rgb = ColorSpec::new();
+    rgb.set_fg(Some(Color::Rgb(0x12, 0x34, 0x56)))
+        .set_dimmed(true);
+    assert_eq!(
+        style_to_css(&rgb, &palette, None),
+        "opacity: 0.7; color: #123456"
+    );
+
+    let mut gray = ColorSpec::new();
+    gray.set_bg(Some(Color::Ansi256(232)));
+    assert_eq!(style_to_css(&gray, &palette, None), "background: #080808");
+    let ramp = [RgbColor(0x10, 0x20, 0x30); 24];
+    assert_eq!(
+        style_to_css(&gray, &palette, Some(&ramp)),
+        "background: #102030"
+    );
+
+    assert_eq!(style_to_css(&ColorSpec::new(), &palette, None), "");
+}
@@ -1,11 +1,17 @@
-use std::{borrow::Cow, fmt::Write as WriteStr};
+use std::{
+    borrow::Cow,
+    fmt::Write as WriteStr,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
 
 use termcolor::NoColor;
 
 #[cfg(feature = "svg")]
 use crate::write::{SvgLine, SvgWriter};
 use crate::{
-    utils::{normalize_newlines, WriteAdapter},
+    utils::{normalize_newlines, RgbColor, WriteAdapter},
     write::HtmlWriter,
     TermError,
 };
@@ -14,13 +20,52 @@ mod parser;
 #[cfg(test)]
 mod tests;
 
-pub(crate) use self::parser::TermOutputParser;
+#[cfg(feature = "svg")]
+pub(crate) use self::parser::fold_lines;
+pub(crate) use self::parser::{clear_before_form_feed, retain_alt_screen, TermOutputParser};
+
+/// Options for [writing](Captured::write_as_html()) captured output as HTML or SVG.
+#[derive(Debug, Clone)]
+pub(crate) struct OutputWriteOptions {
+    /// Width to hard-wrap lines at.
+    pub wrap_width: Option<usize>,
+    /// Whether to make spaces and tabs visible.
+    pub show_whitespace: bool,
+    /// Whether to align box-drawing chars to the char grid (HTML only).
+    pub grid_layout: bool,
+    /// Char displayed at the end of hard-wrapped lines (SVG only).
+    #[cfg(feature = "svg")]
+    pub hard_break_marker: char,
+    /// Colors overriding the 8-bit grayscale ramp.
+    pub grayscale_ramp: Option<[RgbColor; 24]>,
+}
+
+impl Default for OutputWriteOptions {
+    fn default() -> Self {
+        Self {
+            wrap_width: None,
+            show_whitespace: false,
+            grid_layout: false,
+            #[cfg(feature = "svg")]
+            hard_break_marker: SvgWriter::DEFAULT_HARD_BREAK_MARKER,
+            grayscale_ramp: None,
+        }
+    }
+}
 
 /// Marker trait for supported types of terminal output.
 pub trait TermOutput: Clone + Send + Sync + 'static {}
 
 /// Output captured from the terminal.
-#[derive(Debug, Clone)]
+///
+/// Equality and hashing are based on the raw captured string (with normalized newlines),
+/// so outputs with the same text but different escape sequences are considered different.
+///
+/// When converting the output (e.g., [to HTML](Self::to_html())), the text written
+/// to the alternate screen buffer (i.e., between `\x1b[?1049h` and `\x1b[?1049l` sequences)
+/// is discarded. To capture the alternate screen instead, use
+/// [`ShellOptions::with_alt_screen_capture()`](crate::ShellOptions::with_alt_screen_capture()).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Captured(String);
 
 impl AsRef<str> for Captured {
@@ -40,23 +85,73 @@ impl From<String> for Captured {
 }
 
 impl Captured {
+    /// Reads raw terminal output (possibly containing ANSI escape sequences) from the provided
+    /// `reader`, e.g., a log file produced by `script` or a CI system.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if reading from `reader` fails, or if the read data is not UTF-8.
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut raw = vec![];
+        reader.read_to_end(&mut raw)?;
+        let raw = String::from_utf8(raw)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.utf8_error()))?;
+        Ok(Self::from(raw))
+    }
+
+    /// Reads raw terminal output from a file at the specified `path`.
+    /// See [`Self::from_reader()`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be opened or read, or if its contents
+    /// are not UTF-8.
+    pub fn from_ansi_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Appends `other` output starting from a new line. Styles are reset before `other`,
+    /// so that they don't leak from this output.
+    #[cfg(feature = "svg")]
+    pub(crate) fn append_lines(&self, other: &Self) -> Self {
+        let mut merged = String::with_capacity(self.0.len() + other.0.len() + 5);
+        if !self.0.is_empty() {
+            merged.push_str(&self.0);
+            if !merged.ends_with('\n') {
+                merged.push('\n');
+            }
+            merged.push_str("\u{1b}[0m");
+        }
+        merged.push_str(&other.0);
+        Self(merged)
+    }
+
     pub(crate) fn write_as_html(
         &self,
         output: &mut dyn WriteStr,
-        wrap_width: Option<usize>,
+        options: &OutputWriteOptions,
     ) -> Result<(), TermError> {
-        let mut html_writer = HtmlWriter::new(output, wrap_width);
+        let mut html_writer = HtmlWriter::new(output, options.wrap_width)
+            .with_visible_whitespace(options.show_whitespace)
+            .with_grid_layout(options.grid_layout)
+            .with_grayscale_ramp(options.grayscale_ramp);
         TermOutputParser::new(&mut html_writer).parse(self.0.as_bytes())
     }
 
+    /// Writes this output as SVG lines. Also returns the palette colors used in the output
+    /// (see [`SvgWriter::used_colors()`]).
     #[cfg(feature = "svg")]
     pub(crate) fn write_as_svg(
         &self,
-        wrap_width: Option<usize>,
-    ) -> Result<Vec<SvgLine>, TermError> {
-        let mut svg_writer = SvgWriter::new(wrap_width);
+        options: &OutputWriteOptions,
+    ) -> Result<(Vec<SvgLine>, [bool; 16]), TermError> {
+        let mut svg_writer = SvgWriter::new(options.wrap_width)
+            .with_visible_whitespace(options.show_whitespace)
+            .with_hard_break_marker(options.hard_break_marker)
+            .with_grayscale_ramp(options.grayscale_ramp);
         TermOutputParser::new(&mut svg_writer).parse(self.0.as_bytes())?;
-        Ok(svg_writer.into_lines())
+        let used_colors = svg_writer.used_colors();
+        Ok((svg_writer.into_lines(), used_colors))
     }
 
     /// Converts this terminal output to an HTML string.
@@ -85,7 +180,7 @@ impl Captured {
     /// [`white-space`]: https://developer.mozilla.org/en-US/docs/Web/CSS/white-space
     pub fn to_html(&self) -> Result<String, TermError> {
         let mut output = String::with_capacity(self.0.len());
-        self.write_as_html(&mut output, None)?;
+        self.write_as_html(&mut output, &OutputWriteOptions::default())?;
         Ok(output)
     }
 
@@ -104,6 +199,18 @@ impl Captured {
         self.write_as_plaintext(&mut output)?;
         Ok(output)
     }
+
+    /// Writes plaintext from this terminal output to the provided `writer`. This is a streaming
+    /// counterpart to [`Self::to_plaintext()`] that does not allocate the output string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an issue processing output, or if an I/O error occurs
+    /// when writing to `writer`.
+    pub fn write_plaintext<W: io::Write>(&self, writer: W) -> Result<(), TermError> {
+        let mut plaintext_writer = NoColor::new(writer);
+        TermOutputParser::new(&mut plaintext_writer).parse(self.0.as_bytes())
+    }
 }
 
 impl TermOutput for Captured {}
@@ -1,7 +1,7 @@
 //! Parser for terminal output that converts it to a sequence of instructions to
 //! a writer implementing `WriteColor`.
 
-use std::str;
+use std::{borrow::Cow, str};
 
 use termcolor::{Color, ColorSpec, WriteColor};
 
@@ -12,6 +12,8 @@ use crate::TermError;
 pub(crate) struct TermOutputParser<'a, W> {
     writer: &'a mut W,
     color_spec: ColorSpec,
+    /// Is the alternate screen buffer active? Text output to the alternate screen is discarded.
+    in_alt_screen: bool,
 }
 
 impl<'a, W: WriteColor> TermOutputParser<'a, W> {
@@ -19,6 +21,7 @@ impl<'a, W: WriteColor> TermOutputParser<'a, W> {
         Self {
             writer,
             color_spec: ColorSpec::new(),
+            in_alt_screen: false,
         }
     }
 
@@ -35,10 +38,17 @@ impl<'a, W: WriteColor> TermOutputParser<'a, W> {
 
             // We ignore everything before the last occurrence of `\r` as a stop-gap measure
             // that works reasonably well in some cases.
-            let processed_line = line.rsplitn(2, |&ch| ch == b'\r').next().unwrap_or(&[]);
+            let mut parts = line.rsplitn(2, |&ch| ch == b'\r');
+            let processed_line = parts.next().unwrap_or(&[]);
+            if let Some(ignored_part) = parts.next() {
+                // Switching screen buffers must not be ignored though.
+                if let Some(in_alt_screen) = last_alt_screen_toggle(ignored_part) {
+                    self.in_alt_screen = in_alt_screen;
+                }
+            }
             self.parse_line(processed_line)?;
 
-            if i + 1 < line_count {
+            if i + 1 < line_count && !self.in_alt_screen {
                 writeln!(self.writer).map_err(TermError::Io)?;
             }
         }
@@ -70,6 +80,9 @@ impl<'a, W: WriteColor> TermOutputParser<'a, W> {
                     let csi = Csi::parse(&term_output[i..])?;
                     let prev_color_spec = self.color_spec.clone();
                     csi.update_color_spec(&mut self.color_spec)?;
+                    if let Some(in_alt_screen) = csi.alt_screen_toggle() {
+                        self.in_alt_screen = in_alt_screen;
+                    }
                     dirty_color_spec = dirty_color_spec || prev_color_spec != self.color_spec;
                     i += csi.len;
                 } else if next_byte == ANSI_OCS {
@@ -110,6 +123,9 @@ impl<'a, W: WriteColor> TermOutputParser<'a, W> {
                 .set_color(&self.color_spec)
                 .map_err(TermError::Io)?;
         }
+        if self.in_alt_screen {
+            return Ok(());
+        }
         self.writer
             .write_all(&term_output[written_end..i])
             .map_err(TermError::Io)
@@ -120,7 +136,7 @@ impl<'a, W: WriteColor> TermOutputParser<'a, W> {
         text: &[u8],
         dirty_color_spec: &mut bool,
     ) -> Result<(), TermError> {
-        if text.is_empty() {
+        if text.is_empty() || self.in_alt_screen {
             Ok(())
         } else {
             if *dirty_color_spec {
@@ -134,6 +150,160 @@ impl<'a, W: WriteColor> TermOutputParser<'a, W> {
     }
 }
 
+/// Removes the text preceding the last form feed char (`\x0c`) in `term_output`, emulating
+/// the screen being cleared. SGR sequences in the removed part are retained, so that
+/// the styling of the remaining text is unaffected.
+pub(crate) fn clear_before_form_feed(term_output: &str) -> Cow<'_, str> {
+    let Some(form_feed_pos) = term_output.rfind('\x0c') else {
+        return Cow::Borrowed(term_output);
+    };
+    let mut output = String::with_capacity(term_output.len() - form_feed_pos);
+    push_sgr_sequences(&mut output, &term_output[..form_feed_pos]);
+    output.push_str(&term_output[form_feed_pos + 1..]);
+    Cow::Owned(output)
+}
+
+/// Retains only the text output to the alternate screen buffer in `term_output`, i.e.,
+/// the text between `CSI ?1049h` / `CSI ?1049l` sequences (or their `1047` / `47` variations).
+/// Similar to [`clear_before_form_feed()`], SGR sequences in the removed part are retained.
+/// If `term_output` never switches to the alternate screen, it is returned as-is.
+pub(crate) fn retain_alt_screen(term_output: &str) -> Cow<'_, str> {
+    const ANSI_ESC: u8 = 0x1b;
+    const ANSI_CSI: u8 = b'[';
+
+    let bytes = term_output.as_bytes();
+    let mut output = None;
+    let mut in_alt_screen = false;
+    let mut segment_start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == ANSI_ESC && bytes.get(i + 1) == Some(&ANSI_CSI) {
+            let Ok(csi) = Csi::parse(&bytes[i + 2..]) else {
+                break;
+            };
+            let end = i + 2 + csi.len;
+            if let Some(is_enter) = csi.alt_screen_toggle() {
+                let output = output.get_or_insert_with(|| String::with_capacity(term_output.len()));
+                push_screen_segment(output, &term_output[segment_start..i], in_alt_screen);
+                in_alt_screen = is_enter;
+                segment_start = end;
+            }
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+
+    let Some(mut output) = output else {
+        return Cow::Borrowed(term_output);
+    };
+    push_screen_segment(&mut output, &term_output[segment_start..], in_alt_screen);
+    Cow::Owned(output)
+}
+
+fn push_screen_segment(output: &mut String, segment: &str, in_alt_screen: bool) {
+    if in_alt_screen {
+        output.push_str(segment);
+    } else {
+        push_sgr_sequences(output, segment);
+    }
+}
+
+/// Returns the state of the alternate screen buffer after the last switch in `text`, if any.
+fn last_alt_screen_toggle(text: &[u8]) -> Option<bool> {
+    const ANSI_ESC: u8 = 0x1b;
+    const ANSI_CSI: u8 = b'[';
+
+    let mut toggle = None;
+    let mut i = 0;
+    while i < text.len() {
+        if text[i] == ANSI_ESC && text.get(i + 1) == Some(&ANSI_CSI) {
+            let Ok(csi) = Csi::parse(&text[i + 2..]) else {
+                break;
+            };
+            toggle = csi.alt_screen_toggle().or(toggle);
+            i += 2 + csi.len;
+        } else {
+            i += 1;
+        }
+    }
+    toggle
+}
+
+/// Replaces each run of lines in `term_output` covered by `ranges` with a single dimmed
+/// marker line. Lines are zero-based and separated by `\n`; out-of-range lines are ignored.
+/// Similar to [`clear_before_form_feed()`], the styling of the remaining text is unaffected.
+#[cfg(feature = "svg")]
+pub(crate) fn fold_lines(term_output: &str, ranges: &[std::ops::Range<usize>]) -> String {
+    use std::fmt::Write as _;
+
+    let lines: Vec<_> = term_output.split_inclusive('\n').collect();
+    let mut is_folded = vec![false; lines.len()];
+    for range in ranges {
+        let range = range.start.min(lines.len())..range.end.min(lines.len());
+        is_folded[range].fill(true);
+    }
+
+    let mut output = String::with_capacity(term_output.len());
+    let mut pos = 0;
+    let mut line_idx = 0;
+    while line_idx < lines.len() {
+        if !is_folded[line_idx] {
+            output.push_str(lines[line_idx]);
+            pos += lines[line_idx].len();
+            line_idx += 1;
+            continue;
+        }
+
+        let fold_len = is_folded[line_idx..]
+            .iter()
+            .take_while(|&&folded| folded)
+            .count();
+        let folded_lines = &lines[line_idx..line_idx + fold_len];
+        pos += folded_lines.iter().map(|line| line.len()).sum::<usize>();
+        line_idx += fold_len;
+
+        let noun = if fold_len == 1 { "line" } else { "lines" };
+        write!(
+            output,
+            "\u{1b}[0m\u{1b}[2m… {fold_len} {noun} hidden …\u{1b}[0m"
+        )
+        .unwrap();
+        // ^-- `unwrap` is safe; writing to a string never fails.
+        // Restore the styling in effect after the folded lines.
+        push_sgr_sequences(&mut output, &term_output[..pos]);
+        if folded_lines.last().is_some_and(|line| line.ends_with('\n')) {
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Pushes all SGR sequences from `text` to `output`, skipping all other text.
+fn push_sgr_sequences(output: &mut String, text: &str) {
+    const ANSI_ESC: u8 = 0x1b;
+    const ANSI_CSI: u8 = b'[';
+
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == ANSI_ESC && bytes.get(i + 1) == Some(&ANSI_CSI) {
+            let Ok(csi) = Csi::parse(&bytes[i + 2..]) else {
+                // Broken sequences are dropped together with the remaining text.
+                break;
+            };
+            let end = i + 2 + csi.len;
+            if csi.final_byte == b'm' {
+                // Escape sequences consist of ASCII bytes only, so slicing cannot panic.
+                output.push_str(&text[i..end]);
+            }
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct Csi<'a> {
     parameters: &'a [u8],
@@ -166,6 +336,21 @@ impl<'a> Csi<'a> {
         }
     }
 
+    /// Checks whether this sequence switches to (`Some(true)`) or from (`Some(false)`)
+    /// the alternate screen buffer.
+    fn alt_screen_toggle(self) -> Option<bool> {
+        let is_enter = match self.final_byte {
+            b'h' => true,
+            b'l' => false,
+            _ => return None,
+        };
+        let params = self.parameters.strip_prefix(b"?")?;
+        params
+            .split(|&byte| byte == b';')
+            .any(|param| matches!(param, b"1049" | b"1047" | b"47"))
+            .then_some(is_enter)
+    }
+
     fn update_color_spec(self, spec: &mut ColorSpec) -> Result<(), TermError> {
         if self.final_byte != b'm' {
             return Ok(());
@@ -34,6 +34,15 @@ fn converting_captured_output_to_text() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn writing_captured_output_as_plaintext() -> anyhow::Result<()> {
+    let output = Captured(prepare_term_output()?);
+    let mut buffer = vec![];
+    output.write_plaintext(&mut buffer)?;
+    assert_eq!(String::from_utf8(buffer)?, "Hello, world!");
+    Ok(())
+}
+
 #[test]
 fn converting_captured_output_to_html() -> anyhow::Result<()> {
     const EXPECTED_HTML: &str = "<span class=\"underline fg6\">Hello</span>, \
@@ -233,3 +242,129 @@ fn carriage_return_at_middle_of_line() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn reading_captured_output_from_reader() -> anyhow::Result<()> {
+    let raw = prepare_term_output()?.replace(", ", ",\r\n");
+    let output = Captured::from_reader(raw.as_bytes())?;
+    assert_eq!(output.to_plaintext()?, "Hello,\nworld!");
+
+    let err = Captured::from_reader(b"\xff\xfe".as_slice()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    Ok(())
+}
+
+#[test]
+fn clearing_output_before_form_feed() {
+    assert_eq!(clear_before_form_feed("old\x0cnew"), "new");
+    assert_eq!(clear_before_form_feed("no form feed"), "no form feed");
+    assert_eq!(
+        clear_before_form_feed("a\x0cb\nc\x0cnew\nlines"),
+        "new\nlines"
+    );
+
+    let term_output = "\u{1b}[32mold\u{1b}[1A\u{1b}[0m\u{1b}[1mstill old\x0cnew";
+    let cleared = clear_before_form_feed(term_output);
+    assert_eq!(cleared, "\u{1b}[32m\u{1b}[0m\u{1b}[1mnew");
+    let cleared = Captured::from(cleared.into_owned());
+    assert_eq!(cleared.to_plaintext().unwrap(), "new");
+}
+
+#[test]
+fn discarding_alt_screen_output() -> anyhow::Result<()> {
+    let term_output = "before\n\u{1b}[?1049h\u{1b}[Hfull\nscreen\u{1b}[?1049lafter";
+    let output = Captured::from(term_output.to_owned());
+    assert_eq!(output.to_plaintext()?, "before\nafter");
+
+    let term_output = "\u{1b}[?1049h\u{1b}[32mhidden\n\u{1b}[?1049lshown\u{1b}[0m";
+    let mut writer = Ansi::new(vec![]);
+    TermOutputParser::new(&mut writer).parse(term_output.as_bytes())?;
+    let rendered_output = writer.into_inner();
+    assert_eq!(
+        String::from_utf8(rendered_output)?,
+        "\u{1b}[0m\u{1b}[32mshown\u{1b}[0m"
+    );
+    Ok(())
+}
+
+#[test]
+fn alt_screen_switch_before_carriage_return() -> anyhow::Result<()> {
+    let term_output = "\u{1b}[?1049hprogress\rdone\nhidden\n\u{1b}[?47l\rshown";
+    let output = Captured::from(term_output.to_owned());
+    assert_eq!(output.to_plaintext()?, "shown");
+    Ok(())
+}
+
+#[test]
+fn retaining_alt_screen_output() {
+    assert_eq!(retain_alt_screen("no alt screen"), "no alt screen");
+
+    let term_output = "\u{1b}[1mbefore\n\u{1b}[?1049hfull\nscreen\u{1b}[?1049l\u{1b}[0mafter";
+    let retained = retain_alt_screen(term_output);
+    assert_eq!(retained, "\u{1b}[1mfull\nscreen\u{1b}[0m");
+    let retained = Captured::from(retained.into_owned());
+    assert_eq!(retained.to_plaintext().unwrap(), "full\nscreen");
+
+    // Unterminated alt screen output
+    let retained = retain_alt_screen("before\u{1b}[?1049hafter");
+    assert_eq!(retained, "after");
+}
+
+#[cfg(feature = "svg")]
+#[test]
+#[allow(clippy::single_range_in_vec_init)] // Ranges are intentionally passed as a slice
+fn folding_output_lines() {
+    let term_output = "1\n2\n3\n4\n5";
+    assert_eq!(fold_lines(term_output, &[]), term_output);
+    let folded = fold_lines(term_output, &[1..3, 2..4]);
+    assert_eq!(
+        Captured::from(folded).to_plaintext().unwrap(),
+        "1\n… 3 lines hidden …\n5"
+    );
+    let folded = fold_lines(term_output, &[4..10]);
+    assert_eq!(
+        Captured::from(folded).to_plaintext().unwrap(),
+        "1\n2\n3\n4\n… 1 line hidden …"
+    );
+
+    let term_output = "\u{1b}[32mgreen\n\u{1b}[1mbold\nstill bold";
+    let folded = fold_lines(term_output, &[1..2]);
+    assert_eq!(
+        folded,
+        "\u{1b}[32mgreen\n\u{1b}[0m\u{1b}[2m… 1 line hidden …\u{1b}[0m\u{1b}[32m\u{1b}[1m\nstill bold"
+    );
+}
+
+#[test]
+fn deduplicating_captured_outputs() {
+    use std::{collections::HashSet, time::Duration};
+
+    use crate::{Interaction, Transcript, UserInput};
+
+    let outputs = [
+        Captured::from("Hello\r\nworld".to_owned()),
+        Captured::from("Hello\nworld".to_owned()),
+        Captured::from("\u{1b}[32mHello\u{1b}[0m\nworld".to_owned()),
+    ];
+    assert_eq!(outputs[0], outputs[1]);
+    assert_ne!(outputs[1], outputs[2]);
+    let unique: HashSet<_> = outputs.iter().collect();
+    assert_eq!(unique.len(), 2);
+
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "Hello");
+    let mut other_transcript = transcript.clone();
+    assert_eq!(transcript, other_transcript);
+    other_transcript.add_existing_interaction(Interaction::new(UserInput::command("test"), "Hi"));
+    assert_ne!(transcript, other_transcript);
+    let unique: HashSet<_> = [transcript.clone(), other_transcript, transcript].into();
+    assert_eq!(unique.len(), 2);
+
+    let interaction = Interaction::new(UserInput::command("test"), "Hello")
+        .with_duration(Duration::from_millis(10));
+    let other_interaction = Interaction::new(UserInput::command("test"), "Hello")
+        .with_duration(Duration::from_millis(25));
+    assert_eq!(interaction, other_interaction);
+    let unique: HashSet<_> = [interaction, other_interaction].into();
+    assert_eq!(unique.len(), 1);
+}
@@ -1,16 +1,19 @@
 use std::{
     cmp::{self, Ordering},
-    io,
+    fmt, io,
     iter::{self, Peekable},
+    ops::Range,
+    str,
 };
 
-use termcolor::{Color, ColorSpec, WriteColor};
+use termcolor::{Color, ColorSpec, NoColor, WriteColor};
 use unicode_width::UnicodeWidthStr;
 
 #[cfg(test)]
 mod tests;
 
-use crate::{term::TermOutputParser, write::IndexOrRgb, TermError};
+use super::Effect;
+use crate::{term::TermOutputParser, utils::RgbColor, write::IndexOrRgb, TermError};
 
 #[derive(Debug, Clone)]
 pub(crate) struct ColorSpan {
@@ -19,12 +22,51 @@ pub(crate) struct ColorSpan {
 }
 
 impl ColorSpan {
-    pub fn parse(ansi_text: &str) -> Result<Vec<Self>, TermError> {
-        let mut spans = ColorSpansWriter::default();
+    /// Parses spans from ANSI-escaped text. 8-bit grayscale colors are expanded
+    /// using the `grayscale_ramp`, if specified; see [`IndexOrRgb::indexed_color()`].
+    pub fn parse(
+        ansi_text: &str,
+        grayscale_ramp: Option<&[RgbColor; 24]>,
+    ) -> Result<Vec<Self>, TermError> {
+        let mut spans = ColorSpansWriter::new(grayscale_ramp);
         TermOutputParser::new(&mut spans).parse(ansi_text.as_bytes())?;
         Ok(spans.shrink().spans)
     }
 
+    /// Splices spans for texts matched line by line via [`ElidedLines`]: spans for elided lines
+    /// are taken from `expected`, and spans for other lines from `actual`. Thus, the returned spans
+    /// cover the expected text.
+    pub fn splice_elided(expected: &[Self], actual: &[Self], lines: &ElidedLines) -> Vec<Self> {
+        let mut spliced = Vec::with_capacity(actual.len());
+        for line in &lines.0 {
+            if line.is_elided {
+                spliced.extend(Self::slice(expected, line.expected.clone()));
+            } else {
+                spliced.extend(Self::slice(actual, line.actual.clone()));
+            }
+        }
+        spliced
+    }
+
+    /// Returns spans covering the specified byte `range` of the text covered by `spans`.
+    fn slice(spans: &[Self], range: Range<usize>) -> impl Iterator<Item = Self> + '_ {
+        let mut pos = 0;
+        spans.iter().filter_map(move |span| {
+            let start = cmp::max(pos, range.start);
+            let end = cmp::min(pos + span.len, range.end);
+            pos += span.len;
+            (start < end).then(|| Self {
+                len: end - start,
+                color_spec: span.color_spec.clone(),
+            })
+        })
+    }
+
+    /// Checks whether any of `spans` has non-default styling.
+    pub fn has_color(spans: &[Self]) -> bool {
+        spans.iter().any(|span| !span.color_spec.is_none())
+    }
+
     pub fn write_colorized(
         spans: &[Self],
         out: &mut impl WriteColor,
@@ -43,6 +85,86 @@ impl ColorSpan {
         Ok(())
     }
 
+    /// Normalizes the color spec of this span before comparison.
+    fn normalize(mut self, normalization: &Normalization<'_>) -> Self {
+        for &effect in normalization.ignored_effects {
+            effect.clear(&mut self.color_spec);
+        }
+        if let Some(palette) = normalization.palette {
+            if let Some(color) = self.color_spec.fg().copied() {
+                self.color_spec
+                    .set_fg(Some(Self::snap_to_palette(color, palette)));
+            }
+            if let Some(color) = self.color_spec.bg().copied() {
+                self.color_spec
+                    .set_bg(Some(Self::snap_to_palette(color, palette)));
+            }
+        }
+        if normalization.ignored_effects.contains(&Effect::Intense) {
+            if let Some(color) = self.color_spec.fg().copied() {
+                self.color_spec.set_fg(Some(Self::strip_intensity(color)));
+            }
+            if let Some(color) = self.color_spec.bg().copied() {
+                self.color_spec.set_bg(Some(Self::strip_intensity(color)));
+            }
+        }
+        self
+    }
+
+    /// Replaces an intense indexed color (indices 8..=15) with its ordinary variation.
+    fn strip_intensity(color: Color) -> Color {
+        match color {
+            Color::Ansi256(index @ 8..=15) => {
+                ColorSpansWriter::normalize_color(Color::Ansi256(index - 8), None)
+            }
+            _ => color,
+        }
+    }
+
+    /// Replaces an RGB color exactly matching one of `palette` colors with the corresponding
+    /// indexed color.
+    fn snap_to_palette(color: Color, palette: &[RgbColor; 16]) -> Color {
+        let Color::Rgb(r, g, b) = color else {
+            return color;
+        };
+        let rgb = RgbColor(r, g, b);
+        (0_u8..)
+            .zip(palette)
+            .find_map(|(idx, &palette_color)| {
+                (palette_color == rgb)
+                    .then(|| ColorSpansWriter::normalize_color(Color::Ansi256(idx), None))
+            })
+            .unwrap_or(color)
+    }
+
+    /// Checks whether color specs of this and `other` span are equal, allowing RGB colors
+    /// to differ by at most `tolerance` in each channel.
+    fn spec_matches(&self, other: &Self, tolerance: u8) -> bool {
+        if tolerance == 0 {
+            return self.color_spec == other.color_spec;
+        }
+
+        let colors_match = |lhs: Option<&Color>, rhs: Option<&Color>| match (lhs, rhs) {
+            (Some(&Color::Rgb(lr, lg, lb)), Some(&Color::Rgb(rr, rg, rb))) => {
+                lr.abs_diff(rr) <= tolerance
+                    && lg.abs_diff(rg) <= tolerance
+                    && lb.abs_diff(rb) <= tolerance
+            }
+            _ => lhs == rhs,
+        };
+        if !colors_match(self.color_spec.fg(), other.color_spec.fg())
+            || !colors_match(self.color_spec.bg(), other.color_spec.bg())
+        {
+            return false;
+        }
+
+        let mut lhs_spec = self.color_spec.clone();
+        lhs_spec.set_fg(None).set_bg(None);
+        let mut rhs_spec = other.color_spec.clone();
+        rhs_spec.set_fg(None).set_bg(None);
+        lhs_spec == rhs_spec
+    }
+
     /// Writes a single plaintext `line` to `out` using styles from `spans_iter`.
     fn write_line<'a, I: Iterator<Item = (usize, &'a Self)>>(
         spans_iter: &mut Peekable<I>,
@@ -69,25 +191,103 @@ impl ColorSpan {
     }
 }
 
+/// Normalization applied to [`ColorSpan`]s before comparing them in [`ColorDiff`].
+#[derive(Debug, Default)]
+pub(crate) struct Normalization<'a> {
+    /// Effects cleared from both sides.
+    pub ignored_effects: &'a [Effect],
+    /// If set, RGB colors exactly matching palette colors are replaced with indexed colors.
+    pub palette: Option<&'a [RgbColor; 16]>,
+    /// Grayscale ramp used to expand 8-bit grays in captured outputs.
+    pub grayscale_ramp: Option<&'a [RgbColor; 24]>,
+    /// Maximum per-channel difference for RGB colors to be considered equal.
+    pub color_tolerance: u8,
+    /// Marker for expected lines matching arbitrary actual lines.
+    pub elision_marker: Option<&'a str>,
+}
+
+/// Line-by-line correspondence between the expected and actual texts, in which
+/// some expected lines consist of an elision marker and match arbitrary actual lines.
+#[derive(Debug)]
+pub(crate) struct ElidedLines(Vec<LineMatch>);
+
+#[derive(Debug)]
+struct LineMatch {
+    expected: Range<usize>,
+    actual: Range<usize>,
+    is_elided: bool,
+}
+
+impl ElidedLines {
+    /// Matches `expected` and `actual` texts line by line. Returns `None` if the texts
+    /// have a differing number of lines, or if any expected line other than `marker` differs
+    /// from the corresponding actual line.
+    pub fn new(expected: &str, actual: &str, marker: &str) -> Option<Self> {
+        let expected_lines = Self::lines(expected);
+        let actual_lines = Self::lines(actual);
+        if expected_lines.len() != actual_lines.len() {
+            return None;
+        }
+
+        let it = expected_lines.into_iter().zip(actual_lines);
+        let matches = it.map(|(expected_range, actual_range)| {
+            let expected_line = &expected[expected_range.clone()];
+            let actual_line = &actual[actual_range.clone()];
+            let is_elided = expected_line.trim_end_matches('\n') == marker;
+            let is_match = if is_elided {
+                expected_line.ends_with('\n') == actual_line.ends_with('\n')
+            } else {
+                expected_line == actual_line
+            };
+            is_match.then_some(LineMatch {
+                expected: expected_range,
+                actual: actual_range,
+                is_elided,
+            })
+        });
+        matches.collect::<Option<_>>().map(Self)
+    }
+
+    /// Returns byte ranges of `text` lines, including trailing `\n` chars.
+    fn lines(text: &str) -> Vec<Range<usize>> {
+        let mut pos = 0;
+        text.split_inclusive('\n')
+            .map(|line| {
+                pos += line.len();
+                pos - line.len()..pos
+            })
+            .collect()
+    }
+}
+
 /// `Write` / `WriteColor` implementation recording `ColorSpan`s for the input text.
 #[derive(Debug, Default)]
 pub(crate) struct ColorSpansWriter {
     spans: Vec<ColorSpan>,
     color_spec: ColorSpec,
+    grayscale_ramp: Option<[RgbColor; 24]>,
 }
 
 impl ColorSpansWriter {
-    fn normalize_spec(mut spec: ColorSpec) -> ColorSpec {
+    fn new(grayscale_ramp: Option<&[RgbColor; 24]>) -> Self {
+        Self {
+            grayscale_ramp: grayscale_ramp.copied(),
+            ..Self::default()
+        }
+    }
+
+    fn normalize_spec(&self, mut spec: ColorSpec) -> ColorSpec {
+        let grayscale_ramp = self.grayscale_ramp.as_ref();
         if let Some(color) = spec.fg().copied() {
-            spec.set_fg(Some(Self::normalize_color(color)));
+            spec.set_fg(Some(Self::normalize_color(color, grayscale_ramp)));
         }
         if let Some(color) = spec.bg().copied() {
-            spec.set_bg(Some(Self::normalize_color(color)));
+            spec.set_bg(Some(Self::normalize_color(color, grayscale_ramp)));
         }
         spec
     }
 
-    fn normalize_color(color: Color) -> Color {
+    fn normalize_color(color: Color, grayscale_ramp: Option<&[RgbColor; 24]>) -> Color {
         match color {
             Color::Ansi256(0) => Color::Black,
             Color::Ansi256(1) => Color::Red,
@@ -98,10 +298,12 @@ impl ColorSpansWriter {
             Color::Ansi256(6) => Color::Cyan,
             Color::Ansi256(7) => Color::White,
 
-            Color::Ansi256(index) if index >= 16 => match IndexOrRgb::indexed_color(index) {
-                IndexOrRgb::Rgb(r, g, b) => Color::Rgb(r, g, b),
-                IndexOrRgb::Index(_) => color,
-            },
+            Color::Ansi256(index) if index >= 16 => {
+                match IndexOrRgb::indexed_color(index, grayscale_ramp) {
+                    IndexOrRgb::Rgb(r, g, b) => Color::Rgb(r, g, b),
+                    IndexOrRgb::Index(_) => color,
+                }
+            }
 
             _ => color,
         }
@@ -125,6 +327,7 @@ impl ColorSpansWriter {
         Self {
             spans: shrunk_spans,
             color_spec: self.color_spec,
+            grayscale_ramp: self.grayscale_ramp,
         }
     }
 
@@ -154,7 +357,7 @@ impl WriteColor for ColorSpansWriter {
 
     fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
         debug_assert!(spec.reset());
-        self.color_spec = Self::normalize_spec(spec.clone());
+        self.color_spec = self.normalize_spec(spec.clone());
         Ok(())
     }
 
@@ -164,6 +367,122 @@ impl WriteColor for ColorSpansWriter {
     }
 }
 
+/// Difference between a [parsed](super::Parsed) snapshot output and a [captured](crate::Captured)
+/// output, returned by [`Parsed::diff()`](super::Parsed::diff()).
+///
+/// The [`Display`](fmt::Display) implementation outputs the diff in a human-readable format.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum OutputDiff {
+    /// Output texts differ. In this case, styles are not compared.
+    Text {
+        /// Expected (i.e., parsed) output text.
+        expected: String,
+        /// Actual (i.e., captured) output text.
+        actual: String,
+    },
+    /// Output texts match, but styles differ.
+    Styles(StyleDiff),
+}
+
+impl fmt::Display for OutputDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text { expected, actual } => {
+                writeln!(formatter, "Expected:")?;
+                for line in expected.lines() {
+                    writeln!(formatter, "    {line}")?;
+                }
+                writeln!(formatter, "Actual:")?;
+                for line in actual.lines() {
+                    writeln!(formatter, "    {line}")?;
+                }
+                Ok(())
+            }
+            Self::Styles(diff) => fmt::Display::fmt(diff, formatter),
+        }
+    }
+}
+
+/// Difference in styles between outputs with matching texts.
+///
+/// The [`Display`](fmt::Display) implementation outputs the diff as a table with the expected
+/// and actual styles for each differing range.
+#[derive(Debug)]
+pub struct StyleDiff {
+    inner: ColorDiff,
+}
+
+impl StyleDiff {
+    pub(crate) fn new(inner: ColorDiff) -> Self {
+        Self { inner }
+    }
+
+    pub(crate) fn inner(&self) -> &ColorDiff {
+        &self.inner
+    }
+
+    /// Returns byte ranges in the output text with differing styles.
+    pub fn differing_ranges(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.inner
+            .differing_spans
+            .iter()
+            .map(|span| span.start..span.start + span.len)
+    }
+
+    /// Returns summary statistics for this diff. This can be used to apply thresholds
+    /// programmatically instead of inspecting the full diff.
+    pub fn stats(&self) -> StyleDiffStats {
+        let mut stats = StyleDiffStats::default();
+        for span in &self.inner.differing_spans {
+            let (lhs, rhs) = (&span.lhs_color_spec, &span.rhs_color_spec);
+            stats.spans += 1;
+            stats.bytes += span.len;
+            if lhs.fg() != rhs.fg() || lhs.bg() != rhs.bg() {
+                stats.color_changes += 1;
+            }
+
+            let mut lhs_effects = lhs.clone();
+            lhs_effects.set_fg(None).set_bg(None);
+            let mut rhs_effects = rhs.clone();
+            rhs_effects.set_fg(None).set_bg(None);
+            if lhs_effects != rhs_effects {
+                stats.effect_changes += 1;
+            }
+        }
+        stats
+    }
+}
+
+/// Summary statistics for a [`StyleDiff`] returned by [`StyleDiff::stats()`].
+///
+/// A differing span may count towards both [`Self::color_changes`] and [`Self::effect_changes`].
+/// Colors are compared exactly; thus, spans differing only in colors within the configured
+/// [tolerance](super::TestConfig::with_color_tolerance()) are counted as color changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StyleDiffStats {
+    /// Number of differing spans.
+    pub spans: usize,
+    /// Total length of differing spans in bytes.
+    pub bytes: usize,
+    /// Number of spans with differing foreground or background colors.
+    pub color_changes: usize,
+    /// Number of spans with differing text effects (e.g., bold or underlined text).
+    pub effect_changes: usize,
+}
+
+impl fmt::Display for StyleDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = NoColor::new(vec![]);
+        self.inner
+            .write_as_table(&mut buffer)
+            .map_err(|_| fmt::Error)?;
+        let table = str::from_utf8(buffer.get_ref()).map_err(|_| fmt::Error)?;
+        formatter.write_str(table)
+    }
+}
+
 #[derive(Debug)]
 struct DiffColorSpan {
     start: usize,
@@ -178,7 +497,9 @@ pub(crate) struct ColorDiff {
 }
 
 impl ColorDiff {
-    pub fn new(lhs: &[ColorSpan], rhs: &[ColorSpan]) -> Self {
+    /// Creates a diff between the `lhs` and `rhs` spans. The `normalization` is applied
+    /// to both sides before comparison.
+    pub fn new(lhs: &[ColorSpan], rhs: &[ColorSpan], normalization: &Normalization<'_>) -> Self {
         debug_assert_eq!(
             lhs.iter().map(|span| span.len).sum::<usize>(),
             rhs.iter().map(|span| span.len).sum::<usize>(),
@@ -187,11 +508,11 @@ impl ColorDiff {
 
         let mut diff = Self::default();
         let mut pos = 0;
-        let mut lhs_iter = lhs.iter().cloned();
+        let mut lhs_iter = lhs.iter().map(|span| span.clone().normalize(normalization));
         let Some(mut lhs_span) = lhs_iter.next() else {
             return diff;
         };
-        let mut rhs_iter = rhs.iter().cloned();
+        let mut rhs_iter = rhs.iter().map(|span| span.clone().normalize(normalization));
         let Some(mut rhs_span) = rhs_iter.next() else {
             return diff;
         };
@@ -200,7 +521,7 @@ impl ColorDiff {
             let common_len = cmp::min(lhs_span.len, rhs_span.len);
 
             // Record a diff span if the color specs differ.
-            if lhs_span.color_spec != rhs_span.color_spec {
+            if !lhs_span.spec_matches(&rhs_span, normalization.color_tolerance) {
                 diff.differing_spans.push(DiffColorSpan {
                     start: pos,
                     len: common_len,
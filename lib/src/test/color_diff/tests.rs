@@ -9,7 +9,7 @@ use crate::write::HtmlWriter;
 
 #[test]
 fn getting_spans_basics() {
-    let spans = ColorSpan::parse("Apr 18 12:54\n\u{1b}[0m\u{1b}[33m.\u{1b}[0m").unwrap();
+    let spans = ColorSpan::parse("Apr 18 12:54\n\u{1b}[0m\u{1b}[33m.\u{1b}[0m", None).unwrap();
 
     assert_eq!(spans.len(), 2);
     assert!(spans[0].color_spec.is_none());
@@ -40,7 +40,7 @@ fn creating_color_diff_basics() {
         },
     ];
 
-    let color_diff = ColorDiff::new(&lhs, &rhs);
+    let color_diff = ColorDiff::new(&lhs, &rhs, &Normalization::default());
 
     assert_eq!(color_diff.differing_spans.len(), 1);
     let diff_span = &color_diff.differing_spans[0];
@@ -50,6 +50,50 @@ fn creating_color_diff_basics() {
     assert_eq!(diff_span.rhs_color_spec, red);
 }
 
+#[test]
+fn style_diff_stats() {
+    let mut bold_red = ColorSpec::new();
+    bold_red.set_fg(Some(Color::Red)).set_bold(true);
+    let mut bold = ColorSpec::new();
+    bold.set_bold(true);
+    let lhs = [
+        ColorSpan {
+            len: 2,
+            color_spec: ColorSpec::default(),
+        },
+        ColorSpan {
+            len: 3,
+            color_spec: bold_red,
+        },
+        ColorSpan {
+            len: 4,
+            color_spec: ColorSpec::default(),
+        },
+    ];
+    let rhs = [
+        ColorSpan {
+            len: 5,
+            color_spec: ColorSpec::default(),
+        },
+        ColorSpan {
+            len: 4,
+            color_spec: bold,
+        },
+    ];
+
+    let diff = StyleDiff::new(ColorDiff::new(&lhs, &rhs, &Normalization::default()));
+    let stats = diff.stats();
+    assert_eq!(
+        stats,
+        StyleDiffStats {
+            spans: 2,
+            bytes: 7,
+            color_changes: 1,
+            effect_changes: 2,
+        }
+    );
+}
+
 #[test]
 fn creating_color_diff_overlapping_spans() {
     let mut red = ColorSpec::new();
@@ -82,7 +126,7 @@ fn creating_color_diff_overlapping_spans() {
         },
     ];
 
-    let color_diff = ColorDiff::new(&lhs, &rhs);
+    let color_diff = ColorDiff::new(&lhs, &rhs, &Normalization::default());
     assert_eq!(color_diff.differing_spans.len(), 2);
     assert_eq!(color_diff.differing_spans[0].start, 1);
     assert_eq!(color_diff.differing_spans[0].len, 1);
@@ -97,6 +141,100 @@ fn creating_color_diff_overlapping_spans() {
     assert_eq!(color_diff.differing_spans[1].rhs_color_spec, blue);
 }
 
+#[test]
+fn creating_color_diff_with_ignored_effects() {
+    // Some terminals render bold text with intense colors.
+    let lhs = ColorSpan::parse("\u{1b}[1;31mHello\u{1b}[0m", None).unwrap();
+    let rhs = ColorSpan::parse("\u{1b}[91mHello\u{1b}[0m", None).unwrap();
+
+    let normalization = Normalization {
+        ignored_effects: &[Effect::Bold],
+        ..Normalization::default()
+    };
+    let color_diff = ColorDiff::new(&lhs, &rhs, &normalization);
+    assert_eq!(color_diff.differing_spans.len(), 1);
+    let normalization = Normalization {
+        ignored_effects: &[Effect::Intense],
+        ..Normalization::default()
+    };
+    let color_diff = ColorDiff::new(&lhs, &rhs, &normalization);
+    assert_eq!(color_diff.differing_spans.len(), 1);
+    let normalization = Normalization {
+        ignored_effects: &[Effect::Bold, Effect::Intense],
+        ..Normalization::default()
+    };
+    let color_diff = ColorDiff::new(&lhs, &rhs, &normalization);
+    assert!(color_diff.is_empty());
+}
+
+#[test]
+fn creating_color_diff_with_palette_normalization() {
+    let mut palette = [RgbColor(0, 0, 0); 16];
+    palette[1] = RgbColor(0xde, 0x38, 0x2b);
+    palette[12] = RgbColor(0x00, 0x6f, 0xb8);
+
+    let lhs = [ColorSpan {
+        len: 5,
+        color_spec: ColorSpec::new()
+            .set_fg(Some(Color::Red))
+            .set_bg(Some(Color::Ansi256(12)))
+            .clone(),
+    }];
+    let rhs = [ColorSpan {
+        len: 5,
+        color_spec: ColorSpec::new()
+            .set_fg(Some(Color::Rgb(0xde, 0x38, 0x2b)))
+            .set_bg(Some(Color::Rgb(0x00, 0x6f, 0xb8)))
+            .clone(),
+    }];
+
+    let color_diff = ColorDiff::new(&lhs, &rhs, &Normalization::default());
+    assert_eq!(color_diff.differing_spans.len(), 1);
+    let normalization = Normalization {
+        palette: Some(&palette),
+        ..Normalization::default()
+    };
+    let color_diff = ColorDiff::new(&lhs, &rhs, &normalization);
+    assert!(color_diff.is_empty());
+}
+
+#[test]
+fn creating_color_diff_with_color_tolerance() {
+    let lhs = [ColorSpan {
+        len: 5,
+        color_spec: ColorSpec::new()
+            .set_fg(Some(Color::Rgb(0xde, 0x38, 0x2b)))
+            .set_bold(true)
+            .clone(),
+    }];
+    let rhs = [ColorSpan {
+        len: 5,
+        color_spec: ColorSpec::new()
+            .set_fg(Some(Color::Rgb(0xdf, 0x37, 0x2b)))
+            .set_bold(true)
+            .clone(),
+    }];
+
+    let color_diff = ColorDiff::new(&lhs, &rhs, &Normalization::default());
+    assert_eq!(color_diff.differing_spans.len(), 1);
+    let normalization = Normalization {
+        color_tolerance: 1,
+        ..Normalization::default()
+    };
+    let color_diff = ColorDiff::new(&lhs, &rhs, &normalization);
+    assert!(color_diff.is_empty());
+
+    // Effects must still match exactly.
+    let rhs = [ColorSpan {
+        len: 5,
+        color_spec: ColorSpec::new()
+            .set_fg(Some(Color::Rgb(0xdf, 0x37, 0x2b)))
+            .clone(),
+    }];
+    let color_diff = ColorDiff::new(&lhs, &rhs, &normalization);
+    assert_eq!(color_diff.differing_spans.len(), 1);
+}
+
 fn color_spec_to_string(spec: &ColorSpec) -> String {
     let mut buffer = vec![];
     let mut out = NoColor::new(&mut buffer);
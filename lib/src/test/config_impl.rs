@@ -11,12 +11,12 @@ use std::{
 use termcolor::{Color, ColorSpec, NoColor, WriteColor};
 
 use super::{
-    color_diff::{ColorDiff, ColorSpan},
+    color_diff::{ColorSpan, Normalization, OutputDiff},
     parser::Parsed,
     utils::{ColorPrintlnWriter, IndentingWriter},
     MatchKind, TestConfig, TestOutputConfig, TestStats,
 };
-use crate::{traits::SpawnShell, Interaction, TermError, Transcript, UserInput};
+use crate::{traits::SpawnShell, ExitStatus, Interaction, TermError, Transcript, UserInput};
 
 impl<Cmd: SpawnShell + fmt::Debug, F: FnMut(&mut Transcript)> TestConfig<Cmd, F> {
     /// Tests a snapshot at the specified path with the provided inputs.
@@ -249,10 +249,7 @@ impl<Cmd: SpawnShell + fmt::Debug, F: FnMut(&mut Transcript)> TestConfig<Cmd, F>
         parsed: &Transcript<Parsed>,
         reproduced: &Transcript,
     ) -> io::Result<TestStats> {
-        let it = parsed
-            .interactions()
-            .iter()
-            .zip(reproduced.interactions().iter().map(Interaction::output));
+        let it = parsed.interactions().iter().zip(reproduced.interactions());
 
         let mut stats = TestStats {
             matches: Vec::with_capacity(parsed.interactions().len()),
@@ -262,44 +259,48 @@ impl<Cmd: SpawnShell + fmt::Debug, F: FnMut(&mut Transcript)> TestConfig<Cmd, F>
             let _entered =
                 tracing::debug_span!("compare_interaction", input = ?original.input).entered();
 
+            let reproduced_status = reproduced.exit_status();
+            let reproduced = reproduced.output();
+
             write!(out, "  ")?;
             out.set_color(ColorSpec::new().set_intense(true))?;
             write!(out, "[")?;
 
-            // First, process text only.
-            let original_text = original.output().plaintext();
-            let reproduced_text = reproduced
-                .to_plaintext()
-                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
-            let mut actual_match = if original_text == reproduced_text {
-                Some(MatchKind::TextOnly)
-            } else {
-                None
+            // If we do precise matching, styles are compared as well.
+            let is_precise = self.match_kind == MatchKind::Precise;
+            let normalization = Normalization {
+                ignored_effects: &self.ignored_effects,
+                palette: self.palette_colors.as_ref(),
+                grayscale_ramp: self.grayscale_ramp.as_ref(),
+                color_tolerance: self.color_tolerance,
+                elision_marker: self.elision_marker.as_deref(),
             };
+            let diff = original
+                .output()
+                .diff_inner(reproduced, &normalization, is_precise)
+                .map_err(|err| match err {
+                    TermError::Io(err) => err,
+                    other => io::Error::new(io::ErrorKind::InvalidInput, other),
+                })?;
             #[cfg(feature = "tracing")]
-            tracing::debug!(?actual_match, "compared output texts");
+            tracing::debug!(?diff, "compared outputs");
 
-            // If we do precise matching, check it as well.
-            let color_diff = if self.match_kind == MatchKind::Precise && actual_match.is_some() {
-                let original_spans = &original.output().color_spans;
+            let has_color = self.require_no_color && {
                 let reproduced_spans =
-                    ColorSpan::parse(reproduced.as_ref()).map_err(|err| match err {
-                        TermError::Io(err) => err,
-                        other => io::Error::new(io::ErrorKind::InvalidInput, other),
-                    })?;
-
-                let diff = ColorDiff::new(original_spans, &reproduced_spans);
-                #[cfg(feature = "tracing")]
-                tracing::debug!(?diff, "compared output coloring");
-
-                if diff.is_empty() {
-                    actual_match = Some(MatchKind::Precise);
-                    None
-                } else {
-                    Some(diff)
-                }
-            } else {
-                None
+                    ColorSpan::parse(reproduced.as_ref(), self.grayscale_ramp.as_ref())
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+                ColorSpan::has_color(&original.output().color_spans)
+                    || ColorSpan::has_color(&reproduced_spans)
+            };
+            let status_mismatch = self.check_exit_status
+                && original.exit_status().is_some_and(|status| {
+                    reproduced_status.map(ExitStatus::is_success) != Some(status.is_success())
+                });
+            let actual_match = match &diff {
+                _ if has_color || status_mismatch => None,
+                None if is_precise => Some(MatchKind::Precise),
+                None | Some(OutputDiff::Styles(_)) => Some(MatchKind::TextOnly),
+                Some(OutputDiff::Text { .. }) => None,
             };
 
             stats.matches.push(actual_match);
@@ -308,7 +309,7 @@ impl<Cmd: SpawnShell + fmt::Debug, F: FnMut(&mut Transcript)> TestConfig<Cmd, F>
                 write!(out, "+")?;
             } else {
                 out.set_color(ColorSpec::new().set_reset(false).set_fg(Some(Color::Red)))?;
-                if color_diff.is_some() {
+                if matches!(diff, Some(OutputDiff::Styles(_))) {
                     write!(out, "#")?;
                 } else {
                     write!(out, "-")?;
@@ -318,24 +319,97 @@ impl<Cmd: SpawnShell + fmt::Debug, F: FnMut(&mut Transcript)> TestConfig<Cmd, F>
             write!(out, "]")?;
             out.reset()?;
             writeln!(out, " Input: {}", original.input().as_ref())?;
+            if has_color {
+                writeln!(out, "    Colored output is not allowed")?;
+            }
+            if status_mismatch {
+                let expected = original.exit_status().unwrap().0;
+                // ^ `unwrap()` is safe by construction of `status_mismatch`
+                let actual = reproduced_status
+                    .map_or_else(|| "none".to_owned(), |status| status.0.to_string());
+                writeln!(
+                    out,
+                    "    Exit status mismatch: expected {expected}, got {actual}"
+                )?;
+            }
 
-            if let Some(diff) = color_diff {
-                let original_spans = &original.output().color_spans;
-                diff.highlight_text(out, original_text, original_spans)?;
-                diff.write_as_table(out)?;
-            } else if actual_match.is_none() {
-                Self::write_diff(out, original_text, &reproduced_text)?;
-            } else if self.output == TestOutputConfig::Verbose {
-                out.set_color(ColorSpec::new().set_fg(Some(Color::Ansi256(244))))?;
-                let mut out_with_indents = IndentingWriter::new(&mut *out, b"    ");
-                writeln!(out_with_indents, "{}", original.output().plaintext())?;
-                out.reset()?;
+            match &diff {
+                Some(OutputDiff::Styles(diff)) => {
+                    let original = original.output();
+                    let diff = diff.inner();
+                    diff.highlight_text(out, original.plaintext(), &original.color_spans)?;
+                    diff.write_as_table(out)?;
+                }
+                Some(OutputDiff::Text { expected, actual }) => {
+                    self.write_diff_with_context(out, expected, actual)?;
+                }
+                None if self.output == TestOutputConfig::Verbose => {
+                    out.set_color(ColorSpec::new().set_fg(Some(Color::Ansi256(244))))?;
+                    let mut out_with_indents = IndentingWriter::new(&mut *out, b"    ");
+                    writeln!(out_with_indents, "{}", original.output().plaintext())?;
+                    out.reset()?;
+                }
+                None => { /* do nothing */ }
             }
         }
 
         Ok(stats)
     }
 
+    /// Writes the diff between `original` and `reproduced` outputs. If the diff context is set,
+    /// the diff is split into hunks similar to unified diffs: only the changed lines and
+    /// the surrounding context lines are output, and the remaining unchanged lines are elided.
+    fn write_diff_with_context(
+        &self,
+        out: &mut impl Write,
+        original: &str,
+        reproduced: &str,
+    ) -> io::Result<()> {
+        let Some(context) = self.diff_context else {
+            return Self::write_diff(out, original, reproduced);
+        };
+
+        let original_lines: Vec<_> = original.lines().collect();
+        let reproduced_lines: Vec<_> = reproduced.lines().collect();
+        let ops = LineOp::diff(&original_lines, &reproduced_lines);
+
+        // Unchanged lines are shown if they are within `context` ops from a changed line.
+        let mut is_shown = vec![false; ops.len()];
+        for (i, op) in ops.iter().enumerate() {
+            if !matches!(op, LineOp::Equal(..)) {
+                let start = i.saturating_sub(context);
+                let end = (i + context + 1).min(ops.len());
+                is_shown[start..end].fill(true);
+            }
+        }
+
+        let mut pos = 0;
+        while pos < ops.len() {
+            let hunk_len = is_shown[pos..].iter().take_while(|&&shown| shown).count();
+            if hunk_len == 0 {
+                let skipped_len = is_shown[pos..].iter().take_while(|&&shown| !shown).count();
+                writeln!(out, "    ... ({skipped_len} unchanged lines)")?;
+                pos += skipped_len;
+                continue;
+            }
+
+            let hunk = &ops[pos..pos + hunk_len];
+            let hunk_original = hunk.iter().filter_map(|op| match op {
+                LineOp::Equal(idx, _) | LineOp::Removed(idx) => Some(original_lines[*idx]),
+                LineOp::Added(_) => None,
+            });
+            let hunk_reproduced = hunk.iter().filter_map(|op| match op {
+                LineOp::Equal(_, idx) | LineOp::Added(idx) => Some(reproduced_lines[*idx]),
+                LineOp::Removed(_) => None,
+            });
+            let hunk_original: Vec<_> = hunk_original.collect();
+            let hunk_reproduced: Vec<_> = hunk_reproduced.collect();
+            Self::write_diff(out, &hunk_original.join("\n"), &hunk_reproduced.join("\n"))?;
+            pos += hunk_len;
+        }
+        Ok(())
+    }
+
     #[cfg(feature = "pretty_assertions")]
     fn write_diff(out: &mut impl Write, original: &str, reproduced: &str) -> io::Result<()> {
         use pretty_assertions::Comparison;
@@ -375,3 +449,86 @@ impl<Cmd: SpawnShell + fmt::Debug, F: FnMut(&mut Transcript)> TestConfig<Cmd, F>
         Ok(())
     }
 }
+
+/// Operation in a line-based diff. Indices refer to original / reproduced lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineOp {
+    Equal(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+impl LineOp {
+    /// Maximum number of cells in the table used to find the longest common subsequence
+    /// of lines. If the changed parts of outputs are larger, they are treated as changed
+    /// entirely.
+    const MAX_TABLE_SIZE: usize = 1 << 22;
+
+    /// Computes a line-based diff based on the longest common subsequence of lines.
+    fn diff(original: &[&str], reproduced: &[&str]) -> Vec<Self> {
+        let common_prefix = original
+            .iter()
+            .zip(reproduced)
+            .take_while(|(original, reproduced)| original == reproduced)
+            .count();
+        let max_common_suffix = original.len().min(reproduced.len()) - common_prefix;
+        let common_suffix = original
+            .iter()
+            .rev()
+            .zip(reproduced.iter().rev())
+            .take(max_common_suffix)
+            .take_while(|(original, reproduced)| original == reproduced)
+            .count();
+
+        let mut ops: Vec<_> = (0..common_prefix).map(|i| Self::Equal(i, i)).collect();
+        let original_middle = &original[common_prefix..original.len() - common_suffix];
+        let reproduced_middle = &reproduced[common_prefix..reproduced.len() - common_suffix];
+        Self::diff_middle(&mut ops, original_middle, reproduced_middle, common_prefix);
+
+        let original_suffix_start = original.len() - common_suffix;
+        let reproduced_suffix_start = reproduced.len() - common_suffix;
+        ops.extend(
+            (0..common_suffix)
+                .map(|i| Self::Equal(original_suffix_start + i, reproduced_suffix_start + i)),
+        );
+        ops
+    }
+
+    fn diff_middle(ops: &mut Vec<Self>, original: &[&str], reproduced: &[&str], offset: usize) {
+        let (rows, cols) = (original.len() + 1, reproduced.len() + 1);
+        if rows * cols > Self::MAX_TABLE_SIZE {
+            ops.extend((0..original.len()).map(|i| Self::Removed(offset + i)));
+            ops.extend((0..reproduced.len()).map(|i| Self::Added(offset + i)));
+            return;
+        }
+
+        // `table[i * cols + j]` is the LCS length for `original[i..]` and `reproduced[j..]`.
+        let mut table = vec![0_usize; rows * cols];
+        for i in (0..original.len()).rev() {
+            for j in (0..reproduced.len()).rev() {
+                table[i * cols + j] = if original[i] == reproduced[j] {
+                    table[(i + 1) * cols + j + 1] + 1
+                } else {
+                    table[(i + 1) * cols + j].max(table[i * cols + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < original.len() && j < reproduced.len() {
+            if original[i] == reproduced[j] {
+                ops.push(Self::Equal(offset + i, offset + j));
+                i += 1;
+                j += 1;
+            } else if table[(i + 1) * cols + j] >= table[i * cols + j + 1] {
+                ops.push(Self::Removed(offset + i));
+                i += 1;
+            } else {
+                ops.push(Self::Added(offset + j));
+                j += 1;
+            }
+        }
+        ops.extend((i..original.len()).map(|i| Self::Removed(offset + i)));
+        ops.extend((j..reproduced.len()).map(|j| Self::Added(offset + j)));
+    }
+}
@@ -52,11 +52,11 @@
 //! # }
 //! ```
 
-use std::process::Command;
 #[cfg(feature = "svg")]
 use std::{env, ffi::OsStr};
+use std::{path::Path, process::Command};
 
-use termcolor::ColorChoice;
+use termcolor::{ColorChoice, ColorSpec};
 
 mod color_diff;
 mod config_impl;
@@ -65,10 +65,13 @@ mod parser;
 mod tests;
 mod utils;
 
-pub use self::parser::Parsed;
+pub use self::{
+    color_diff::{OutputDiff, StyleDiff, StyleDiffStats},
+    parser::{ContainerError, ParseError, Parsed},
+};
 #[cfg(feature = "svg")]
-use crate::svg::Template;
-use crate::{traits::SpawnShell, ShellOptions, Transcript};
+use crate::svg::{Palette, Template};
+use crate::{traits::SpawnShell, utils::RgbColor, ShellOptions, Transcript};
 
 /// Configuration of output produced during testing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -164,6 +167,14 @@ pub struct TestConfig<Cmd = Command, F = fn(&mut Transcript)> {
     match_kind: MatchKind,
     output: TestOutputConfig,
     color_choice: ColorChoice,
+    diff_context: Option<usize>,
+    ignored_effects: Vec<Effect>,
+    palette_colors: Option<[RgbColor; 16]>,
+    grayscale_ramp: Option<[RgbColor; 24]>,
+    require_no_color: bool,
+    color_tolerance: u8,
+    check_exit_status: bool,
+    elision_marker: Option<String>,
     #[cfg(feature = "svg")]
     update_mode: UpdateMode,
     #[cfg(feature = "svg")]
@@ -184,6 +195,14 @@ impl<Cmd: SpawnShell> TestConfig<Cmd> {
             match_kind: MatchKind::TextOnly,
             output: TestOutputConfig::Normal,
             color_choice: ColorChoice::Auto,
+            diff_context: None,
+            ignored_effects: vec![],
+            palette_colors: None,
+            grayscale_ramp: None,
+            require_no_color: false,
+            color_tolerance: 0,
+            check_exit_status: false,
+            elision_marker: None,
             #[cfg(feature = "svg")]
             update_mode: UpdateMode::from_env(),
             #[cfg(feature = "svg")]
@@ -204,6 +223,14 @@ impl<Cmd: SpawnShell> TestConfig<Cmd> {
             match_kind: self.match_kind,
             output: self.output,
             color_choice: self.color_choice,
+            diff_context: self.diff_context,
+            ignored_effects: self.ignored_effects,
+            palette_colors: self.palette_colors,
+            grayscale_ramp: self.grayscale_ramp,
+            require_no_color: self.require_no_color,
+            color_tolerance: self.color_tolerance,
+            check_exit_status: self.check_exit_status,
+            elision_marker: self.elision_marker,
             #[cfg(feature = "svg")]
             update_mode: self.update_mode,
             #[cfg(feature = "svg")]
@@ -232,6 +259,20 @@ impl<Cmd: SpawnShell, F: FnMut(&mut Transcript)> TestConfig<Cmd, F> {
         self
     }
 
+    /// Chooses coloring of the output based on the environment. Output is not colored if
+    /// the `NO_COLOR` env var is set to a non-empty value, or if `CLICOLOR` is set to `0`.
+    /// Output is always colored if `CLICOLOR_FORCE` is set to a non-empty value other than `0`.
+    /// Otherwise, output is colored only if stdout is a terminal (i.e., when running tests
+    /// locally, but not in CI logs).
+    ///
+    /// The env vars are read when this method is called. Coloring may be overridden later
+    /// via [`Self::with_color_choice()`].
+    #[must_use]
+    pub fn with_auto_color(mut self) -> Self {
+        self.color_choice = utils::color_choice_from_env();
+        self
+    }
+
     /// Configures test output.
     #[must_use]
     pub fn with_output(mut self, output: TestOutputConfig) -> Self {
@@ -239,6 +280,88 @@ impl<Cmd: SpawnShell, F: FnMut(&mut Transcript)> TestConfig<Cmd, F> {
         self
     }
 
+    /// Sets the number of unchanged lines displayed around the changed lines when outputting
+    /// a diff between the original and reproduced outputs. Similar to unified diffs,
+    /// the diff is split into hunks, and the unchanged lines outside of hunks are elided.
+    ///
+    /// By default, the entire output is displayed.
+    #[must_use]
+    pub fn with_diff_context(mut self, lines: usize) -> Self {
+        self.diff_context = Some(lines);
+        self
+    }
+
+    /// Sets text effects ignored during [precise matching](MatchKind::Precise). This can be used
+    /// to reduce flakiness caused by terminals representing effects differently (e.g., rendering
+    /// bold text using intense colors).
+    #[must_use]
+    pub fn with_ignored_effects(mut self, effects: impl IntoIterator<Item = Effect>) -> Self {
+        self.ignored_effects.extend(effects);
+        self
+    }
+
+    /// Sets the maximum per-channel difference for RGB colors to be considered equal during
+    /// [precise matching](MatchKind::Precise). This can be used to compare outputs produced
+    /// by tools that round colors slightly differently. Indexed colors are still compared exactly.
+    ///
+    /// By default, the tolerance is zero, i.e., RGB colors must match exactly.
+    #[must_use]
+    pub fn with_color_tolerance(mut self, tolerance: u8) -> Self {
+        self.color_tolerance = tolerance;
+        self
+    }
+
+    /// Sets the marker for eliding volatile lines in snapshots (e.g., `[..]`). If a snapshot output
+    /// line consists of the marker, the corresponding captured line matches regardless
+    /// of its content; other lines must match as usual. Each marker matches exactly one line,
+    /// so the snapshot and the captured output must have the same number of lines.
+    /// During [precise matching](MatchKind::Precise), styles of the elided lines are not compared.
+    ///
+    /// Since snapshots are rendered from captured outputs, markers need to be added to snapshots
+    /// manually. By default, no lines are elided.
+    #[must_use]
+    pub fn with_elision_marker(mut self, marker: &str) -> Self {
+        self.elision_marker = Some(marker.to_owned());
+        self
+    }
+
+    /// Requires both the snapshot and the captured output to be free of colors and text effects.
+    /// If either of them contains styled text, the corresponding interaction is considered
+    /// mismatched, regardless of the [match kind](Self::with_match_kind()). This can be used
+    /// to guard tests for programs that should produce plain output (e.g., if stdout
+    /// is not a terminal or `NO_COLOR` is set).
+    #[must_use]
+    pub fn require_no_color(mut self) -> Self {
+        self.require_no_color = true;
+        self
+    }
+
+    /// Sets whether to compare [exit statuses](crate::Interaction::exit_status()) recorded
+    /// in the snapshot with the freshly captured ones. If enabled, an interaction is considered
+    /// mismatched, regardless of the [match kind](Self::with_match_kind()), if the snapshot
+    /// records an exit status and the captured status differs from it in success (e.g.,
+    /// the snapshot says the command succeeded, but it now fails). Interactions without
+    /// a recorded exit status are not checked.
+    ///
+    /// Exit statuses are only captured if the [status check](ShellOptions::with_status_check())
+    /// is configured in the shell options; otherwise, all interactions with a recorded status
+    /// will be mismatched. By default, exit statuses are not checked.
+    #[must_use]
+    pub fn check_exit_status(mut self, check: bool) -> Self {
+        self.check_exit_status = check;
+        self
+    }
+
+    /// Sets the working directory of the shell in which snapshot inputs are executed.
+    /// This is useful if snapshots use relative paths and were recorded in a directory
+    /// different from the current dir of the test. This is a shortcut for
+    /// [`ShellOptions::with_current_dir()`].
+    #[must_use]
+    pub fn with_working_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.shell_options = self.shell_options.with_current_dir(dir);
+        self
+    }
+
     /// Sets the template for rendering new snapshots.
     #[cfg(feature = "svg")]
     #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
@@ -248,6 +371,32 @@ impl<Cmd: SpawnShell, F: FnMut(&mut Transcript)> TestConfig<Cmd, F> {
         self
     }
 
+    /// Enables normalizing RGB colors during [precise matching](MatchKind::Precise): RGB colors
+    /// in the captured output that exactly match one of `palette` colors are considered equal
+    /// to the corresponding base (indexed) color. This allows comparing snapshots with outputs
+    /// of programs using 24-bit colors to represent palette colors.
+    #[cfg(feature = "svg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
+    #[must_use]
+    pub fn with_palette_normalization(mut self, palette: &Palette) -> Self {
+        self.palette_colors = Some(palette.to_indexed_colors());
+        self
+    }
+
+    /// Sets the grayscale ramp used to expand 8-bit grays in captured outputs during
+    /// [precise matching](MatchKind::Precise). This should be set to the same value
+    /// as [`TemplateOptions::grayscale_ramp`] used to render snapshots; otherwise, grays
+    /// rendered with a custom ramp will not match the captured ones.
+    ///
+    /// [`TemplateOptions::grayscale_ramp`]: crate::svg::TemplateOptions::grayscale_ramp
+    #[cfg(feature = "svg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
+    #[must_use]
+    pub fn with_grayscale_ramp(mut self, grayscale_ramp: [RgbColor; 24]) -> Self {
+        self.grayscale_ramp = Some(grayscale_ramp);
+        self
+    }
+
     /// Overrides the strategy for saving new snapshots for failed tests.
     ///
     /// By default, the strategy is determined from the execution environment
@@ -271,6 +420,35 @@ pub enum MatchKind {
     Precise,
 }
 
+/// Text effect that can be [ignored](TestConfig::with_ignored_effects()) during precise matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Effect {
+    /// Bold text.
+    Bold,
+    /// Dimmed text.
+    Dimmed,
+    /// Italic text.
+    Italic,
+    /// Underlined text.
+    Underline,
+    /// Intense (aka bright) color variations. If ignored, intense indexed colors
+    /// (e.g., set via `\x1b[91m`) are considered equal to the corresponding ordinary colors.
+    Intense,
+}
+
+impl Effect {
+    fn clear(self, spec: &mut ColorSpec) {
+        match self {
+            Self::Bold => spec.set_bold(false),
+            Self::Dimmed => spec.set_dimmed(false),
+            Self::Italic => spec.set_italic(false),
+            Self::Underline => spec.set_underline(false),
+            Self::Intense => spec.set_intense(false),
+        };
+    }
+}
+
 /// Stats of a single snapshot test output by [`TestConfig::test_transcript_for_stats()`].
 #[derive(Debug, Clone)]
 pub struct TestStats {
@@ -8,6 +8,7 @@ use std::{
     mem,
     num::ParseIntError,
     str::{self, Utf8Error},
+    time::Duration,
 };
 
 use quick_xml::{
@@ -15,15 +16,18 @@ use quick_xml::{
     events::{attributes::Attributes, Event},
     Reader as XmlReader,
 };
-use termcolor::WriteColor;
+use termcolor::{Ansi, WriteColor};
 
 #[cfg(test)]
 mod tests;
 mod text;
 
 use self::text::TextReadingState;
+#[cfg(feature = "svg")]
+use crate::{svg::Palette, utils::RgbColor};
 use crate::{
-    test::color_diff::ColorSpan, ExitStatus, Interaction, TermOutput, Transcript, UserInput,
+    test::color_diff::{ColorDiff, ColorSpan, ElidedLines, Normalization, OutputDiff, StyleDiff},
+    Captured, ExitStatus, Interaction, TermError, TermOutput, Transcript, UserInput,
 };
 
 fn map_utf8_error(err: Utf8Error) -> quick_xml::Error {
@@ -65,6 +69,88 @@ impl Parsed {
         &self.html
     }
 
+    /// Converts this parsed output to [`Captured`] output, with styles represented
+    /// by ANSI escape sequences.
+    #[allow(clippy::missing_panics_doc)] // Panic should never be triggered
+    pub fn to_captured(&self) -> Captured {
+        let mut ansi = Ansi::new(vec![]);
+        self.write_colorized(&mut ansi)
+            .expect("writing to `Vec` never fails");
+        let raw = String::from_utf8(ansi.into_inner()).expect("ANSI output is not UTF-8");
+        Captured::from(raw)
+    }
+
+    /// Compares this parsed output with the `captured` output. Texts of the outputs are compared
+    /// first; if they match, styles are compared as well. Returns `None` if the outputs fully match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `captured` output cannot be parsed (e.g., it contains invalid
+    /// escape sequences).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use term_transcript::{svg::Template, test::OutputDiff, Captured, Transcript, UserInput};
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut transcript = Transcript::new();
+    /// transcript.add_interaction(UserInput::command("test"), "Hello, \u{1b}[32mworld\u{1b}[0m!");
+    /// let svg = Template::default().render_to_string(&transcript)?;
+    /// let parsed = Transcript::from_svg(svg.as_bytes())?;
+    /// let parsed_output = parsed.interactions()[0].output();
+    ///
+    /// let captured = Captured::from("Hello, \u{1b}[32mworld\u{1b}[0m!".to_owned());
+    /// assert!(parsed_output.diff(&captured)?.is_none());
+    ///
+    /// let captured = Captured::from("Hello, \u{1b}[31mworld\u{1b}[0m!".to_owned());
+    /// let diff = parsed_output.diff(&captured)?.unwrap();
+    /// let OutputDiff::Styles(diff) = diff else { unreachable!() };
+    /// assert_eq!(diff.differing_ranges().collect::<Vec<_>>(), [7..12]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff(&self, captured: &Captured) -> Result<Option<OutputDiff>, TermError> {
+        self.diff_inner(captured, &Normalization::default(), true)
+    }
+
+    pub(crate) fn diff_inner(
+        &self,
+        captured: &Captured,
+        normalization: &Normalization<'_>,
+        compare_styles: bool,
+    ) -> Result<Option<OutputDiff>, TermError> {
+        let actual = captured.to_plaintext()?;
+        let elided_lines = if self.plaintext == actual {
+            None
+        } else {
+            let elided_lines = normalization
+                .elision_marker
+                .and_then(|marker| ElidedLines::new(&self.plaintext, &actual, marker));
+            if elided_lines.is_none() {
+                return Ok(Some(OutputDiff::Text {
+                    expected: self.plaintext.clone(),
+                    actual,
+                }));
+            }
+            elided_lines
+        };
+        if !compare_styles {
+            return Ok(None);
+        }
+
+        let mut captured_spans = ColorSpan::parse(captured.as_ref(), normalization.grayscale_ramp)?;
+        if let Some(elided_lines) = &elided_lines {
+            captured_spans =
+                ColorSpan::splice_elided(&self.color_spans, &captured_spans, elided_lines);
+        }
+        let diff = ColorDiff::new(&self.color_spans, &captured_spans, normalization);
+        Ok(if diff.is_empty() {
+            None
+        } else {
+            Some(OutputDiff::Styles(StyleDiff::new(diff)))
+        })
+    }
+
     /// Converts this parsed fragment into text for `UserInput`. This takes into account
     /// that while the first space after prompt is inserted automatically, the further whitespace
     /// may be significant.
@@ -91,6 +177,112 @@ impl Transcript<Parsed> {
     /// [`Template::render()`]: crate::svg::Template::render()
     #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
     pub fn from_svg<R: BufRead>(reader: R) -> Result<Self, ParseError> {
+        Self::parse_svg(reader, &mut ErrorRecovery::strict())
+    }
+
+    /// Parses a transcript from the provided `reader` similarly to [`Self::from_svg()`], but
+    /// recovers from non-fatal errors (e.g., an invalid exit status or an unexpected attribute
+    /// of a transcript container) and collects all encountered errors instead of stopping
+    /// at the first one.
+    ///
+    /// The returned transcript is `None` if a fatal error was encountered (e.g., the input
+    /// is not well-formed XML); in this case, the fatal error is the last one in the returned
+    /// errors.
+    pub fn from_svg_collecting<R: BufRead>(reader: R) -> (Option<Self>, Vec<ParseError>) {
+        let mut recovery = ErrorRecovery::collecting();
+        match Self::parse_svg(reader, &mut recovery) {
+            Ok(transcript) => (Some(transcript), recovery.errors),
+            Err(err) => {
+                recovery.errors.push(err);
+                (None, recovery.errors)
+            }
+        }
+    }
+
+    /// Parses a gzip-compressed transcript from the provided `reader`. This is equivalent
+    /// to decompressing the input and calling [`Self::from_svg()`] on the result.
+    ///
+    /// # Errors
+    ///
+    /// - Returns an error if the input cannot be decompressed.
+    /// - Returns an error under the same conditions as [`Self::from_svg()`].
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    pub fn from_svg_gz<R: io::Read>(reader: R) -> Result<Self, ParseError> {
+        let decoder = flate2::read::GzDecoder::new(reader);
+        Self::from_svg(io::BufReader::new(decoder))
+    }
+
+    /// Reads the [`Palette`] used to render an SVG transcript from the provided `reader`.
+    /// The palette is inferred from the `.fg0`..`.fg15` CSS rules in the first `<style>` element,
+    /// which are emitted by both standard [templates](crate::svg::Template).
+    ///
+    /// Returns `None` if the input is not well-formed XML, or if its styles do not define
+    /// all 16 palette colors (e.g., because it was rendered with a custom template).
+    #[cfg(feature = "svg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
+    pub fn palette_from_svg<R: BufRead>(reader: R) -> Option<Palette> {
+        let mut reader = XmlReader::from_reader(reader);
+        let mut buffer = vec![];
+        let mut in_style = false;
+        let mut css = String::new();
+        loop {
+            match reader.read_event_into(&mut buffer).ok()? {
+                Event::Start(tag) if tag.name().as_ref() == b"style" => in_style = true,
+                Event::End(tag) if in_style && tag.name().as_ref() == b"style" => break,
+                Event::Text(text) if in_style => css.push_str(&text.unescape().ok()?),
+                Event::CData(text) if in_style => css.push_str(str::from_utf8(&text).ok()?),
+                Event::Eof => return None,
+                _ => { /* Do nothing. */ }
+            }
+            buffer.clear();
+        }
+        parse_palette_css(&css)
+    }
+
+    /// Converts this transcript to a transcript with [`Captured`] outputs, e.g., in order
+    /// to re-render it with different [template options](crate::svg::TemplateOptions).
+    /// User inputs and exit statuses are retained.
+    pub fn to_captured(&self) -> Transcript {
+        let interactions = self
+            .interactions
+            .iter()
+            .map(|interaction| Interaction {
+                input: interaction.input.clone(),
+                output: interaction.output.to_captured(),
+                stderr: None,
+                exit_status: interaction.exit_status,
+                duration: interaction.duration,
+                highlighted_lines: vec![],
+                is_truncated: false,
+                folded_lines: vec![],
+                #[cfg(feature = "svg")]
+                palette: None,
+            })
+            .collect();
+        Transcript { interactions }
+    }
+
+    /// Checks that the SVG transcript from the provided `reader` is self-consistent without
+    /// running any shell. This is a cheap lint for hand-edited or externally generated snapshots.
+    ///
+    /// Besides [parsing](Self::from_svg()) the transcript (which checks, e.g., that exit status
+    /// attributes are well-formed), this checks that the displayed text of each
+    /// [folded](crate::Interaction::fold_lines()) output is consistent with the full output
+    /// retained in its `data-unfolded` attribute, which is the one used for testing.
+    /// That is, each displayed line must either be equal to the corresponding full output line,
+    /// or be a marker of the correct number of hidden lines.
+    ///
+    /// # Errors
+    ///
+    /// - Returns an error if the input cannot be parsed or is inconsistent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+    pub fn validate_svg<R: BufRead>(reader: R) -> Result<(), ParseError> {
+        Self::parse_svg(reader, &mut ErrorRecovery::validating())?;
+        Ok(())
+    }
+
+    fn parse_svg<R: BufRead>(reader: R, recovery: &mut ErrorRecovery) -> Result<Self, ParseError> {
         let mut reader = XmlReader::from_reader(reader);
         let mut buffer = vec![];
         let mut state = ParserState::Initialized;
@@ -113,7 +305,8 @@ impl Transcript<Parsed> {
                 _ => { /* Do nothing. */ }
             }
 
-            if let Some(interaction) = state.process(event)? {
+            let index = transcript.interactions.len();
+            if let Some(interaction) = state.process(event, index, recovery)? {
                 #[cfg(feature = "tracing")]
                 tracing::debug!(
                     ?interaction.input,
@@ -131,11 +324,50 @@ impl Transcript<Parsed> {
                 transcript.interactions.push(interaction);
                 Ok(transcript)
             }
+            ParserState::EncounteredSvgTag => {
+                Err(ParseError::InvalidContainer(ContainerError::NotFound))
+            }
             _ => Err(ParseError::UnexpectedEof),
         }
     }
 }
 
+/// Parses palette colors from `.fgN { color: ..; }` (default template) or `.fgN { fill: ..; }`
+/// (pure SVG template) CSS rules. Rules with more complex selectors are ignored.
+#[cfg(feature = "svg")]
+fn parse_palette_css(css: &str) -> Option<Palette> {
+    let mut colors = [None; 16];
+    for rule in css.split('}') {
+        let Some((selector, declarations)) = rule.split_once('{') else {
+            continue;
+        };
+        let index = selector.trim().strip_prefix(".fg");
+        let Some(index) = index.and_then(|index| index.parse::<usize>().ok()) else {
+            continue;
+        };
+        let Some(slot) = colors.get_mut(index) else {
+            continue;
+        };
+        if slot.is_some() {
+            continue; // only use the first rule for each color
+        }
+        *slot = declarations.split(';').find_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            if matches!(property.trim(), "color" | "fill") {
+                value.trim().parse::<RgbColor>().ok()
+            } else {
+                None
+            }
+        });
+    }
+
+    let mut indexed_colors = [RgbColor(0, 0, 0); 16];
+    for (dest, color) in indexed_colors.iter_mut().zip(colors) {
+        *dest = color?;
+    }
+    Some(Palette::from_indexed_colors(indexed_colors))
+}
+
 fn parse_classes(attributes: Attributes<'_>) -> Result<Cow<'_, [u8]>, ParseError> {
     let mut class = None;
     for attr in attributes {
@@ -166,18 +398,101 @@ fn parse_exit_status(attributes: Attributes<'_>) -> Result<Option<ExitStatus>, P
     Ok(exit_status)
 }
 
+fn parse_duration(attributes: Attributes<'_>) -> Result<Option<Duration>, ParseError> {
+    let mut duration = None;
+    for attr in attributes {
+        let attr = attr.map_err(quick_xml::Error::InvalidAttr)?;
+        if attr.key.as_ref() == b"data-duration-ms" {
+            let millis =
+                str::from_utf8(&attr.value).map_err(|err| ParseError::Xml(map_utf8_error(err)))?;
+            let millis = millis.parse().map_err(ParseError::InvalidDuration)?;
+            duration = Some(Duration::from_millis(millis));
+        }
+    }
+    Ok(duration)
+}
+
+/// Parses the full output [retained](crate::Interaction::fold_lines()) in the `data-unfolded`
+/// attribute of the output element, if any.
+fn parse_unfolded_output(attributes: Attributes<'_>) -> Result<Option<Parsed>, ParseError> {
+    for attr in attributes {
+        let attr = attr.map_err(quick_xml::Error::InvalidAttr)?;
+        if attr.key.as_ref() == b"data-unfolded" {
+            let html = attr.unescape_value()?;
+            // The HTML is a fragment of the `<pre>` element contents, so we wrap it to get
+            // a well-formed XML document.
+            let html = format!("<pre>{html}</pre>");
+            let mut reader = XmlReader::from_str(&html);
+            reader.read_event()?; // skip the opening `<pre>` tag
+            let mut text = TextReadingState::default();
+            loop {
+                let event = reader.read_event()?;
+                if matches!(event, Event::Eof) {
+                    return Err(ParseError::UnexpectedEof);
+                }
+                if let Some(parsed) = text.process(event)? {
+                    return Ok(Some(parsed));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Checks that the `displayed` text of a folded output can be obtained from the `unfolded` text
+/// by replacing runs of lines with fold markers. On failure, returns the zero-based index
+/// of the first inconsistent displayed line.
+fn check_folded_output(unfolded: &str, displayed: &str) -> Result<(), usize> {
+    let mut unfolded_lines = unfolded.split('\n');
+    let mut line_count = 0;
+    for (i, line) in displayed.split('\n').enumerate() {
+        line_count = i + 1;
+        if let Some(hidden_count) = parse_fold_marker(line) {
+            for _ in 0..hidden_count {
+                unfolded_lines.next().ok_or(i)?;
+            }
+        } else if unfolded_lines.next() != Some(line) {
+            return Err(i);
+        }
+    }
+    if unfolded_lines.next().is_some() {
+        return Err(line_count);
+    }
+    Ok(())
+}
+
+/// Parses the number of hidden lines from a fold marker line, e.g. `… 3 lines hidden …`.
+fn parse_fold_marker(line: &str) -> Option<usize> {
+    let line = line.strip_prefix("… ")?.strip_suffix(" hidden …")?;
+    let (count, noun) = line.split_once(' ')?;
+    let count = count.parse().ok()?;
+    let expected_noun = if count == 1 { "line" } else { "lines" };
+    (noun == expected_noun).then_some(count)
+}
+
 /// Errors that can occur during parsing SVG transcripts.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum ParseError {
     /// Unexpected root XML tag; must be `<svg>`.
     UnexpectedRoot(String),
-    /// Invalid transcript container.
-    InvalidContainer,
+    /// Invalid or missing transcript container. This usually means that the SVG was not produced
+    /// by this crate, or was produced by an incompatible template.
+    InvalidContainer(ContainerError),
     /// Invalid recorded exit status of an executed command.
     InvalidExitStatus(ParseIntError),
+    /// Invalid recorded duration of an executed command.
+    InvalidDuration(ParseIntError),
     /// Unexpected end of file.
     UnexpectedEof,
+    /// Displayed text of a folded terminal output is inconsistent with the full output retained
+    /// in the `data-unfolded` attribute. Only produced by [`Transcript::validate_svg()`].
+    InconsistentFoldedOutput {
+        /// Zero-based index of the offending interaction.
+        interaction: usize,
+        /// Zero-based index of the first inconsistent displayed line.
+        line: usize,
+    },
     /// Error parsing XML.
     Xml(quick_xml::Error),
 }
@@ -201,41 +516,161 @@ impl fmt::Display for ParseError {
                 formatter,
                 "unexpected root XML tag: <{tag_name}>; expected <svg>"
             ),
-            Self::InvalidContainer => formatter.write_str("invalid transcript container"),
+            Self::InvalidContainer(err) => write!(formatter, "invalid transcript container: {err}"),
             Self::InvalidExitStatus(err) => write!(formatter, "invalid exit status: {err}"),
+            Self::InvalidDuration(err) => write!(formatter, "invalid duration: {err}"),
             Self::UnexpectedEof => formatter.write_str("unexpected EOF"),
+            Self::InconsistentFoldedOutput { interaction, line } => write!(
+                formatter,
+                "displayed line #{} of output #{} is inconsistent with the full output",
+                line + 1,
+                interaction + 1
+            ),
             Self::Xml(err) => write!(formatter, "error parsing XML: {err}"),
         }
     }
 }
 
+/// Details of a [`ParseError::InvalidContainer`] error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ContainerError {
+    /// The container `<div>` was not found.
+    NotFound,
+    /// The container has no `xmlns` attribute.
+    MissingNamespace,
+    /// The `xmlns` attribute of the container has an unexpected value (enclosed).
+    InvalidNamespace(String),
+    /// The container has no `class` attribute.
+    MissingClass,
+    /// The `class` attribute of the container has an unexpected value (enclosed).
+    InvalidClass(String),
+}
+
+impl ContainerError {
+    const HTML_NS: &'static str = "http://www.w3.org/1999/xhtml";
+    const CLASS: &'static str = "container";
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(
+                formatter,
+                "container <div class=\"{}\"> not found; is the SVG produced by term-transcript?",
+                Self::CLASS
+            ),
+            Self::MissingNamespace => write!(
+                formatter,
+                "missing `xmlns` attribute; expected `{}`",
+                Self::HTML_NS
+            ),
+            Self::InvalidNamespace(ns) => write!(
+                formatter,
+                "unexpected `xmlns` attribute `{ns}`; expected `{}`",
+                Self::HTML_NS
+            ),
+            Self::MissingClass => write!(
+                formatter,
+                "missing `class` attribute; expected `{}`",
+                Self::CLASS
+            ),
+            Self::InvalidClass(class) => write!(
+                formatter,
+                "unexpected `class` attribute `{class}`; expected `{}`",
+                Self::CLASS
+            ),
+        }
+    }
+}
+
 impl StdError for ParseError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             Self::Xml(err) => Some(err),
-            Self::InvalidExitStatus(err) => Some(err),
+            Self::InvalidExitStatus(err) | Self::InvalidDuration(err) => Some(err),
             _ => None,
         }
     }
 }
 
+/// Policy for handling non-fatal parsing errors.
+#[derive(Debug)]
+struct ErrorRecovery {
+    is_enabled: bool,
+    checks_folded_outputs: bool,
+    errors: Vec<ParseError>,
+}
+
+impl ErrorRecovery {
+    fn strict() -> Self {
+        Self {
+            is_enabled: false,
+            checks_folded_outputs: false,
+            errors: vec![],
+        }
+    }
+
+    fn collecting() -> Self {
+        Self {
+            is_enabled: true,
+            checks_folded_outputs: false,
+            errors: vec![],
+        }
+    }
+
+    /// Strict mode with additional consistency checks.
+    fn validating() -> Self {
+        Self {
+            checks_folded_outputs: true,
+            ..Self::strict()
+        }
+    }
+
+    /// Handles a non-fatal error by either propagating it (in the strict mode), or recording it
+    /// and substituting the `fallback` value.
+    fn recover<T>(&mut self, result: Result<T, ParseError>, fallback: T) -> Result<T, ParseError> {
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) if self.is_enabled => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(%err, "recovered from parsing error");
+                self.errors.push(err);
+                Ok(fallback)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct UserInputState {
     exit_status: Option<ExitStatus>,
+    duration: Option<Duration>,
     is_hidden: bool,
     text: TextReadingState,
     prompt: Option<Cow<'static, str>>,
     prompt_open_tags: Option<usize>,
+    continuation_prompt: Option<Cow<'static, str>>,
+    /// Number of open tags and the start position in the plaintext buffer
+    /// for the continuation prompt being read.
+    continuation_prompt_start: Option<(usize, usize)>,
+    /// Position in the plaintext buffer of the space following a continuation prompt.
+    continuation_space_pos: Option<usize>,
 }
 
 impl UserInputState {
-    fn new(exit_status: Option<ExitStatus>, is_hidden: bool) -> Self {
+    fn new(exit_status: Option<ExitStatus>, duration: Option<Duration>, is_hidden: bool) -> Self {
         Self {
             exit_status,
+            duration,
             is_hidden,
             text: TextReadingState::default(),
             prompt: None,
             prompt_open_tags: None,
+            continuation_prompt: None,
+            continuation_prompt_start: None,
+            continuation_space_pos: None,
         }
     }
 }
@@ -255,18 +690,46 @@ impl UserInputState {
 
     fn process(&mut self, event: Event<'_>) -> Result<Option<Interaction<Parsed>>, ParseError> {
         let mut is_prompt_end = false;
+        let mut is_continuation_prompt_end = false;
         if let Event::Start(tag) = &event {
             if self.can_start_prompt() && parse_classes(tag.attributes())?.as_ref() == b"prompt" {
                 // Got prompt start.
                 self.prompt_open_tags = Some(self.text.open_tags());
+            } else if self.prompt.is_some()
+                && self.continuation_prompt_start.is_none()
+                && self.text.plaintext_buffer.ends_with('\n')
+                && parse_classes(tag.attributes())?.as_ref() == b"prompt"
+            {
+                // Got continuation prompt start (continuation prompts are only rendered
+                // at the start of a line).
+                let start = self.text.plaintext_buffer.len();
+                self.continuation_prompt_start = Some((self.text.open_tags(), start));
             }
         } else if let Event::End(_) = &event {
             if self.can_end_prompt() {
                 is_prompt_end = true;
             }
+            is_continuation_prompt_end = self
+                .continuation_prompt_start
+                .is_some_and(|(tags, _)| tags + 1 == self.text.open_tags());
         }
 
         let maybe_parsed = self.text.process(event)?;
+        if is_continuation_prompt_end {
+            let (_, start) = self.continuation_prompt_start.take().unwrap();
+            let prompt = self.text.plaintext_buffer.split_off(start);
+            self.continuation_prompt = Some(UserInput::intern_prompt(prompt));
+            self.continuation_space_pos = Some(start);
+        } else if let Some(pos) = self.continuation_space_pos {
+            // Remove the space separating the continuation prompt from the text.
+            let buffer = &mut self.text.plaintext_buffer;
+            if buffer.len() > pos {
+                if buffer[pos..].starts_with(' ') {
+                    buffer.remove(pos);
+                }
+                self.continuation_space_pos = None;
+            }
+        }
         if is_prompt_end {
             if let Some(parsed) = maybe_parsed {
                 // Special case: user input consists of the prompt only.
@@ -274,11 +737,21 @@ impl UserInputState {
                     text: String::new(),
                     prompt: Some(UserInput::intern_prompt(parsed.plaintext)),
                     hidden: self.is_hidden,
+                    interrupt: false,
+                    stdin_data: None,
+                    continuation_prompt: None,
                 };
                 return Ok(Some(Interaction {
                     input,
                     output: Parsed::default(),
+                    stderr: None,
                     exit_status: self.exit_status,
+                    duration: self.duration,
+                    highlighted_lines: vec![],
+                    is_truncated: false,
+                    folded_lines: vec![],
+                    #[cfg(feature = "svg")]
+                    palette: None,
                 }));
             }
             let text = mem::take(&mut self.text.plaintext_buffer);
@@ -286,15 +759,29 @@ impl UserInputState {
         }
 
         Ok(maybe_parsed.map(|parsed| {
+            let text = parsed.into_input_text();
+            let prompt = self.prompt.take();
+            // Interrupts are rendered as `^C` without a prompt.
+            let interrupt = prompt.is_none() && text == UserInput::INTERRUPT_TEXT;
             let input = UserInput {
-                text: parsed.into_input_text(),
-                prompt: self.prompt.take(),
+                text,
+                prompt,
                 hidden: self.is_hidden,
+                interrupt,
+                stdin_data: None,
+                continuation_prompt: self.continuation_prompt.take(),
             };
             Interaction {
                 input,
                 output: Parsed::default(),
+                stderr: None,
                 exit_status: self.exit_status,
+                duration: self.duration,
+                highlighted_lines: vec![],
+                is_truncated: false,
+                folded_lines: vec![],
+                #[cfg(feature = "svg")]
+                palette: None,
             }
         }))
     }
@@ -313,8 +800,9 @@ enum ParserState {
     ReadingUserInput(UserInputState),
     /// Finished reading user input; searching for `<div class="output">`.
     EncounteredUserInput(Interaction<Parsed>),
-    /// Reading terminal output (`<div class="output">` contents).
-    ReadingTermOutput(Interaction<Parsed>, TextReadingState),
+    /// Reading terminal output (`<div class="output">` contents). The last field contains
+    /// the full output if some output lines are folded.
+    ReadingTermOutput(Interaction<Parsed>, TextReadingState, Option<Box<Parsed>>),
 }
 
 impl ParserState {
@@ -323,9 +811,19 @@ impl ParserState {
             text: String::new(),
             prompt: None,
             hidden: false,
+            interrupt: false,
+            stdin_data: None,
+            continuation_prompt: None,
         },
         output: Parsed::DEFAULT,
+        stderr: None,
         exit_status: None,
+        duration: None,
+        highlighted_lines: Vec::new(),
+        is_truncated: false,
+        folded_lines: Vec::new(),
+        #[cfg(feature = "svg")]
+        palette: None,
     };
 
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
@@ -333,8 +831,16 @@ impl ParserState {
         *self = new_state;
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", err))]
-    fn process(&mut self, event: Event<'_>) -> Result<Option<Interaction<Parsed>>, ParseError> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(recovery), err)
+    )]
+    fn process(
+        &mut self,
+        event: Event<'_>,
+        interaction_index: usize,
+        recovery: &mut ErrorRecovery,
+    ) -> Result<Option<Interaction<Parsed>>, ParseError> {
         match self {
             Self::Initialized => {
                 if let Event::Start(tag) = event {
@@ -350,7 +856,7 @@ impl ParserState {
             Self::EncounteredSvgTag => {
                 if let Event::Start(tag) = event {
                     if tag.name().as_ref() == b"div" {
-                        Self::verify_container_attrs(tag.attributes())?;
+                        recovery.recover(Self::verify_container_attrs(tag.attributes()), ())?;
                         self.set_state(Self::EncounteredContainer);
                     }
                 }
@@ -358,14 +864,18 @@ impl ParserState {
 
             Self::EncounteredContainer => {
                 if let Event::Start(tag) = event {
-                    let classes = parse_classes(tag.attributes())?;
+                    let classes =
+                        recovery.recover(parse_classes(tag.attributes()), Cow::Borrowed(b""))?;
                     if Self::is_input_class(extract_base_class(&classes)) {
                         let is_hidden = classes
                             .split(|byte| *byte == b' ')
                             .any(|chunk| chunk == b"input-hidden");
-                        let exit_status = parse_exit_status(tag.attributes())?;
+                        let exit_status =
+                            recovery.recover(parse_exit_status(tag.attributes()), None)?;
+                        let duration = recovery.recover(parse_duration(tag.attributes()), None)?;
                         self.set_state(Self::ReadingUserInput(UserInputState::new(
                             exit_status,
+                            duration,
                             is_hidden,
                         )));
                     }
@@ -380,23 +890,30 @@ impl ParserState {
 
             Self::EncounteredUserInput(interaction) => {
                 if let Event::Start(tag) = event {
-                    let classes = parse_classes(tag.attributes())?;
+                    let classes =
+                        recovery.recover(parse_classes(tag.attributes()), Cow::Borrowed(b""))?;
                     let base_class = extract_base_class(&classes);
 
                     if Self::is_output_class(base_class) {
                         let interaction = mem::replace(interaction, Self::DUMMY_INTERACTION);
+                        let unfolded =
+                            recovery.recover(parse_unfolded_output(tag.attributes()), None)?;
                         self.set_state(Self::ReadingTermOutput(
                             interaction,
                             TextReadingState::default(),
+                            unfolded.map(Box::new),
                         ));
                     } else if Self::is_input_class(base_class) {
                         let interaction = mem::replace(interaction, Self::DUMMY_INTERACTION);
-                        let exit_status = parse_exit_status(tag.attributes())?;
+                        let exit_status =
+                            recovery.recover(parse_exit_status(tag.attributes()), None)?;
+                        let duration = recovery.recover(parse_duration(tag.attributes()), None)?;
                         let is_hidden = classes
                             .split(|byte| *byte == b' ')
                             .any(|chunk| chunk == b"input-hidden");
                         self.set_state(Self::ReadingUserInput(UserInputState::new(
                             exit_status,
+                            duration,
                             is_hidden,
                         )));
                         return Ok(Some(interaction));
@@ -404,10 +921,20 @@ impl ParserState {
                 }
             }
 
-            Self::ReadingTermOutput(interaction, text_state) => {
+            Self::ReadingTermOutput(interaction, text_state, unfolded) => {
                 if let Some(term_output) = text_state.process(event)? {
                     let mut interaction = mem::replace(interaction, Self::DUMMY_INTERACTION);
-                    interaction.output = term_output;
+                    if let Some(unfolded) =
+                        unfolded.as_ref().filter(|_| recovery.checks_folded_outputs)
+                    {
+                        check_folded_output(&unfolded.plaintext, &term_output.plaintext).map_err(
+                            |line| ParseError::InconsistentFoldedOutput {
+                                interaction: interaction_index,
+                                line,
+                            },
+                        )?;
+                    }
+                    interaction.output = unfolded.take().map_or(term_output, |unfolded| *unfolded);
                     self.set_state(Self::EncounteredContainer);
                     return Ok(Some(interaction));
                 }
@@ -429,23 +956,24 @@ impl ParserState {
         tracing::instrument(level = "debug", skip_all, err)
     )]
     fn verify_container_attrs(attributes: Attributes<'_>) -> Result<(), ParseError> {
-        const HTML_NS: &[u8] = b"http://www.w3.org/1999/xhtml";
-
         let mut has_ns_attribute = false;
         let mut has_class_attribute = false;
 
         for attr in attributes {
             let attr = attr.map_err(quick_xml::Error::InvalidAttr)?;
+            let value = || String::from_utf8_lossy(&attr.value).into_owned();
             match attr.key.as_ref() {
                 b"xmlns" => {
-                    if attr.value.as_ref() != HTML_NS {
-                        return Err(ParseError::InvalidContainer);
+                    if attr.value.as_ref() != ContainerError::HTML_NS.as_bytes() {
+                        let err = ContainerError::InvalidNamespace(value());
+                        return Err(ParseError::InvalidContainer(err));
                     }
                     has_ns_attribute = true;
                 }
                 b"class" => {
-                    if attr.value.as_ref() != b"container" {
-                        return Err(ParseError::InvalidContainer);
+                    if attr.value.as_ref() != ContainerError::CLASS.as_bytes() {
+                        let err = ContainerError::InvalidClass(value());
+                        return Err(ParseError::InvalidContainer(err));
                     }
                     has_class_attribute = true;
                 }
@@ -453,10 +981,13 @@ impl ParserState {
             }
         }
 
-        if has_ns_attribute && has_class_attribute {
-            Ok(())
+        let err = if !has_ns_attribute {
+            ContainerError::MissingNamespace
+        } else if !has_class_attribute {
+            ContainerError::MissingClass
         } else {
-            Err(ParseError::InvalidContainer)
-        }
+            return Ok(());
+        };
+        Err(ParseError::InvalidContainer(err))
     }
 }
@@ -62,6 +62,94 @@ fn reading_file(file_contents: &[u8]) {
     assert_eq!(color_spans.len(), 5); // 2 colored regions + 3 surrounding areas
 }
 
+#[test_casing(2, [SVG, LEGACY_SVG])]
+fn validating_file(file_contents: &[u8]) {
+    Transcript::validate_svg(file_contents).unwrap();
+}
+
+#[test]
+fn checking_folded_output() {
+    let unfolded = "1\n2\n3\n4";
+    check_folded_output(unfolded, unfolded).unwrap();
+    check_folded_output(unfolded, "1\n… 2 lines hidden …\n4").unwrap();
+    check_folded_output(unfolded, "… 1 line hidden …\n2\n… 2 lines hidden …").unwrap();
+
+    assert_eq!(
+        check_folded_output(unfolded, "1\n… 2 lines hidden …\n5"),
+        Err(2)
+    );
+    assert_eq!(
+        check_folded_output(unfolded, "1\n… 3 lines hidden …\n4"),
+        Err(2)
+    );
+    assert_eq!(
+        check_folded_output(unfolded, "1\n… 1 line hidden …\n4"),
+        Err(2)
+    );
+    assert_eq!(
+        check_folded_output(unfolded, "1\n… 1 lines hidden …"),
+        Err(1)
+    );
+    assert_eq!(
+        check_folded_output(unfolded, "1\n… 1 line hidden …"),
+        Err(2)
+    );
+}
+
+#[test]
+fn converting_parsed_transcript_to_captured() {
+    let parsed = Transcript::from_svg(SVG).unwrap();
+    let captured = Transcript::concat([parsed.to_captured(), parsed.to_captured()]);
+    assert_eq!(captured.interactions().len(), 2);
+
+    for interaction in captured.interactions() {
+        assert_eq!(interaction.input().as_ref(), "ls -al --color=always");
+        let output = interaction.output();
+        assert_eq!(
+            output.to_plaintext().unwrap(),
+            parsed.interactions[0].output.plaintext
+        );
+        assert!(output.as_ref().contains("\u{1b}[34m."), "{output:?}");
+    }
+}
+
+#[test]
+fn truncating_transcript() {
+    let parsed = Transcript::from_svg(SVG).unwrap();
+    let mut transcript = Transcript::concat([parsed.clone(), parsed]);
+    transcript.interactions_mut()[1].input.hidden = true;
+    transcript.truncate(5);
+    assert_eq!(transcript.interactions().len(), 2);
+
+    transcript.truncate(1);
+    assert_eq!(transcript.interactions().len(), 1);
+    assert!(!transcript.interactions()[0].input().hidden);
+}
+
+#[test]
+fn diffing_parsed_output() {
+    let parsed = Transcript::from_svg(SVG).unwrap();
+    let parsed_output = &parsed.interactions[0].output;
+    let captured = parsed_output.to_captured();
+    assert!(parsed_output.diff(&captured).unwrap().is_none());
+
+    let recolored = captured.as_ref().replace("\u{1b}[34m", "\u{1b}[35m");
+    let diff = parsed_output.diff(&Captured::from(recolored)).unwrap();
+    let diff = assert_matches!(diff, Some(OutputDiff::Styles(diff)) => diff);
+    assert_eq!(diff.differing_ranges().count(), 2);
+    let diff_string = diff.to_string();
+    assert!(diff_string.contains("Expected style"), "{diff_string}");
+
+    let captured = Captured::from("total 0".to_owned());
+    let diff = parsed_output.diff(&captured).unwrap();
+    let (expected, actual) = assert_matches!(
+        diff,
+        Some(OutputDiff::Text { expected, actual }) => (expected, actual)
+    );
+    assert_eq!(expected, parsed_output.plaintext);
+    assert_eq!(actual, "total 0");
+}
+
 #[test]
 fn reading_file_with_extra_info() {
     let mut data = SVG.to_owned();
@@ -158,6 +246,48 @@ fn invalid_exit_code_info() {
 
     let err = Transcript::from_svg(SVG).unwrap_err();
     assert_matches!(err, ParseError::InvalidExitStatus(_));
+    let err = Transcript::validate_svg(SVG).unwrap_err();
+    assert_matches!(err, ParseError::InvalidExitStatus(_));
+}
+
+#[test]
+fn collecting_recoverable_errors() {
+    const SVG: &[u8] = br#"
+        <svg viewBox="0 0 652 344" xmlns="http://www.w3.org/2000/svg">
+          <foreignObject x="0" y="0" width="652" height="344">
+            <div xmlns="http://www.w3.org/2000/svg" class="container">
+              <div class="input input-failure" data-exit-status="??"><pre><span class="prompt">$</span> what</pre></div>
+              <div class="input" data-exit-status="-"><pre><span class="prompt">$</span> echo</pre></div>
+            </div>
+          </foreignObject>
+        </svg>
+    "#;
+
+    let (transcript, errors) = Transcript::from_svg_collecting(SVG);
+    let transcript = transcript.unwrap();
+    assert_eq!(transcript.interactions.len(), 2);
+    assert_eq!(transcript.interactions[0].input.text, "what");
+    assert_eq!(transcript.interactions[0].exit_status, None);
+    assert_eq!(transcript.interactions[1].input.text, "echo");
+
+    assert_eq!(errors.len(), 3, "{errors:?}");
+    assert_matches!(
+        &errors[0],
+        ParseError::InvalidContainer(ContainerError::InvalidNamespace(ns))
+            if ns == "http://www.w3.org/2000/svg"
+    );
+    assert_matches!(errors[1], ParseError::InvalidExitStatus(_));
+    assert_matches!(errors[2], ParseError::InvalidExitStatus(_));
+}
+
+#[test]
+fn collecting_errors_with_fatal_error() {
+    let data: &[u8] = b"<div>Text</div>";
+    let (transcript, errors) = Transcript::from_svg_collecting(data);
+
+    assert!(transcript.is_none());
+    assert_eq!(errors.len(), 1);
+    assert_matches!(&errors[0], ParseError::UnexpectedRoot(tag) if tag == "div");
 }
 
 #[test]
@@ -177,23 +307,37 @@ fn reading_file_without_container() {
     "#;
     let err = Transcript::from_svg(bogus_data).unwrap_err();
 
-    assert_matches!(err, ParseError::UnexpectedEof);
+    assert_matches!(err, ParseError::InvalidContainer(ContainerError::NotFound));
+    let err = err.to_string();
+    assert!(
+        err.contains("is the SVG produced by term-transcript?"),
+        "{err}"
+    );
 }
 
-const INVALID_ATTRS: [&str; 5] = [
-    "",
+const INVALID_ATTRS: [(&str, &str); 5] = [
+    ("", "missing `xmlns` attribute"),
     // no class
-    r#"xmlns="http://www.w3.org/1999/xhtml""#,
+    (
+        r#"xmlns="http://www.w3.org/1999/xhtml""#,
+        "missing `class` attribute",
+    ),
     // no namespace
-    r#"class="container""#,
+    (r#"class="container""#, "missing `xmlns` attribute"),
     // invalid namespace
-    r#"xmlns="http://www.w3.org/2000/svg" class="container""#,
+    (
+        r#"xmlns="http://www.w3.org/2000/svg" class="container""#,
+        "unexpected `xmlns` attribute `http://www.w3.org/2000/svg`",
+    ),
     // invalid class
-    r#"xmlns="http://www.w3.org/1999/xhtml" class="cont""#,
+    (
+        r#"xmlns="http://www.w3.org/1999/xhtml" class="cont""#,
+        "unexpected `class` attribute `cont`",
+    ),
 ];
 
 #[test_casing(5, INVALID_ATTRS)]
-fn reading_file_with_invalid_container(attrs: &str) {
+fn reading_file_with_invalid_container(attrs: &str, expected_message: &str) {
     let bogus_data = format!(
         r#"
         <svg viewBox="0 0 652 344" xmlns="http://www.w3.org/2000/svg" version="1.1">
@@ -205,12 +349,15 @@ fn reading_file_with_invalid_container(attrs: &str) {
     );
     let err = Transcript::from_svg(bogus_data.as_bytes()).unwrap_err();
 
-    assert_matches!(err, ParseError::InvalidContainer);
+    assert_matches!(err, ParseError::InvalidContainer(_));
+    let err = err.to_string();
+    assert!(err.starts_with("invalid transcript container: "), "{err}");
+    assert!(err.contains(expected_message), "{err}");
 }
 
 #[test]
 fn reading_user_input_with_manual_events() {
-    let mut state = UserInputState::new(None, false);
+    let mut state = UserInputState::new(None, None, false);
     {
         let event = Event::Start(BytesStart::new("pre"));
         assert!(state.process(event).unwrap().is_none());
@@ -255,7 +402,7 @@ fn read_user_input(input: &[u8]) -> UserInput {
     wrapped_input.extend_from_slice(b"</div>");
 
     let mut reader = XmlReader::from_reader(wrapped_input.as_slice());
-    let mut state = UserInputState::new(None, false);
+    let mut state = UserInputState::new(None, None, false);
 
     // Skip the `<div>` start event.
     while !matches!(reader.read_event().unwrap(), Event::Start(_)) {
@@ -298,6 +445,16 @@ fn reading_user_input_without_prompt() {
     assert_eq!(user_input.text, "echo foo");
 }
 
+#[test]
+fn reading_interrupt_user_input() {
+    let user_input = read_user_input(b"<pre>^C</pre>");
+    assert!(user_input.is_interrupt());
+    assert_eq!(user_input, UserInput::interrupt());
+
+    let user_input = read_user_input(br#"<pre><span class="prompt">$</span> ^C</pre>"#);
+    assert!(!user_input.is_interrupt());
+}
+
 #[test]
 fn reading_user_input_with_prompt_only() {
     let user_input = read_user_input(br#"<pre><span class="prompt">$</span></pre>"#);
@@ -1,10 +1,16 @@
+use std::time::Duration;
+
+use assert_matches::assert_matches;
 use termcolor::NoColor;
 use test_casing::test_casing;
 
-use super::{color_diff::ColorSpan, *};
+use super::{
+    color_diff::{ColorSpan, Normalization},
+    *,
+};
 use crate::{
-    svg::{Template, TemplateOptions},
-    Captured, Interaction, Transcript, UserInput,
+    svg::{NamedPalette, RgbColor, Template, TemplateOptions},
+    Captured, ExitStatus, Interaction, Transcript, UserInput,
 };
 
 #[test_casing(2, [MatchKind::TextOnly, MatchKind::Precise])]
@@ -23,6 +29,178 @@ fn snapshot_testing(match_kind: MatchKind) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn durations_roundtrip_via_svg() -> anyhow::Result<()> {
+    let mut transcript = Transcript::new();
+    let interaction = Interaction::new(UserInput::command("test"), "Hello!")
+        .with_duration(Duration::from_millis(1_234));
+    transcript.add_existing_interaction(interaction);
+    transcript.add_interaction(UserInput::command("other"), "Hi!");
+
+    let options = TemplateOptions {
+        show_timings: true,
+        ..TemplateOptions::default()
+    };
+    let mut svg_buffer = vec![];
+    Template::new(options).render(&transcript, &mut svg_buffer)?;
+    let svg = String::from_utf8(svg_buffer)?;
+    assert!(svg.contains(r#"data-duration-ms="1234""#), "{svg}");
+    assert!(svg.contains(r#"<div class="timing">(1.2s)</div>"#), "{svg}");
+
+    let parsed = Transcript::from_svg(svg.as_bytes())?;
+    let durations: Vec<_> = parsed
+        .interactions()
+        .iter()
+        .map(Interaction::duration)
+        .collect();
+    assert_eq!(durations, [Some(Duration::from_millis(1_234)), None]);
+    assert_eq!(parsed.interactions()[0].input().as_ref(), "test");
+    assert_eq!(parsed.interactions()[0].output().plaintext(), "Hello!");
+    Ok(())
+}
+
+#[test]
+fn grid_layout_roundtrip_via_svg() -> anyhow::Result<()> {
+    let mut transcript = Transcript::new();
+    let output = "┌───┐\n│ \u{1b}[1mA\u{1b}[0m │\n└───┘";
+    transcript.add_interaction(UserInput::command("table"), output);
+
+    let options = TemplateOptions {
+        grid_layout: true,
+        ..TemplateOptions::default()
+    };
+    let mut svg_buffer = vec![];
+    Template::new(options).render(&transcript, &mut svg_buffer)?;
+
+    let parsed = Transcript::from_svg(svg_buffer.as_slice())?;
+    let parsed_output = parsed.interactions()[0].output();
+    assert_eq!(parsed_output.plaintext(), "┌───┐\n│ A │\n└───┘");
+    let diff = parsed_output.diff(&Captured::from(output.to_owned()))?;
+    assert!(diff.is_none(), "{diff:?}");
+    Ok(())
+}
+
+#[test]
+fn reading_palette_from_svg() -> anyhow::Result<()> {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "\u{1b}[31mHello\u{1b}[0m");
+
+    for palette in [NamedPalette::Dracula, NamedPalette::Xterm] {
+        let options = TemplateOptions {
+            palette: palette.into(),
+            ..TemplateOptions::default()
+        };
+        for template in [Template::new(options.clone()), Template::pure_svg(options)] {
+            let mut svg_buffer = vec![];
+            template.render(&transcript, &mut svg_buffer)?;
+            let parsed = Transcript::palette_from_svg(svg_buffer.as_slice());
+            assert_eq!(parsed, Some(palette.into()));
+        }
+    }
+
+    let svg =
+        br#"<svg xmlns="http://www.w3.org/2000/svg"><style>.fg0 { fill: #000; }</style></svg>"#;
+    assert_eq!(Transcript::palette_from_svg(svg.as_slice()), None);
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzipped_snapshot_roundtrip() -> anyhow::Result<()> {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(
+        UserInput::command("test"),
+        "Hello, \u{1b}[32mworld\u{1b}[0m!",
+    );
+
+    let mut compressed = vec![];
+    Template::new(TemplateOptions::default()).render_gz(&transcript, &mut compressed)?;
+    assert_eq!(compressed[..2], [0x1f, 0x8b]); // gzip magic bytes
+
+    let parsed = Transcript::from_svg_gz(compressed.as_slice())?;
+    assert_eq!(parsed.interactions().len(), 1);
+    assert_eq!(parsed.interactions()[0].input().as_ref(), "test");
+    assert_eq!(
+        parsed.interactions()[0].output().plaintext(),
+        "Hello, world!"
+    );
+
+    // Uncompressed input should be rejected.
+    assert!(Transcript::from_svg_gz(b"<svg></svg>".as_slice()).is_err());
+    Ok(())
+}
+
+#[test]
+fn folded_snapshot_roundtrip() -> anyhow::Result<()> {
+    let output = "1\n\u{1b}[32m2 & 3\n4\u{1b}[0m\n5";
+    let interaction = Interaction::new(UserInput::command("test"), output).fold_lines(1..3);
+    let mut transcript = Transcript::new();
+    transcript.add_existing_interaction(interaction);
+
+    let svg = Template::new(TemplateOptions::default()).render_to_string(&transcript)?;
+    assert!(svg.contains("… 2 lines hidden …"), "{svg}");
+    assert!(svg.contains("data-unfolded="), "{svg}");
+
+    let parsed = Transcript::from_svg(svg.as_bytes())?;
+    let parsed_output = parsed.interactions()[0].output();
+    assert_eq!(parsed_output.plaintext(), "1\n2 & 3\n4\n5");
+    let captured = transcript.interactions()[0].output();
+    assert!(parsed_output.diff(captured)?.is_none());
+    Transcript::validate_svg(svg.as_bytes())?;
+
+    let tampered_svg = svg.replace("… 2 lines hidden …", "… 3 lines hidden …");
+    Transcript::from_svg(tampered_svg.as_bytes())?;
+    let err = Transcript::validate_svg(tampered_svg.as_bytes()).unwrap_err();
+    assert_matches!(
+        err,
+        ParseError::InconsistentFoldedOutput {
+            interaction: 0,
+            line: 2
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn multiline_input_roundtrip() -> anyhow::Result<()> {
+    let input = UserInput::multiline(["echo \\", "  hello \\", "world"]);
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(input.clone(), "hello world");
+
+    let svg = Template::new(TemplateOptions::default()).render_to_string(&transcript)?;
+    let parsed = Transcript::from_svg(svg.as_bytes())?;
+    assert_eq!(*parsed.interactions()[0].input(), input);
+    Ok(())
+}
+
+#[test]
+fn snapshot_with_grayscale_ramp_roundtrip() -> anyhow::Result<()> {
+    let ramp = [RgbColor(0x10, 0x20, 0x30); 24];
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("test"), "\u{1b}[38;5;240mgray\u{1b}[0m");
+
+    let options = TemplateOptions {
+        grayscale_ramp: Some(ramp),
+        ..TemplateOptions::default()
+    };
+    let svg = Template::new(options).render_to_string(&transcript)?;
+    assert!(svg.contains("#102030"), "{svg}");
+
+    let parsed = Transcript::from_svg(svg.as_bytes())?;
+    let parsed_output = parsed.interactions()[0].output();
+    let captured = transcript.interactions()[0].output();
+    assert!(parsed_output.diff(captured)?.is_some());
+
+    let normalization = Normalization {
+        grayscale_ramp: Some(&ramp),
+        ..Normalization::default()
+    };
+    assert!(parsed_output
+        .diff_inner(captured, &normalization, true)?
+        .is_none());
+    Ok(())
+}
+
 fn test_negative_snapshot_testing(
     out: &mut Vec<u8>,
     test_config: &mut TestConfig,
@@ -42,6 +220,30 @@ fn test_negative_snapshot_testing(
     Ok(())
 }
 
+#[cfg(unix)]
+#[test]
+fn snapshot_testing_with_working_dir() -> anyhow::Result<()> {
+    let mut transcript = Transcript::new();
+    transcript.add_interaction(UserInput::command("ls lib.rs"), "lib.rs");
+    let mut svg_buffer = vec![];
+    Template::new(TemplateOptions::default()).render(&transcript, &mut svg_buffer)?;
+    let parsed = Transcript::from_svg(svg_buffer.as_slice())?;
+
+    let src_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut test_config = TestConfig::new(ShellOptions::default())
+        .with_output(TestOutputConfig::Quiet)
+        .with_working_dir(src_dir);
+    let (stats, _) = test_config.test_transcript_for_stats(&parsed)?;
+    assert_eq!(stats.errors(MatchKind::TextOnly), 0);
+
+    let mut test_config = TestConfig::new(ShellOptions::default())
+        .with_output(TestOutputConfig::Quiet)
+        .with_working_dir(env!("CARGO_MANIFEST_DIR"));
+    let (stats, _) = test_config.test_transcript_for_stats(&parsed)?;
+    assert_eq!(stats.errors(MatchKind::TextOnly), 1);
+    Ok(())
+}
+
 #[test]
 fn negative_snapshot_testing_with_default_output() {
     let mut out = vec![];
@@ -74,25 +276,43 @@ fn negative_snapshot_testing_with_verbose_output() {
 }
 
 fn diff_snapshot_with_color(expected_capture: &str, actual_capture: &str) -> (TestStats, String) {
+    let config = TestConfig::new(ShellOptions::default()).with_match_kind(MatchKind::Precise);
+    diff_snapshot_with_config(expected_capture, actual_capture, &config)
+}
+
+fn parsed_transcript(expected_capture: &str) -> Transcript<Parsed> {
     let expected_capture = Captured::from(expected_capture.to_owned());
-    let parsed = Transcript {
+    Transcript {
         interactions: vec![Interaction {
             input: UserInput::command("test"),
             output: Parsed {
                 plaintext: expected_capture.to_plaintext().unwrap(),
-                color_spans: ColorSpan::parse(expected_capture.as_ref()).unwrap(),
+                color_spans: ColorSpan::parse(expected_capture.as_ref(), None).unwrap(),
                 html: expected_capture.to_html().unwrap(),
             },
+            stderr: None,
             exit_status: None,
+            duration: None,
+            highlighted_lines: vec![],
+            is_truncated: false,
+            folded_lines: vec![],
+            #[cfg(feature = "svg")]
+            palette: None,
         }],
-    };
+    }
+}
 
+fn diff_snapshot_with_config(
+    expected_capture: &str,
+    actual_capture: &str,
+    config: &TestConfig,
+) -> (TestStats, String) {
+    let parsed = parsed_transcript(expected_capture);
     let mut reproduced = Transcript::new();
     reproduced.add_interaction(UserInput::command("test"), actual_capture);
 
     let mut out: Vec<u8> = vec![];
-    let stats = TestConfig::new(ShellOptions::default())
-        .with_match_kind(MatchKind::Precise)
+    let stats = config
         .compare_transcripts(&mut NoColor::new(&mut out), &parsed, &reproduced)
         .unwrap();
     (stats, String::from_utf8(out).unwrap())
@@ -131,3 +351,160 @@ fn text_match_for_snapshot_testing_with_color_diff() {
     assert!(out.contains("[#] Input: test"), "{out}");
     assert!(out.contains("13..14 ----   yellow/(none)   ----     blue/(none)"));
 }
+
+#[test]
+fn snapshot_testing_with_required_no_color() {
+    let config = TestConfig::new(ShellOptions::default()).require_no_color();
+    let (stats, out) = diff_snapshot_with_config("Apr 18 12:54 .", "Apr 18 12:54 .", &config);
+    assert_eq!(stats.matches(), [Some(MatchKind::TextOnly)]);
+    assert!(out.contains("[+] Input: test"), "{out}");
+
+    let colored = "Apr 18 12:54 \u{1b}[0m\u{1b}[34m.\u{1b}[0m";
+    for (expected, actual) in [(colored, "Apr 18 12:54 ."), ("Apr 18 12:54 .", colored)] {
+        let (stats, out) = diff_snapshot_with_config(expected, actual, &config);
+        assert_eq!(stats.matches(), [None]);
+        assert!(out.contains("[-] Input: test"), "{out}");
+        assert!(out.contains("Colored output is not allowed"), "{out}");
+    }
+}
+
+#[test]
+fn snapshot_testing_with_exit_status_check() {
+    let config = TestConfig::new(ShellOptions::default()).check_exit_status(true);
+    let mut parsed = parsed_transcript("Hello");
+    parsed.interactions[0].exit_status = Some(ExitStatus(0));
+
+    for (status, should_match) in [
+        (Some(ExitStatus(0)), true),
+        (Some(ExitStatus(1)), false),
+        (None, false),
+    ] {
+        let mut interaction = Interaction::new(UserInput::command("test"), "Hello");
+        interaction.exit_status = status;
+        let mut reproduced = Transcript::new();
+        reproduced.add_existing_interaction(interaction);
+
+        let mut out: Vec<u8> = vec![];
+        let stats = config
+            .compare_transcripts(&mut NoColor::new(&mut out), &parsed, &reproduced)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        if should_match {
+            assert_eq!(stats.matches(), [Some(MatchKind::TextOnly)]);
+            assert!(out.contains("[+] Input: test"), "{out}");
+        } else {
+            assert_eq!(stats.matches(), [None]);
+            assert!(out.contains("[-] Input: test"), "{out}");
+            assert!(out.contains("Exit status mismatch: expected 0"), "{out}");
+        }
+    }
+
+    // Statuses are not checked if the check is disabled.
+    let config = TestConfig::new(ShellOptions::default());
+    let mut reproduced = Transcript::new();
+    reproduced.add_interaction(UserInput::command("test"), "Hello");
+    let stats = config
+        .compare_transcripts(&mut NoColor::new(vec![]), &parsed, &reproduced)
+        .unwrap();
+    assert_eq!(stats.matches(), [Some(MatchKind::TextOnly)]);
+}
+
+#[test]
+fn snapshot_testing_with_elision_marker() {
+    let config = TestConfig::new(ShellOptions::default())
+        .with_match_kind(MatchKind::Precise)
+        .with_elision_marker("[..]");
+    let expected = "Started\n[..]\n\u{1b}[32mDone\u{1b}[0m";
+
+    let actual = "Started\nElapsed: \u{1b}[1m1.23s\u{1b}[0m\n\u{1b}[32mDone\u{1b}[0m";
+    let (stats, out) = diff_snapshot_with_config(expected, actual, &config);
+    assert_eq!(stats.matches(), [Some(MatchKind::Precise)]);
+    assert!(out.contains("[+] Input: test"), "{out}");
+
+    // Styles of non-elided lines are still compared.
+    let actual = "Started\nElapsed: 1.23s\n\u{1b}[31mDone\u{1b}[0m";
+    let (stats, out) = diff_snapshot_with_config(expected, actual, &config);
+    assert_eq!(stats.matches(), [Some(MatchKind::TextOnly)]);
+    assert!(out.contains("[#] Input: test"), "{out}");
+
+    // Each marker corresponds to exactly one line.
+    let actual = "Started\nElapsed: 1.23s\nMore\n\u{1b}[32mDone\u{1b}[0m";
+    let (stats, _) = diff_snapshot_with_config(expected, actual, &config);
+    assert_eq!(stats.matches(), [None]);
+
+    let actual = "Starting\nElapsed: 1.23s\n\u{1b}[32mDone\u{1b}[0m";
+    let (stats, _) = diff_snapshot_with_config(expected, actual, &config);
+    assert_eq!(stats.matches(), [None]);
+}
+
+fn diff_with_context(expected: String, actual: &str, context: usize) -> String {
+    let expected_capture = Captured::from(expected);
+    let parsed = Transcript {
+        interactions: vec![Interaction {
+            input: UserInput::command("test"),
+            output: Parsed {
+                plaintext: expected_capture.to_plaintext().unwrap(),
+                color_spans: ColorSpan::parse(expected_capture.as_ref(), None).unwrap(),
+                html: expected_capture.to_html().unwrap(),
+            },
+            stderr: None,
+            exit_status: None,
+            duration: None,
+            highlighted_lines: vec![],
+            is_truncated: false,
+            folded_lines: vec![],
+            #[cfg(feature = "svg")]
+            palette: None,
+        }],
+    };
+    let mut reproduced = Transcript::new();
+    reproduced.add_interaction(UserInput::command("test"), actual);
+
+    let mut out: Vec<u8> = vec![];
+    let stats = TestConfig::new(ShellOptions::default())
+        .with_diff_context(context)
+        .compare_transcripts(&mut NoColor::new(&mut out), &parsed, &reproduced)
+        .unwrap();
+    assert_eq!(stats.matches(), [None]);
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn diff_with_limited_context() {
+    let expected = (0..20)
+        .map(|i| format!("line #{i}\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    let actual = expected.replace("line #10\n", "modified line\n");
+    let out = diff_with_context(expected, &actual, 2);
+
+    assert!(out.contains("... (8 unchanged lines)"), "{out}");
+    assert!(out.contains("... (7 unchanged lines)"), "{out}");
+    assert!(out.contains("line #8"), "{out}");
+    assert!(out.contains("line #12"), "{out}");
+    assert!(!out.contains("line #7"), "{out}");
+    assert!(!out.contains("line #13"), "{out}");
+}
+
+#[test]
+fn diff_with_limited_context_and_multiple_hunks() {
+    let expected = (0..30)
+        .map(|i| format!("line #{i}\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    let actual = expected
+        .replace("line #5\n", "modified line\n")
+        .replace("line #20\n", "")
+        .replace("line #25\n", "line #25\ninserted line\n");
+    let out = diff_with_context(expected, &actual, 1);
+
+    assert!(out.contains("... (4 unchanged lines)"), "{out}");
+    assert!(out.contains("... (12 unchanged lines)"), "{out}");
+    assert_eq!(out.matches("... (3 unchanged lines)").count(), 2, "{out}");
+    for shown_line in [4, 6, 19, 21, 25, 26] {
+        assert!(out.contains(&format!("line #{shown_line}\n")), "{out}");
+    }
+    for elided_line in [3, 7, 12, 18, 22, 24, 27] {
+        assert!(!out.contains(&format!("line #{elided_line}\n")), "{out}");
+    }
+}
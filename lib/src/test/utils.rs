@@ -1,4 +1,6 @@
 use std::{
+    env,
+    ffi::OsString,
     io::{self, IsTerminal, Write},
     str,
 };
@@ -101,6 +103,28 @@ impl Write for PrintlnWriter {
     }
 }
 
+/// Chooses coloring based on the `NO_COLOR`, `CLICOLOR_FORCE` and `CLICOLOR` env vars
+/// as per [the corresponding conventions](https://bixense.com/clicolors/). If none of these vars
+/// affect coloring, returns [`ColorChoice::Auto`], i.e., coloring depends on whether stdout
+/// is a terminal.
+pub(super) fn color_choice_from_env() -> ColorChoice {
+    color_choice_from_vars(|name| env::var_os(name))
+}
+
+fn color_choice_from_vars(var: impl Fn(&str) -> Option<OsString>) -> ColorChoice {
+    let is_set = |name| var(name).is_some_and(|value| !value.is_empty() && value != "0");
+
+    if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        ColorChoice::Never
+    } else if is_set("CLICOLOR_FORCE") {
+        ColorChoice::Always
+    } else if var("CLICOLOR").is_some_and(|value| value == "0") {
+        ColorChoice::Never
+    } else {
+        ColorChoice::Auto
+    }
+}
+
 /// `PrintlnWriter` extension with ANSI color support.
 pub(super) enum ColorPrintlnWriter {
     NoColor(NoColor<PrintlnWriter>),
@@ -191,6 +215,33 @@ mod tests {
         });
     }
 
+    #[test]
+    fn choosing_color_from_env_vars() {
+        let choose = |vars: &[(&str, &str)]| {
+            color_choice_from_vars(|name| {
+                vars.iter()
+                    .find(|(var_name, _)| *var_name == name)
+                    .map(|(_, value)| OsString::from(value))
+            })
+        };
+
+        assert_eq!(choose(&[]), ColorChoice::Auto);
+        assert_eq!(choose(&[("NO_COLOR", "1")]), ColorChoice::Never);
+        assert_eq!(choose(&[("NO_COLOR", "")]), ColorChoice::Auto);
+        assert_eq!(choose(&[("CLICOLOR", "0")]), ColorChoice::Never);
+        assert_eq!(choose(&[("CLICOLOR", "1")]), ColorChoice::Auto);
+        assert_eq!(choose(&[("CLICOLOR_FORCE", "1")]), ColorChoice::Always);
+        assert_eq!(choose(&[("CLICOLOR_FORCE", "0")]), ColorChoice::Auto);
+        assert_eq!(
+            choose(&[("CLICOLOR_FORCE", "1"), ("CLICOLOR", "0")]),
+            ColorChoice::Always
+        );
+        assert_eq!(
+            choose(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]),
+            ColorChoice::Never
+        );
+    }
+
     #[test]
     fn indenting_writer_basics() -> io::Result<()> {
         let mut buffer = vec![];
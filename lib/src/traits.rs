@@ -16,6 +16,28 @@ pub trait ConfigureCommand {
     fn current_dir(&mut self, dir: &Path);
     /// Sets an environment variable.
     fn env(&mut self, name: &str, value: &OsStr);
+    /// Clears all environment variables, including ones inherited from the parent process
+    /// and ones previously set via [`Self::env()`]. This is used for
+    /// [hermetic captures](crate::ShellOptions::clear_env()).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command doesn't support clearing the environment.
+    /// The default implementation always returns an error with [`io::ErrorKind::Unsupported`]
+    /// kind, so that a command not overriding this method cannot silently inherit
+    /// the parent environment.
+    fn env_clear(&mut self) -> io::Result<()> {
+        let message = "clearing the environment is not supported by the command";
+        Err(io::Error::new(io::ErrorKind::Unsupported, message))
+    }
+
+    /// Returns the name of the executed program, if it is known and is valid UTF-8.
+    /// This is used for diagnostics, e.g., in errors and tracing spans.
+    ///
+    /// The default implementation returns `None`.
+    fn program_name(&self) -> Option<&str> {
+        None
+    }
 }
 
 impl ConfigureCommand for Command {
@@ -26,6 +48,15 @@ impl ConfigureCommand for Command {
     fn env(&mut self, name: &str, value: &OsStr) {
         self.env(name, value);
     }
+
+    fn env_clear(&mut self) -> io::Result<()> {
+        self.env_clear();
+        Ok(())
+    }
+
+    fn program_name(&self) -> Option<&str> {
+        self.get_program().to_str()
+    }
 }
 
 /// Encapsulates spawning and sending inputs / receiving outputs from the shell.
@@ -86,6 +117,45 @@ pub trait ShellProcess {
     fn is_echoing(&self) -> bool {
         false
     }
+
+    /// Interrupts the program currently executing in the shell, as if the user has pressed Ctrl+C.
+    /// This is used to replay [interrupt inputs](crate::UserInput::interrupt()).
+    ///
+    /// The default implementation writes the ETX byte (`\x03`) to `stdin`. This works for shells
+    /// connected to a terminal, which translates the byte to an interrupt signal.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the process cannot be interrupted.
+    fn interrupt(&mut self, stdin: &mut dyn io::Write) -> io::Result<()> {
+        stdin.write_all(b"\x03")?;
+        stdin.flush()
+    }
+
+    /// Signals the end of input to the program currently executing in the shell, as if the user
+    /// has pressed Ctrl+D. This is used after writing [stdin data](crate::UserInput::with_stdin_data())
+    /// for an input.
+    ///
+    /// The default implementation writes the EOT byte (`\x04`) to `stdin`. This works for shells
+    /// connected to a terminal on Unix, which translates the byte to the end of input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the end of input cannot be signaled.
+    fn send_eof(&mut self, stdin: &mut dyn io::Write) -> io::Result<()> {
+        stdin.write_all(b"\x04")?;
+        stdin.flush()
+    }
+
+    /// Returns `true` if [signaling the end of input](Self::send_eof()) is supported.
+    /// This is checked before recording a transcript with
+    /// [stdin data](crate::UserInput::with_stdin_data()), so that no inputs are sent to a shell
+    /// that cannot process them.
+    ///
+    /// The default implementation returns `true`.
+    fn supports_eof(&self) -> bool {
+        true
+    }
 }
 
 /// Wrapper for spawned shell and related I/O returned by [`SpawnShell::spawn_shell()`].
@@ -162,6 +232,51 @@ impl ShellProcess for Child {
         }
         Ok(())
     }
+
+    /// Sends `SIGINT` to the direct children of the shell process using `pkill`, since OS pipes
+    /// do not translate Ctrl+C into a signal.
+    #[cfg(unix)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(_stdin), err)
+    )]
+    fn interrupt(&mut self, _stdin: &mut dyn io::Write) -> io::Result<()> {
+        let status = Command::new("pkill")
+            .args(["-INT", "-P", &self.id().to_string()])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        // `pkill` exits with 1 if no processes were matched; we don't consider this an error.
+        if matches!(status.code(), Some(0 | 1)) {
+            Ok(())
+        } else {
+            let message =
+                format!("failed interrupting shell children: `pkill` exited with {status}");
+            Err(io::Error::other(message))
+        }
+    }
+
+    /// Interrupting programs executing in a shell connected via OS pipes is not supported
+    /// on non-Unix platforms.
+    #[cfg(not(unix))]
+    fn interrupt(&mut self, _stdin: &mut dyn io::Write) -> io::Result<()> {
+        let message = "interrupting programs is not supported for shells connected via OS pipes \
+            on this platform; consider using a PTY";
+        Err(io::Error::new(io::ErrorKind::Unsupported, message))
+    }
+
+    /// Signaling the end of input is not supported for shells connected via OS pipes,
+    /// since it would require closing the shell input.
+    fn send_eof(&mut self, _stdin: &mut dyn io::Write) -> io::Result<()> {
+        let message = "signaling end of input is not supported for shells connected via OS pipes; \
+            consider using a PTY";
+        Err(io::Error::new(io::ErrorKind::Unsupported, message))
+    }
+
+    fn supports_eof(&self) -> bool {
+        false
+    }
 }
 
 /// Wrapper that allows configuring echoing of the shell process.
@@ -188,6 +303,14 @@ impl<S: ConfigureCommand> ConfigureCommand for Echoing<S> {
     fn env(&mut self, name: &str, value: &OsStr) {
         self.inner.env(name, value);
     }
+
+    fn env_clear(&mut self) -> io::Result<()> {
+        self.inner.env_clear()
+    }
+
+    fn program_name(&self) -> Option<&str> {
+        self.inner.program_name()
+    }
 }
 
 impl<S: SpawnShell> SpawnShell for Echoing<S> {
@@ -220,4 +343,16 @@ impl<S: ShellProcess> ShellProcess for Echoing<S> {
     fn is_echoing(&self) -> bool {
         self.is_echoing
     }
+
+    fn interrupt(&mut self, stdin: &mut dyn io::Write) -> io::Result<()> {
+        self.inner.interrupt(stdin)
+    }
+
+    fn send_eof(&mut self, stdin: &mut dyn io::Write) -> io::Result<()> {
+        self.inner.send_eof(stdin)
+    }
+
+    fn supports_eof(&self) -> bool {
+        self.inner.supports_eof()
+    }
 }
@@ -1,12 +1,31 @@
 //! Misc utils.
 
-use std::{borrow::Cow, fmt::Write as WriteStr, io, str};
+use std::{borrow::Cow, fmt, fmt::Write as WriteStr, io, str};
 
-#[cfg(any(feature = "svg", feature = "test"))]
-pub use self::rgb_color::RgbColor;
 #[cfg(feature = "svg")]
 pub use self::rgb_color::RgbColorParseError;
 
+/// RGB color with 8-bit channels.
+///
+/// A color [can be parsed](str::FromStr) from a hex string like `#fed` or `#de382b`, or from
+/// the CSS functional notation like `rgb(222, 56, 43)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RgbColor(pub u8, pub u8, pub u8);
+
+impl fmt::LowerHex for RgbColor {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Outputs the color in the `#rrggbb` format, same as the [`LowerHex`](fmt::LowerHex)
+/// implementation.
+impl fmt::Display for RgbColor {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, formatter)
+    }
+}
+
 /// Adapter for `dyn fmt::Write` that implements `io::Write`.
 pub(crate) struct WriteAdapter<'a> {
     inner: &'a mut dyn WriteStr,
@@ -62,17 +81,7 @@ pub(crate) fn is_recoverable_kill_error(err: &io::Error) -> bool {
 mod rgb_color {
     use std::{error::Error as StdError, fmt, num::ParseIntError, str::FromStr};
 
-    /// RGB color with 8-bit channels.
-    ///
-    /// A color [can be parsed](FromStr) from a hex string like `#fed` or `#de382b`.
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub struct RgbColor(pub u8, pub u8, pub u8);
-
-    impl fmt::LowerHex for RgbColor {
-        fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(formatter, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
-        }
-    }
+    use super::RgbColor;
 
     /// Errors that can occur when [parsing](FromStr) an [`RgbColor`] from a string.
     #[derive(Debug)]
@@ -88,6 +97,12 @@ mod rgb_color {
         IncorrectLen(usize),
         /// Error parsing color channel value.
         IncorrectDigit(ParseIntError),
+        /// The color in the `rgb()` functional notation does not have exactly 3 comma-separated
+        /// channels.
+        IncorrectRgbNotation,
+        /// Error parsing a decimal color channel value in the `rgb()` functional notation
+        /// (e.g., the value is not in the 0..=255 range).
+        IncorrectChannel(ParseIntError),
     }
 
     impl fmt::Display for RgbColorParseError {
@@ -100,6 +115,15 @@ mod rgb_color {
                     "unexpected byte length {len} of color string, expected 4 or 7"
                 ),
                 Self::IncorrectDigit(err) => write!(formatter, "error parsing hex digit: {err}"),
+                Self::IncorrectRgbNotation => {
+                    formatter.write_str("expected 3 comma-separated channels in `rgb()` notation")
+                }
+                Self::IncorrectChannel(err) => {
+                    write!(
+                        formatter,
+                        "error parsing color channel (expected 0..=255): {err}"
+                    )
+                }
             }
         }
     }
@@ -107,17 +131,93 @@ mod rgb_color {
     impl StdError for RgbColorParseError {
         fn source(&self) -> Option<&(dyn StdError + 'static)> {
             match self {
-                Self::IncorrectDigit(err) => Some(err),
+                Self::IncorrectDigit(err) | Self::IncorrectChannel(err) => Some(err),
                 _ => None,
             }
         }
     }
 
+    impl RgbColor {
+        /// Reference colors used in [`Self::nearest_named()`]. Colors are taken from the xterm
+        /// palette; intense colors are marked with `*`, as in style diffs.
+        #[cfg(feature = "svg")]
+        const NAMED_COLORS: [(&'static str, Self); 16] = [
+            ("black", Self(0, 0, 0)),
+            ("red", Self(0xcd, 0, 0)),
+            ("green", Self(0, 0xcd, 0)),
+            ("yellow", Self(0xcd, 0xcd, 0)),
+            ("blue", Self(0, 0, 0xee)),
+            ("magenta", Self(0xcd, 0, 0xcd)),
+            ("cyan", Self(0, 0xcd, 0xcd)),
+            ("white", Self(0xe5, 0xe5, 0xe5)),
+            ("black*", Self(0x7f, 0x7f, 0x7f)),
+            ("red*", Self(0xff, 0, 0)),
+            ("green*", Self(0, 0xff, 0)),
+            ("yellow*", Self(0xff, 0xff, 0)),
+            ("blue*", Self(0x5c, 0x5c, 0xff)),
+            ("magenta*", Self(0xff, 0, 0xff)),
+            ("cyan*", Self(0, 0xff, 0xff)),
+            ("white*", Self(0xff, 0xff, 0xff)),
+        ];
+
+        /// Returns the name of the closest of 16 base ANSI colors (as defined by the xterm palette),
+        /// e.g. `"red"`. Names of intense colors are suffixed with `*`, e.g. `"red*"`.
+        /// Closeness is measured as the Euclidean distance between colors in the RGB space.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// # use term_transcript::svg::RgbColor;
+        /// assert_eq!(RgbColor(0xde, 0x38, 0x2b).nearest_named(), "red");
+        /// assert_eq!(RgbColor(0xfa, 0xfa, 0xfa).nearest_named(), "white*");
+        /// ```
+        #[cfg(feature = "svg")]
+        #[allow(clippy::missing_panics_doc)] // Panic should never be triggered
+        pub fn nearest_named(self) -> &'static str {
+            let distance = |other: Self| {
+                [(self.0, other.0), (self.1, other.1), (self.2, other.2)]
+                    .into_iter()
+                    .map(|(x, y)| u32::from(x.abs_diff(y)).pow(2))
+                    .sum::<u32>()
+            };
+            Self::NAMED_COLORS
+                .iter()
+                .min_by_key(|(_, color)| distance(*color))
+                .map(|(name, _)| *name)
+                .unwrap()
+            // ^ `unwrap()` is safe: the list of named colors is non-empty
+        }
+
+        /// Parses the color from the CSS functional notation, e.g. `rgb(222, 56, 43)`.
+        fn from_rgb_notation(channels: &str) -> Result<Self, RgbColorParseError> {
+            let mut channels = channels.split(',').map(|channel| {
+                channel
+                    .trim()
+                    .parse::<u8>()
+                    .map_err(RgbColorParseError::IncorrectChannel)
+            });
+            let (Some(r), Some(g), Some(b), None) = (
+                channels.next(),
+                channels.next(),
+                channels.next(),
+                channels.next(),
+            ) else {
+                return Err(RgbColorParseError::IncorrectRgbNotation);
+            };
+            Ok(Self(r?, g?, b?))
+        }
+    }
+
     impl FromStr for RgbColor {
         type Err = RgbColorParseError;
 
         fn from_str(s: &str) -> Result<Self, Self::Err> {
-            if s.is_empty() || s.as_bytes()[0] != b'#' {
+            if let Some(channels) = s
+                .strip_prefix("rgb(")
+                .and_then(|rest| rest.strip_suffix(')'))
+            {
+                Self::from_rgb_notation(channels)
+            } else if s.is_empty() || s.as_bytes()[0] != b'#' {
                 Err(RgbColorParseError::NoHashPrefix)
             } else if s.len() == 4 {
                 if !s.is_ascii() {
@@ -164,6 +264,35 @@ mod tests {
         assert_eq!((r, g, b), (0xc0, 0xff, 0xee));
     }
 
+    #[test]
+    fn parsing_color_in_rgb_notation() {
+        let RgbColor(r, g, b) = "rgb(222, 56, 43)".parse().unwrap();
+        assert_eq!((r, g, b), (222, 56, 43));
+        let RgbColor(r, g, b) = "rgb(0,255,  7 )".parse().unwrap();
+        assert_eq!((r, g, b), (0, 255, 7));
+    }
+
+    #[test]
+    fn displaying_color() {
+        let color = RgbColor(0xde, 0x38, 0x2b);
+        assert_eq!(color.to_string(), "#de382b");
+        assert_eq!(color.to_string(), format!("{color:x}"));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn getting_nearest_named_color() {
+        assert_eq!(RgbColor(0, 0, 0).nearest_named(), "black");
+        assert_eq!(RgbColor(0x10, 0x12, 0x14).nearest_named(), "black");
+        assert_eq!(RgbColor(0xc0, 0x10, 0x10).nearest_named(), "red");
+        assert_eq!(RgbColor(0xff, 0x20, 0x20).nearest_named(), "red*");
+        assert_eq!(RgbColor(0x10, 0xc8, 0x20).nearest_named(), "green");
+        assert_eq!(RgbColor(0x10, 0x10, 0xe0).nearest_named(), "blue");
+        assert_eq!(RgbColor(0x80, 0x80, 0x80).nearest_named(), "black*");
+        assert_eq!(RgbColor(0xe0, 0xe0, 0xe0).nearest_named(), "white");
+        assert_eq!(RgbColor(0xff, 0xff, 0xfe).nearest_named(), "white*");
+    }
+
     #[test]
     fn errors_parsing_color() {
         let err = "123".parse::<RgbColor>().unwrap_err();
@@ -174,5 +303,15 @@ mod tests {
         assert_matches!(err, RgbColorParseError::NotAscii);
         let err = "#coffee".parse::<RgbColor>().unwrap_err();
         assert_matches!(err, RgbColorParseError::IncorrectDigit(_));
+
+        let err = "rgb(1, 2)".parse::<RgbColor>().unwrap_err();
+        assert_matches!(err, RgbColorParseError::IncorrectRgbNotation);
+        let err = "rgb(1, 2, 3, 4)".parse::<RgbColor>().unwrap_err();
+        assert_matches!(err, RgbColorParseError::IncorrectRgbNotation);
+        let err = "rgb(1, 256, 3)".parse::<RgbColor>().unwrap_err();
+        assert_matches!(err, RgbColorParseError::IncorrectChannel(_));
+        let err = "rgb(1, -2, 3)".parse::<RgbColor>().unwrap_err();
+        assert_matches!(err, RgbColorParseError::IncorrectChannel(_));
+        assert!(err.to_string().contains("0..=255"), "{err}");
     }
 }
@@ -1,26 +1,20 @@
 //! `HtmlWriter` and related types.
 
-use std::{fmt, io};
+use std::{fmt, io, str};
 
 use termcolor::{ColorSpec, WriteColor};
 
 use super::{
-    fmt_to_io_error, IndexOrRgb, LineBreak, LineSplitter, StyledSpan, WriteLines, WriteStr,
+    fmt_to_io_error, IndexOrRgb, LineBreak, LineSplitter, SpanStyle, StyledSpan, WriteLines,
+    WriteStr,
 };
+use crate::utils::RgbColor;
 
 impl StyledSpan {
-    fn set_html_bg(&mut self, spec: &ColorSpec) -> io::Result<()> {
-        use fmt::Write as _;
-
-        let mut back_color_class = String::with_capacity(4);
-        back_color_class.push_str("bg");
-        let back_color = spec.bg().map(|&color| IndexOrRgb::new(color)).transpose()?;
-        match back_color {
+    fn set_html_bg(&mut self, style: &SpanStyle) {
+        match style.bg {
             Some(IndexOrRgb::Index(idx)) => {
-                let final_idx = if spec.intense() { idx | 8 } else { idx };
-                write!(&mut back_color_class, "{final_idx}").unwrap();
-                // ^-- `unwrap` is safe; writing to a string never fails.
-                self.classes.push(back_color_class);
+                self.classes.push(format!("bg{idx}"));
             }
             Some(IndexOrRgb::Rgb(r, g, b)) => {
                 self.styles
@@ -28,7 +22,6 @@ impl StyledSpan {
             }
             None => { /* Do nothing. */ }
         }
-        Ok(())
     }
 }
 
@@ -40,6 +33,9 @@ pub(crate) struct HtmlWriter<'a> {
     output: &'a mut dyn fmt::Write,
     is_colored: bool,
     line_splitter: Option<LineSplitter>,
+    show_whitespace: bool,
+    grid_layout: bool,
+    grayscale_ramp: Option<[RgbColor; 24]>,
 }
 
 impl<'a> HtmlWriter<'a> {
@@ -48,15 +44,59 @@ impl<'a> HtmlWriter<'a> {
             output,
             is_colored: false,
             line_splitter: max_width.map(LineSplitter::new),
+            show_whitespace: false,
+            grid_layout: false,
+            grayscale_ramp: None,
         }
     }
 
+    /// Wraps spaces and tabs into elements so that they can be visualized with CSS.
+    /// The whitespace chars themselves are retained, so the text remains copyable.
+    pub fn with_visible_whitespace(mut self, show_whitespace: bool) -> Self {
+        self.show_whitespace = show_whitespace;
+        self
+    }
+
+    /// Wraps box-drawing and block chars into elements so that they can be aligned
+    /// to the monospace grid with CSS.
+    pub fn with_grid_layout(mut self, grid_layout: bool) -> Self {
+        self.grid_layout = grid_layout;
+        self
+    }
+
+    /// Overrides the colors of the 8-bit grayscale ramp (indices 232..=255).
+    pub fn with_grayscale_ramp(mut self, grayscale_ramp: Option<[RgbColor; 24]>) -> Self {
+        self.grayscale_ramp = grayscale_ramp;
+        self
+    }
+
     fn write_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
-        let mut span = StyledSpan::new(spec, "color")?;
-        span.set_html_bg(spec)?;
+        let style = SpanStyle::new(spec, self.grayscale_ramp.as_ref())?;
+        let mut span = StyledSpan::new(&style, "color");
+        span.set_html_bg(&style);
         span.write_tag(self, "span")?;
         Ok(())
     }
+
+    /// Checks whether `ch` is a box-drawing (U+2500..=U+257F) or a block (U+2580..=U+259F) char.
+    fn is_grid_char(ch: char) -> bool {
+        matches!(ch, '\u{2500}'..='\u{259f}')
+    }
+
+    fn write_with_grid(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        let text = str::from_utf8(buffer)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let mut last_grid_char = 0;
+        for (i, ch) in text.char_indices() {
+            if Self::is_grid_char(ch) {
+                self.io_write(&buffer[last_grid_char..i], false)?;
+                self.write_html_entity(&format!("<b class=\"grid\">{ch}</b>"))?;
+                last_grid_char = i + ch.len_utf8();
+            }
+        }
+        self.io_write(&buffer[last_grid_char..], false)?;
+        Ok(buffer.len())
+    }
 }
 
 impl WriteStr for HtmlWriter<'_> {
@@ -77,11 +117,28 @@ impl WriteLines for HtmlWriter<'_> {
     fn write_new_line(&mut self, _char_width: usize) -> io::Result<()> {
         self.write_str("\n")
     }
+
+    fn shows_whitespace(&self) -> bool {
+        self.show_whitespace
+    }
+
+    fn write_visible_whitespace(&mut self, byte: u8) -> io::Result<()> {
+        let markup = if byte == b'\t' {
+            "<b class=\"tab\">\t</b>"
+        } else {
+            r#"<b class="space"> </b>"#
+        };
+        self.write_html_entity(markup)
+    }
 }
 
 impl io::Write for HtmlWriter<'_> {
     fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
-        self.io_write(buffer, false)
+        if self.grid_layout {
+            self.write_with_grid(buffer)
+        } else {
+            self.io_write(buffer, false)
+        }
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -5,6 +5,8 @@ use std::{fmt, io, str};
 use termcolor::{Color, ColorSpec};
 use unicode_width::UnicodeWidthChar;
 
+use crate::utils::RgbColor;
+
 mod html;
 #[cfg(feature = "svg")]
 mod svg;
@@ -19,6 +21,74 @@ fn fmt_to_io_error(err: fmt::Error) -> io::Error {
     io::Error::new(io::ErrorKind::Other, err)
 }
 
+/// Text attribute: `ColorSpec` getter, the CSS class used by the default templates,
+/// and the equivalent CSS declaration.
+type TextAttribute = (fn(&ColorSpec) -> bool, &'static str, &'static str);
+
+const TEXT_ATTRIBUTES: [TextAttribute; 4] = [
+    (ColorSpec::bold, "bold", "font-weight: bold"),
+    (ColorSpec::dimmed, "dimmed", "opacity: 0.7"),
+    (ColorSpec::italic, "italic", "font-style: italic"),
+    (
+        ColorSpec::underline,
+        "underline",
+        "text-decoration: underline",
+    ),
+];
+
+/// Text style resolved from a [`ColorSpec`]. Indexed colors are normalized, with
+/// the intensity flag applied to base colors.
+#[derive(Debug)]
+struct SpanStyle {
+    /// Pairs of CSS classes and declarations from [`TEXT_ATTRIBUTES`].
+    attributes: Vec<(&'static str, &'static str)>,
+    fg: Option<IndexOrRgb>,
+    bg: Option<IndexOrRgb>,
+}
+
+impl SpanStyle {
+    fn new(spec: &ColorSpec, grayscale_ramp: Option<&[RgbColor; 24]>) -> io::Result<Self> {
+        let resolve_color = |&color: &Color| {
+            let color = IndexOrRgb::new(color, grayscale_ramp)?;
+            Ok::<_, io::Error>(match color {
+                IndexOrRgb::Index(idx) if spec.intense() => IndexOrRgb::Index(idx | 8),
+                _ => color,
+            })
+        };
+
+        Ok(Self {
+            attributes: TEXT_ATTRIBUTES
+                .iter()
+                .filter(|(is_set, ..)| is_set(spec))
+                .map(|&(_, class, declaration)| (class, declaration))
+                .collect(),
+            fg: spec.fg().map(resolve_color).transpose()?,
+            bg: spec.bg().map(resolve_color).transpose()?,
+        })
+    }
+
+    #[cfg(feature = "svg")]
+    fn to_css(&self, palette: &[RgbColor; 16]) -> String {
+        let resolve_color = |color| match color {
+            IndexOrRgb::Index(idx) => palette[usize::from(idx)],
+            IndexOrRgb::Rgb(r, g, b) => RgbColor(r, g, b),
+        };
+
+        let mut declarations: Vec<_> = self
+            .attributes
+            .iter()
+            .map(|&(_, declaration)| declaration.to_owned())
+            .collect();
+        if let Some(color) = self.fg {
+            declarations.push(format!("color: {:x}", resolve_color(color)));
+        }
+        if let Some(color) = self.bg {
+            declarations.push(format!("background: {:x}", resolve_color(color)));
+        }
+        declarations.join("; ")
+    }
+}
+
 /// HTML `<span>` / SVG `<tspan>` containing styling info.
 #[derive(Debug)]
 struct StyledSpan {
@@ -27,43 +97,25 @@ struct StyledSpan {
 }
 
 impl StyledSpan {
-    fn new(spec: &ColorSpec, fg_property: &str) -> io::Result<Self> {
-        let mut classes = vec![];
-        if spec.bold() {
-            classes.push("bold".to_owned());
-        }
-        if spec.dimmed() {
-            classes.push("dimmed".to_owned());
-        }
-        if spec.italic() {
-            classes.push("italic".to_owned());
-        }
-        if spec.underline() {
-            classes.push("underline".to_owned());
-        }
-
+    fn new(style: &SpanStyle, fg_property: &str) -> Self {
         let mut this = Self {
-            classes,
+            classes: style
+                .attributes
+                .iter()
+                .map(|&(class, _)| class.to_owned())
+                .collect(),
             styles: vec![],
         };
-        if let Some(color) = spec.fg() {
-            let color = IndexOrRgb::new(*color)?;
-            this.set_fg(color, spec.intense(), &[fg_property]);
+        if let Some(color) = style.fg {
+            this.set_fg(color, &[fg_property]);
         }
-        Ok(this)
+        this
     }
 
-    fn set_fg(&mut self, color: IndexOrRgb, intense: bool, fg_properties: &[&str]) {
-        use fmt::Write as _;
-
-        let mut fore_color_class = String::with_capacity(4);
-        fore_color_class.push_str("fg");
+    fn set_fg(&mut self, color: IndexOrRgb, fg_properties: &[&str]) {
         match color {
             IndexOrRgb::Index(idx) => {
-                let final_idx = if intense { idx | 8 } else { idx };
-                write!(&mut fore_color_class, "{final_idx}").unwrap();
-                // ^-- `unwrap` is safe; writing to a string never fails.
-                self.classes.push(fore_color_class);
+                self.classes.push(format!("fg{idx}"));
             }
             IndexOrRgb::Rgb(r, g, b) => {
                 for property in fg_properties {
@@ -101,6 +153,19 @@ impl StyledSpan {
     }
 }
 
+/// Converts `spec` to CSS declarations separated by `; ` (e.g., `font-weight: bold; color: #ff0000`).
+/// Indexed colors are resolved using `palette` and `grayscale_ramp`, same as when writing HTML / SVG.
+/// If `spec` contains unsupported colors, returns an empty string.
+#[cfg(feature = "svg")]
+pub(crate) fn style_to_css(
+    spec: &ColorSpec,
+    palette: &[RgbColor; 16],
+    grayscale_ramp: Option<&[RgbColor; 24]>,
+) -> String {
+    SpanStyle::new(spec, grayscale_ramp)
+        .map_or_else(|_| String::new(), |style| style.to_css(palette))
+}
+
 /// Analogue of `std::fmt::Write`, but with `io::Error`s.
 trait WriteStr {
     fn write_str(&mut self, s: &str) -> io::Result<()>;
@@ -123,6 +188,13 @@ trait WriteLines: WriteStr {
     /// Writes a newline `\n` to this writer.
     fn write_new_line(&mut self, char_width: usize) -> io::Result<()>;
 
+    /// Checks whether spaces and tabs should be made visible.
+    fn shows_whitespace(&self) -> bool;
+
+    /// Writes the specified whitespace `byte` (a space or a tab) so that it's visible.
+    /// Only called if [`Self::shows_whitespace()`] returns `true`.
+    fn write_visible_whitespace(&mut self, byte: u8) -> io::Result<()>;
+
     /// Writes the specified text displayed to the user that should be subjected to wrapping.
     #[allow(clippy::option_if_let_else)] // false positive
     fn write_text(&mut self, s: &str) -> io::Result<()> {
@@ -164,16 +236,21 @@ trait WriteLines: WriteStr {
         let mut last_escape = 0;
         for (i, &byte) in buffer.iter().enumerate() {
             let escaped = match byte {
-                b'>' => "&gt;",
-                b'<' => "&lt;",
-                b'&' => "&amp;",
-                b' ' if convert_spaces => "\u{a0}", // non-breakable space
+                b'>' => Some("&gt;"),
+                b'<' => Some("&lt;"),
+                b'&' => Some("&amp;"),
+                b' ' | b'\t' if self.shows_whitespace() => None,
+                b' ' if convert_spaces => Some("\u{a0}"), // non-breakable space
                 _ => continue,
             };
             let saved_str = str::from_utf8(&buffer[last_escape..i])
                 .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
             self.write_text(saved_str)?;
-            self.write_html_entity(escaped)?;
+            if let Some(escaped) = escaped {
+                self.write_html_entity(escaped)?;
+            } else {
+                self.write_visible_whitespace(byte)?;
+            }
             last_escape = i + 1;
         }
 
@@ -193,7 +270,7 @@ pub(crate) enum IndexOrRgb {
 impl IndexOrRgb {
     #[allow(clippy::match_wildcard_for_single_variants)]
     // ^-- `Color` is an old-school non-exhaustive enum
-    fn new(color: Color) -> io::Result<Self> {
+    fn new(color: Color, grayscale_ramp: Option<&[RgbColor; 24]>) -> io::Result<Self> {
         Ok(match color {
             Color::Black => Self::index(0),
             Color::Red => Self::index(1),
@@ -203,7 +280,7 @@ impl IndexOrRgb {
             Color::Magenta => Self::index(5),
             Color::Cyan => Self::index(6),
             Color::White => Self::index(7),
-            Color::Ansi256(idx) => Self::indexed_color(idx),
+            Color::Ansi256(idx) => Self::indexed_color(idx, grayscale_ramp),
             Color::Rgb(r, g, b) => Self::Rgb(r, g, b),
             _ => return Err(io::Error::new(io::ErrorKind::Other, "Unsupported color")),
         })
@@ -214,7 +291,9 @@ impl IndexOrRgb {
         Self::Index(value)
     }
 
-    pub fn indexed_color(index: u8) -> Self {
+    /// Expands an indexed 8-bit color. If `grayscale_ramp` is specified, it overrides
+    /// the colors of the grayscale ramp (indices 232..=255).
+    pub fn indexed_color(index: u8, grayscale_ramp: Option<&[RgbColor; 24]>) -> Self {
         match index {
             0..=15 => Self::index(index),
 
@@ -227,6 +306,10 @@ impl IndexOrRgb {
             }
 
             _ => {
+                if let Some(ramp) = grayscale_ramp {
+                    let RgbColor(r, g, b) = ramp[usize::from(index - 232)];
+                    return Self::Rgb(r, g, b);
+                }
                 let gray = 10 * (index - 232) + 8;
                 Self::Rgb(gray, gray, gray)
             }
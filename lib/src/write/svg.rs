@@ -1,12 +1,13 @@
-use std::{fmt, io, iter, mem, str};
+use std::{io, iter, mem, str};
 
 use serde::Serialize;
 use termcolor::{ColorSpec, WriteColor};
 
-use super::{IndexOrRgb, LineBreak, LineSplitter, StyledSpan, WriteLines, WriteStr};
+use super::{IndexOrRgb, LineBreak, LineSplitter, SpanStyle, StyledSpan, WriteLines, WriteStr};
+use crate::utils::RgbColor;
 
 impl StyledSpan {
-    fn for_bg(color: IndexOrRgb, intense: bool, dimmed: bool) -> Self {
+    fn for_bg(color: IndexOrRgb, dimmed: bool) -> Self {
         let classes = if dimmed {
             vec!["dimmed".to_owned()]
         } else {
@@ -16,7 +17,7 @@ impl StyledSpan {
             classes,
             styles: vec![],
         };
-        this.set_fg(color, intense, &["fill", "stroke"]);
+        this.set_fg(color, &["fill", "stroke"]);
         // ^ Ideally, we'd want to add `stroke: context-fill` to the `.output-bg` selector.
         // Unfortunately, it's not supported by all viewers.
         this
@@ -27,10 +28,17 @@ impl StyledSpan {
 pub(crate) struct SvgLine {
     pub background: Option<String>,
     pub foreground: String,
+    /// Markers for visible whitespace. Rendered separately from the foreground, so that
+    /// the whitespace is retained when copying the text.
+    pub whitespace: Option<String>,
 }
 
 impl SvgLine {
-    fn new(foreground: String, background_segments: Vec<BackgroundSegment>) -> Self {
+    fn new(
+        foreground: String,
+        background_segments: Vec<BackgroundSegment>,
+        whitespace_markers: Vec<WhitespaceMarker>,
+    ) -> Self {
         let background = if let Some(segment) = background_segments.last() {
             let estimated_capacity =
                 16 * background_segments.len() + segment.start_pos + segment.char_width;
@@ -46,9 +54,40 @@ impl SvgLine {
             None
         };
 
+        let whitespace = if let Some(marker) = whitespace_markers.last() {
+            let mut whitespace = String::with_capacity(32 * whitespace_markers.len() + marker.pos);
+            let mut pos = 0;
+            for marker in whitespace_markers {
+                whitespace.extend(iter::repeat('\u{a0}').take(marker.pos - pos));
+                pos = marker.pos + 1;
+                whitespace.push_str(marker.markup());
+            }
+            Some(whitespace)
+        } else {
+            None
+        };
+
         Self {
             background,
             foreground,
+            whitespace,
+        }
+    }
+}
+
+/// Visible whitespace char in an [`SvgLine`].
+#[derive(Debug)]
+struct WhitespaceMarker {
+    pos: usize,
+    is_tab: bool,
+}
+
+impl WhitespaceMarker {
+    fn markup(&self) -> &'static str {
+        if self.is_tab {
+            r#"<tspan class="tab">→</tspan>"#
+        } else {
+            r#"<tspan class="space">·</tspan>"#
         }
     }
 }
@@ -72,48 +111,69 @@ impl BackgroundSegment {
 pub(crate) struct SvgWriter {
     output: Vec<SvgLine>,
     current_background: Vec<BackgroundSegment>,
+    current_whitespace: Vec<WhitespaceMarker>,
     current_line: String,
     current_style: Option<ColorSpec>,
     line_splitter: LineSplitter,
+    show_whitespace: bool,
+    hard_break_marker: char,
+    grayscale_ramp: Option<[RgbColor; 24]>,
+    used_colors: [bool; 16],
 }
 
 impl SvgWriter {
+    pub const DEFAULT_HARD_BREAK_MARKER: char = '↓';
+
     pub fn new(max_width: Option<usize>) -> Self {
         Self {
             output: vec![],
             current_background: vec![],
+            current_whitespace: vec![],
             current_line: String::new(),
             current_style: None,
             line_splitter: max_width.map_or_else(LineSplitter::default, LineSplitter::new),
+            show_whitespace: false,
+            hard_break_marker: Self::DEFAULT_HARD_BREAK_MARKER,
+            grayscale_ramp: None,
+            used_colors: [false; 16],
         }
     }
 
+    /// Replaces spaces and tabs with visible glyphs.
+    pub fn with_visible_whitespace(mut self, show_whitespace: bool) -> Self {
+        self.show_whitespace = show_whitespace;
+        self
+    }
+
+    /// Sets the char displayed at the end of hard-wrapped lines.
+    pub fn with_hard_break_marker(mut self, marker: char) -> Self {
+        self.hard_break_marker = marker;
+        self
+    }
+
+    /// Overrides the colors of the 8-bit grayscale ramp (indices 232..=255).
+    pub fn with_grayscale_ramp(mut self, grayscale_ramp: Option<[RgbColor; 24]>) -> Self {
+        self.grayscale_ramp = grayscale_ramp;
+        self
+    }
+
     fn write_color(&mut self, spec: ColorSpec, start_pos: usize) -> io::Result<()> {
-        use fmt::Write as _;
-
-        let mut span = StyledSpan::new(&spec, "fill")?;
-
-        let mut back_color_class = String::with_capacity(4);
-        back_color_class.push_str("bg");
-        let back_color = spec.bg().map(|&color| IndexOrRgb::new(color)).transpose()?;
-        match back_color {
-            Some(IndexOrRgb::Index(idx)) => {
-                let final_idx = if spec.intense() { idx | 8 } else { idx };
-                write!(&mut back_color_class, "{final_idx}").unwrap();
-                // ^-- `unwrap` is safe; writing to a string never fails.
-                span.classes.push(back_color_class);
+        let style = SpanStyle::new(&spec, self.grayscale_ramp.as_ref())?;
+        for color in [style.fg, style.bg].into_iter().flatten() {
+            if let IndexOrRgb::Index(idx) = color {
+                self.used_colors[usize::from(idx)] = true;
             }
-            Some(IndexOrRgb::Rgb(r, g, b)) => {
-                write!(&mut back_color_class, "#{r:02x}{g:02x}{b:02x}").unwrap();
-                span.classes.push(back_color_class);
-            }
-            None => { /* Do nothing. */ }
         }
-        if let Some(color) = back_color {
+        let mut span = StyledSpan::new(&style, "fill");
+        if let Some(color) = style.bg {
+            span.classes.push(match color {
+                IndexOrRgb::Index(idx) => format!("bg{idx}"),
+                IndexOrRgb::Rgb(r, g, b) => format!("bg#{r:02x}{g:02x}{b:02x}"),
+            });
             self.current_background.push(BackgroundSegment {
                 start_pos,
                 char_width: 0,
-                span: StyledSpan::for_bg(color, spec.intense(), spec.dimmed()),
+                span: StyledSpan::for_bg(color, spec.dimmed()),
             });
         }
 
@@ -139,11 +199,18 @@ impl SvgWriter {
         segment.char_width = current_width - segment.start_pos;
     }
 
+    /// Returns flags indicating which of the 16 base palette colors were used
+    /// in the written output, either as foreground or background.
+    pub fn used_colors(&self) -> [bool; 16] {
+        self.used_colors
+    }
+
     pub fn into_lines(mut self) -> Vec<SvgLine> {
         if self.line_splitter.current_width > 0 {
             self.output.push(SvgLine::new(
                 mem::take(&mut self.current_line),
                 mem::take(&mut self.current_background),
+                mem::take(&mut self.current_whitespace),
             ));
         }
         self.output
@@ -165,7 +232,21 @@ impl WriteLines for SvgWriter {
         const HARD_BR: &str =
             r#"<tspan class="hard-br" rotate="45" dx=".1em" dy="-.2em">↓</tspan>"#;
         match br {
-            LineBreak::Hard => self.write_str(HARD_BR)?,
+            LineBreak::Hard if self.hard_break_marker == Self::DEFAULT_HARD_BREAK_MARKER => {
+                self.write_str(HARD_BR)?;
+            }
+            LineBreak::Hard => {
+                // Custom markers are not rotated since they may be directional (e.g., `↩`).
+                let marker = match self.hard_break_marker {
+                    '<' => "&lt;".to_owned(),
+                    '>' => "&gt;".to_owned(),
+                    '&' => "&amp;".to_owned(),
+                    marker => marker.to_string(),
+                };
+                self.write_str(r#"<tspan class="hard-br" dx=".1em">"#)?;
+                self.write_str(&marker)?;
+                self.write_str("</tspan>")?;
+            }
         }
         self.write_new_line(char_width)
     }
@@ -177,6 +258,7 @@ impl WriteLines for SvgWriter {
         self.output.push(SvgLine::new(
             mem::take(&mut self.current_line),
             mem::take(&mut self.current_background),
+            mem::take(&mut self.current_whitespace),
         ));
 
         if let Some(spec) = current_style {
@@ -184,6 +266,22 @@ impl WriteLines for SvgWriter {
         }
         Ok(())
     }
+
+    fn shows_whitespace(&self) -> bool {
+        self.show_whitespace
+    }
+
+    fn write_visible_whitespace(&mut self, byte: u8) -> io::Result<()> {
+        let is_tab = byte == b'\t';
+        self.write_html_entity(if is_tab { "\t" } else { "\u{a0}" })?;
+        // The whitespace is written as a single char, so its position can be determined
+        // after writing (i.e., after a potential line break).
+        self.current_whitespace.push(WhitespaceMarker {
+            pos: self.line_splitter.current_width - 1,
+            is_tab,
+        });
+        Ok(())
+    }
 }
 
 impl io::Write for SvgWriter {
@@ -243,7 +341,9 @@ mod tests {
         let SvgLine {
             background,
             foreground,
+            whitespace,
         } = lines.pop().unwrap();
+        assert!(whitespace.is_none());
         let background = background.unwrap();
         assert_eq!(
             background,
@@ -269,7 +369,9 @@ mod tests {
         let SvgLine {
             background,
             foreground,
+            whitespace,
         } = lines.pop().unwrap();
+        assert!(whitespace.is_none());
         assert!(background.is_none());
         assert_eq!(foreground, r#"<tspan class="fg12">blue</tspan>"#);
         Ok(())
@@ -312,7 +414,9 @@ mod tests {
         let SvgLine {
             background,
             foreground,
+            whitespace,
         } = lines.pop().unwrap();
+        assert!(whitespace.is_none());
         let background = background.unwrap();
         assert_eq!(
             background,
@@ -375,6 +479,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn svg_writer_with_visible_whitespace() -> anyhow::Result<()> {
+        let mut writer = SvgWriter::new(Some(3)).with_visible_whitespace(true);
+        write!(writer, "a b\tc")?;
+
+        let lines = writer.into_lines();
+        let [first, second] = lines.as_slice() else {
+            panic!("Unexpected lines: {lines:?}");
+        };
+        assert_eq!(
+            first.foreground,
+            "a\u{a0}b<tspan class=\"hard-br\" rotate=\"45\" dx=\".1em\" dy=\"-.2em\">↓</tspan>"
+        );
+        assert_eq!(
+            first.whitespace.as_ref().unwrap(),
+            "\u{a0}<tspan class=\"space\">·</tspan>"
+        );
+        assert_eq!(second.foreground, "\tc");
+        assert_eq!(
+            second.whitespace.as_ref().unwrap(),
+            "<tspan class=\"tab\">→</tspan>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn svg_writer_with_custom_hard_break_marker() -> anyhow::Result<()> {
+        let mut writer = SvgWriter::new(Some(5)).with_hard_break_marker('<');
+        write!(writer, "Hello, world")?;
+
+        let lines = writer.into_lines();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0].foreground,
+            r#"Hello<tspan class="hard-br" dx=".1em">&lt;</tspan>"#
+        );
+        Ok(())
+    }
+
     #[test]
     fn splitting_lines_in_svg_writer() -> anyhow::Result<()> {
         let mut writer = SvgWriter::new(Some(5));
@@ -198,3 +198,13 @@ fn splitting_lines_with_newlines() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn html_writer_with_visible_whitespace() -> anyhow::Result<()> {
+    let mut buffer = String::new();
+    let mut writer = HtmlWriter::new(&mut buffer, None).with_visible_whitespace(true);
+    write!(writer, "a b\tc")?;
+
+    assert_eq!(buffer, "a<b class=\"space\"> </b>b<b class=\"tab\">\t</b>c");
+    Ok(())
+}